@@ -5,12 +5,12 @@ use std::collections::BTreeMap;
 use fj_math::Point;
 
 use crate::{
-    geometry::{CurveBoundary, GlobalPath, SurfacePath},
+    geometry::{CurveBoundary, GlobalPath, SurfaceGeometry, SurfacePath},
     objects::{Curve, Surface},
     storage::{Handle, HandleWrapper},
 };
 
-use super::{Approx, ApproxPoint, Tolerance};
+use super::{Approx, ApproxError, ApproxPoint, Tolerance};
 
 impl Approx
     for (
@@ -27,18 +27,27 @@ impl Approx
         self,
         tolerance: impl Into<Tolerance>,
         cache: &mut Self::Cache,
-    ) -> Self::Approximation {
+    ) -> Result<Self::Approximation, ApproxError> {
         let (curve, surface_path, surface, boundary) = self;
+        let tolerance = tolerance.into();
 
-        match cache.get(curve, boundary) {
+        let key = CurveApproxKey {
+            path: surface_path,
+            surface: surface.geometry(),
+            tolerance,
+        };
+
+        let approx = match cache.get(curve, key, boundary) {
             Some(approx) => approx,
             None => {
                 let approx =
-                    approx_curve(&surface_path, surface, boundary, tolerance);
+                    approx_curve(&surface_path, surface, boundary, tolerance)?;
 
-                cache.insert(curve.clone(), boundary, approx)
+                cache.insert(curve.clone(), key, boundary, approx)
             }
-        }
+        };
+
+        Ok(approx)
     }
 }
 
@@ -47,7 +56,7 @@ fn approx_curve(
     surface: &Surface,
     boundary: CurveBoundary<Point<1>>,
     tolerance: impl Into<Tolerance>,
-) -> CurveApprox {
+) -> Result<CurveApprox, ApproxError> {
     // There are different cases of varying complexity. Circles are the hard
     // part here, as they need to be approximated, while lines don't need to be.
     //
@@ -56,13 +65,11 @@ fn approx_curve(
     // in a more abstract way.
     let points = match (path, surface.geometry().u) {
         (SurfacePath::Circle(_), GlobalPath::Circle(_)) => {
-            todo!(
-                "Approximating a circle on a curved surface not supported yet."
-            )
+            return Err(ApproxError::CircleOnCurvedSurface);
         }
         (SurfacePath::Circle(_), GlobalPath::Line(_)) => {
             (path, boundary)
-                .approx_with_cache(tolerance, &mut ())
+                .approx_with_cache(tolerance, &mut ())?
                 .into_iter()
                 .map(|(point_curve, point_surface)| {
                     // We're throwing away `point_surface` here, which is a bit
@@ -94,15 +101,19 @@ fn approx_curve(
                 }));
 
             let approx_u = (surface.geometry().u, range_u)
-                .approx_with_cache(tolerance, &mut ());
+                .approx_with_cache(tolerance, &mut ())?;
 
             let mut points = Vec::new();
             for (u, _) in approx_u {
+                // The approximation is in terms of the surface's u-coordinate,
+                // but the curve coordinates are what's needed here. Those are
+                // only the same, if the line starts at `u = 0` and goes in
+                // positive direction at unit speed.
                 let t = (u.t - line.origin().u) / line.direction().u;
                 let point_surface = path.point_from_path_coords([t]);
                 let point_global =
                     surface.geometry().point_from_surface_coords(point_surface);
-                points.push((u, point_global));
+                points.push((Point::from([t]), point_global));
             }
 
             points
@@ -115,7 +126,7 @@ fn approx_curve(
             ApproxPoint::new(point_curve, point_global)
         })
         .collect();
-    CurveApprox { points }
+    Ok(CurveApprox { points })
 }
 
 /// Approximation of [`Curve`], within a specific boundary
@@ -133,51 +144,131 @@ impl CurveApprox {
 }
 
 /// Cache for curve approximations
+///
+/// Approximations are keyed by curve identity, boundary, and tolerance, as well
+/// as the path and surface that the curve is defined by. A curve can be shared
+/// by half-edges on different surfaces, and the same handle might show up on a
+/// moved or otherwise changed surface, so identity alone is not enough to tell
+/// whether a cached approximation is still valid.
+///
+/// Within the approximation of a single shape, the half-edges that share a
+/// curve must share its approximation too, even if computing it from each of
+/// their surfaces would result in slightly different points. See
+/// [`CurveApproxCache::start_shape`].
 #[derive(Default)]
 pub struct CurveApproxCache {
-    inner:
-        BTreeMap<(HandleWrapper<Curve>, CurveBoundary<Point<1>>), CurveApprox>,
+    current: BTreeMap<
+        (HandleWrapper<Curve>, CurveBoundary<Point<1>>, Tolerance),
+        CurveApprox,
+    >,
+    inner: BTreeMap<
+        (
+            HandleWrapper<Curve>,
+            CurveApproxKey,
+            CurveBoundary<Point<1>>,
+        ),
+        CachedCurveApprox,
+    >,
 }
 
+/// A cached curve approximation, and whether it was used since the last
+/// eviction
+type CachedCurveApprox = (CurveApprox, bool);
+
 impl CurveApproxCache {
     fn get(
-        &self,
+        &mut self,
         handle: &Handle<Curve>,
+        key: CurveApproxKey,
         boundary: CurveBoundary<Point<1>>,
     ) -> Option<CurveApprox> {
         let handle = HandleWrapper::from(handle.clone());
 
-        if let Some(approx) = self.inner.get(&(handle.clone(), boundary)) {
-            return Some(approx.clone());
-        }
-        if let Some(approx) = self.inner.get(&(handle, boundary.reverse())) {
-            return Some(approx.clone().reverse());
+        let current = self
+            .current
+            .get(&(handle.clone(), boundary, key.tolerance))
+            .cloned()
+            .or_else(|| {
+                self.current
+                    .get(&(handle.clone(), boundary.reverse(), key.tolerance))
+                    .map(|approx| approx.clone().reverse())
+            });
+        if let Some(approx) = current {
+            self.inner
+                .insert((handle, key, boundary), (approx.clone(), true));
+            return Some(approx);
         }
 
-        None
+        let approx = if let Some((approx, used)) =
+            self.inner.get_mut(&(handle.clone(), key, boundary))
+        {
+            *used = true;
+            approx.clone()
+        } else if let Some((approx, used)) =
+            self.inner
+                .get_mut(&(handle.clone(), key, boundary.reverse()))
+        {
+            *used = true;
+            approx.clone().reverse()
+        } else {
+            return None;
+        };
+
+        self.current
+            .insert((handle, boundary, key.tolerance), approx.clone());
+
+        Some(approx)
     }
 
     fn insert(
         &mut self,
         handle: Handle<Curve>,
+        key: CurveApproxKey,
         boundary: CurveBoundary<Point<1>>,
         approx: CurveApprox,
     ) -> CurveApprox {
         let handle = HandleWrapper::from(handle);
+
+        self.current
+            .insert((handle.clone(), boundary, key.tolerance), approx.clone());
         self.inner
-            .insert((handle, boundary), approx.clone())
-            .unwrap_or(approx)
+            .insert((handle, key, boundary), (approx.clone(), true));
+
+        approx
+    }
+
+    /// Start the approximation of a new shape
+    ///
+    /// Until this is called, approximations of a curve are shared between all
+    /// half-edges that refer to it, regardless of their path and surface.
+    pub fn start_shape(&mut self) {
+        self.current.clear();
+    }
+
+    /// Remove all approximations that weren't used since the last call
+    pub fn evict_unused(&mut self) {
+        self.inner.retain(|_, (_, used)| std::mem::take(used));
     }
 }
 
+/// Everything besides curve identity and boundary, that an approximation
+/// depends on
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+struct CurveApproxKey {
+    path: SurfacePath,
+    surface: SurfaceGeometry,
+    tolerance: Tolerance,
+}
+
 #[cfg(test)]
 mod tests {
     use std::{f64::consts::TAU, ops::Deref};
 
+    use fj_math::{Point, Scalar};
     use pretty_assertions::assert_eq;
 
     use crate::{
-        algorithms::approx::{Approx, ApproxPoint},
+        algorithms::approx::{Approx, ApproxError, ApproxPoint},
         geometry::{CurveBoundary, GlobalPath, SurfaceGeometry, SurfacePath},
         objects::{Curve, Surface},
         operations::insert::Insert,
@@ -185,7 +276,7 @@ mod tests {
     };
 
     #[test]
-    fn approx_line_on_flat_surface() {
+    fn approx_line_on_flat_surface() -> anyhow::Result<()> {
         let mut core = Instance::new();
 
         let curve = Curve::new().insert(&mut core.services);
@@ -195,14 +286,17 @@ mod tests {
         let surface = core.services.objects.surfaces.xz_plane();
 
         let tolerance = 1.;
-        let approx =
-            (&curve, surface_path, surface.deref(), boundary).approx(tolerance);
+        let approx = (&curve, surface_path, surface.deref(), boundary)
+            .approx(tolerance)?;
 
         assert_eq!(approx.points, vec![]);
+
+        Ok(())
     }
 
     #[test]
-    fn approx_line_on_curved_surface_but_not_along_curve() {
+    fn approx_line_on_curved_surface_but_not_along_curve() -> anyhow::Result<()>
+    {
         let mut core = Instance::new();
 
         let curve = Curve::new().insert(&mut core.services);
@@ -216,13 +310,15 @@ mod tests {
 
         let tolerance = 1.;
         let approx =
-            (&curve, surface_path, &surface, boundary).approx(tolerance);
+            (&curve, surface_path, &surface, boundary).approx(tolerance)?;
 
         assert_eq!(approx.points, vec![]);
+
+        Ok(())
     }
 
     #[test]
-    fn approx_line_on_curved_surface_along_curve() {
+    fn approx_line_on_curved_surface_along_curve() -> anyhow::Result<()> {
         let mut core = Instance::new();
 
         let global_path = GlobalPath::circle_from_radius(1.);
@@ -239,10 +335,10 @@ mod tests {
 
         let tolerance = 1.;
         let approx =
-            (&curve, surface_path, &surface, boundary).approx(tolerance);
+            (&curve, surface_path, &surface, boundary).approx(tolerance)?;
 
         let expected_approx = (global_path, boundary)
-            .approx(tolerance)
+            .approx(tolerance)?
             .into_iter()
             .map(|(point_local, _)| {
                 let point_surface =
@@ -253,10 +349,59 @@ mod tests {
             })
             .collect::<Vec<_>>();
         assert_eq!(approx.points, expected_approx);
+
+        Ok(())
+    }
+
+    #[test]
+    fn approx_line_on_curved_surface_along_curve_with_scaled_coords(
+    ) -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        // The curve coordinates of this line don't match the `u` coordinates
+        // of the surface. They go from 0 to 1, while `u` goes around the
+        // circle.
+        let global_path = GlobalPath::circle_from_radius(1.);
+        let curve = Curve::new().insert(&mut core.services);
+        let surface_path = SurfacePath::line_from_points_with_coords([
+            ([0.], [0., 1.]),
+            ([1.], [TAU, 1.]),
+        ]);
+        let boundary = CurveBoundary::from([[0.], [1.]]);
+        let surface = Surface::new(SurfaceGeometry {
+            u: global_path,
+            v: [0., 0., 1.].into(),
+        });
+
+        let tolerance = 1.;
+        let approx =
+            (&curve, surface_path, &surface, boundary).approx(tolerance)?;
+
+        let expected_approx = (global_path, CurveBoundary::from([[0.], [TAU]]))
+            .approx(tolerance)?
+            .into_iter()
+            .map(|(point_u, point_global)| {
+                let point_local = Point::from([point_u.t / TAU]);
+                ApproxPoint::new(point_local, point_global + [0., 0., 1.])
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(approx.points.len(), expected_approx.len());
+        for (point, expected) in approx.points.iter().zip(&expected_approx) {
+            assert!(
+                (point.local_form - expected.local_form).magnitude()
+                    < Scalar::from(1e-12)
+            );
+            assert!(
+                (point.global_form - expected.global_form).magnitude()
+                    < Scalar::from(1e-12)
+            );
+        }
+
+        Ok(())
     }
 
     #[test]
-    fn approx_circle_on_flat_surface() {
+    fn approx_circle_on_flat_surface() -> anyhow::Result<()> {
         let mut core = Instance::new();
 
         let curve = Curve::new().insert(&mut core.services);
@@ -266,11 +411,11 @@ mod tests {
         let surface = core.services.objects.surfaces.xz_plane();
 
         let tolerance = 1.;
-        let approx =
-            (&curve, surface_path, surface.deref(), boundary).approx(tolerance);
+        let approx = (&curve, surface_path, surface.deref(), boundary)
+            .approx(tolerance)?;
 
         let expected_approx = (&surface_path, boundary)
-            .approx(tolerance)
+            .approx(tolerance)?
             .into_iter()
             .map(|(point_local, _)| {
                 let point_surface =
@@ -281,5 +426,27 @@ mod tests {
             })
             .collect::<Vec<_>>();
         assert_eq!(approx.points, expected_approx);
+
+        Ok(())
+    }
+
+    #[test]
+    fn approx_circle_on_curved_surface() {
+        let mut core = Instance::new();
+
+        let curve = Curve::new().insert(&mut core.services);
+        let surface_path =
+            SurfacePath::circle_from_center_and_radius([0., 0.], 1.);
+        let boundary = CurveBoundary::from([[0.], [TAU]]);
+        let surface = Surface::new(SurfaceGeometry {
+            u: GlobalPath::circle_from_radius(1.),
+            v: [0., 0., 1.].into(),
+        });
+
+        let tolerance = 1.;
+        let result =
+            (&curve, surface_path, &surface, boundary).approx(tolerance);
+
+        assert!(matches!(result, Err(ApproxError::CircleOnCurvedSurface)));
     }
 }
@@ -10,7 +10,7 @@ use crate::objects::{Cycle, Surface};
 
 use super::{
     edge::{HalfEdgeApprox, HalfEdgeApproxCache},
-    Approx, ApproxPoint, Tolerance,
+    Approx, ApproxError, ApproxPoint, Tolerance,
 };
 
 impl Approx for (&Cycle, &Surface) {
@@ -21,19 +21,24 @@ impl Approx for (&Cycle, &Surface) {
         self,
         tolerance: impl Into<Tolerance>,
         cache: &mut Self::Cache,
-    ) -> Self::Approximation {
+    ) -> Result<Self::Approximation, ApproxError> {
         let (cycle, surface) = self;
         let tolerance = tolerance.into();
 
         let half_edges = cycle
             .half_edges()
             .iter()
-            .map(|edge| {
-                (edge.deref(), surface).approx_with_cache(tolerance, cache)
+            .map(|half_edge| {
+                (half_edge.deref(), surface)
+                    .approx_with_cache(tolerance, cache)
+                    .map_err(|err| ApproxError::HalfEdge {
+                        half_edge: half_edge.clone(),
+                        source: Box::new(err),
+                    })
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
 
-        CycleApprox { half_edges }
+        Ok(CycleApprox { half_edges })
     }
 }
 
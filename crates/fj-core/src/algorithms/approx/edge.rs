@@ -8,8 +8,8 @@
 use crate::objects::{HalfEdge, Surface};
 
 use super::{
-    curve::CurveApproxCache, vertex::VertexApproxCache, Approx, ApproxPoint,
-    Tolerance,
+    curve::CurveApproxCache, vertex::VertexApproxCache, Approx, ApproxError,
+    ApproxPoint, Tolerance,
 };
 
 impl Approx for (&HalfEdge, &Surface) {
@@ -20,21 +20,27 @@ impl Approx for (&HalfEdge, &Surface) {
         self,
         tolerance: impl Into<Tolerance>,
         cache: &mut Self::Cache,
-    ) -> Self::Approximation {
+    ) -> Result<Self::Approximation, ApproxError> {
         let (edge, surface) = self;
         let tolerance = tolerance.into();
 
         let start_position_surface = edge.start_position();
-        let start_position = match cache.start_position.get(edge.start_vertex())
-        {
+        let start_position = match cache.start_position.get(
+            edge.start_vertex(),
+            surface.geometry(),
+            start_position_surface,
+        ) {
             Some(position) => position,
             None => {
                 let position_global = surface
                     .geometry()
                     .point_from_surface_coords(start_position_surface);
-                cache
-                    .start_position
-                    .insert(edge.start_vertex().clone(), position_global)
+                cache.start_position.insert(
+                    edge.start_vertex().clone(),
+                    surface.geometry(),
+                    start_position_surface,
+                    position_global,
+                )
             }
         };
 
@@ -42,7 +48,7 @@ impl Approx for (&HalfEdge, &Surface) {
 
         let rest = {
             let approx = (edge.curve(), edge.path(), surface, edge.boundary())
-                .approx_with_cache(tolerance, &mut cache.curve);
+                .approx_with_cache(tolerance, &mut cache.curve)?;
 
             approx.points.into_iter().map(|point| {
                 let point_surface =
@@ -55,7 +61,7 @@ impl Approx for (&HalfEdge, &Surface) {
         let mut points = vec![first];
         points.extend(rest);
 
-        HalfEdgeApprox { points }
+        Ok(HalfEdgeApprox { points })
     }
 }
 
@@ -67,8 +73,31 @@ pub struct HalfEdgeApprox {
 }
 
 /// Cache for half-edge approximations
+///
+/// The cache can be kept around between approximations, to reuse the
+/// approximations of vertices and curves that were already approximated. See
+/// [`VertexApproxCache`] and [`CurveApproxCache`].
 #[derive(Default)]
 pub struct HalfEdgeApproxCache {
     start_position: VertexApproxCache,
     curve: CurveApproxCache,
 }
+
+impl HalfEdgeApproxCache {
+    /// Start the approximation of a new shape
+    ///
+    /// Within the approximation of a shape, vertices and curves are only
+    /// approximated once, so all faces they are part of agree on them. Call
+    /// this before reusing the cache for another shape, or for a changed
+    /// version of the same shape.
+    pub fn start_shape(&mut self) {
+        self.start_position.start_shape();
+        self.curve.start_shape();
+    }
+
+    /// Remove all approximations that weren't used since the last call
+    pub fn evict_unused(&mut self) {
+        self.start_position.evict_unused();
+        self.curve.evict_unused();
+    }
+}
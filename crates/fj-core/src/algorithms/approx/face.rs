@@ -2,18 +2,23 @@
 //!
 //! See [`FaceApprox`].
 
-use std::{collections::BTreeSet, ops::Deref};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Deref,
+};
 
 use fj_interop::Color;
 
 use crate::{
+    geometry::SurfaceGeometry,
     objects::{Face, Handedness, ObjectSet},
+    storage::Handle,
     validate::ValidationConfig,
 };
 
 use super::{
-    cycle::CycleApprox, edge::HalfEdgeApproxCache, Approx, ApproxPoint,
-    Tolerance,
+    cycle::CycleApprox, edge::HalfEdgeApproxCache, Approx, ApproxError,
+    ApproxPoint, Tolerance,
 };
 
 impl Approx for &ObjectSet<Face> {
@@ -24,41 +29,50 @@ impl Approx for &ObjectSet<Face> {
         self,
         tolerance: impl Into<Tolerance>,
         cache: &mut Self::Cache,
-    ) -> Self::Approximation {
+    ) -> Result<Self::Approximation, ApproxError> {
         let tolerance = tolerance.into();
 
         let approx = self
             .into_iter()
-            .map(|face| face.approx_with_cache(tolerance, cache))
-            .collect();
+            .map(|face| {
+                let approx = face.approx_with_cache(tolerance, cache).map_err(
+                    |err| ApproxError::Face {
+                        face: face.clone(),
+                        source: Box::new(err),
+                    },
+                )?;
+
+                Ok((face, approx))
+            })
+            .collect::<Result<Vec<_>, ApproxError>>()?;
 
         let min_distance = ValidationConfig::default().distinct_min_distance;
-        let mut all_points: BTreeSet<ApproxPoint<2>> = BTreeSet::new();
+        let mut all_points: BTreeMap<ApproxPoint<2>, &Handle<Face>> =
+            BTreeMap::new();
 
         // Run some validation code on the approximation.
-        for approx in &approx {
-            let approx: &FaceApprox = approx;
-
+        for (face, approx) in &approx {
             for a in &approx.points() {
-                for b in &all_points {
+                for (b, other_face) in &all_points {
                     let distance = (b.global_form - a.global_form).magnitude();
 
                     if b.global_form != a.global_form && distance < min_distance
                     {
-                        panic!(
-                            "Invalid approximation: \
-                            Distinct points are too close \
-                            (a: {:?}, b: {:?}, distance: {distance})",
-                            a.global_form, b.global_form,
-                        );
+                        return Err(ApproxError::DistinctPointsTooClose {
+                            face_a: (*face).clone(),
+                            face_b: (*other_face).clone(),
+                            a: a.global_form,
+                            b: b.global_form,
+                            distance,
+                        });
                     }
                 }
 
-                all_points.insert(*a);
+                all_points.insert(*a, face);
             }
         }
 
-        approx
+        Ok(approx.into_iter().map(|(_, approx)| approx).collect())
     }
 }
 
@@ -70,7 +84,7 @@ impl Approx for &Face {
         self,
         tolerance: impl Into<Tolerance>,
         cache: &mut Self::Cache,
-    ) -> Self::Approximation {
+    ) -> Result<Self::Approximation, ApproxError> {
         let tolerance = tolerance.into();
 
         // Curved faces whose curvature is not fully defined by their edges
@@ -86,23 +100,30 @@ impl Approx for &Face {
         // would need to provide its own approximation, as the edges that bound
         // it have nothing to do with its curvature.
 
+        if self.region().exterior().try_winding().is_none() {
+            return Err(ApproxError::UndefinedWinding {
+                cycle: self.region().exterior().clone(),
+            });
+        }
+
         let exterior =
             (self.region().exterior().deref(), self.surface().deref())
-                .approx_with_cache(tolerance, cache);
+                .approx_with_cache(tolerance, cache)?;
 
         let mut interiors = BTreeSet::new();
         for cycle in self.region().interiors() {
             let cycle = (cycle.deref(), self.surface().deref())
-                .approx_with_cache(tolerance, cache);
+                .approx_with_cache(tolerance, cache)?;
             interiors.insert(cycle);
         }
 
-        FaceApprox {
+        Ok(FaceApprox {
             exterior,
             interiors,
+            surface: self.surface().geometry(),
             color: self.region().color(),
             coord_handedness: self.coord_handedness(),
-        }
+        })
     }
 }
 
@@ -115,6 +136,9 @@ pub struct FaceApprox {
     /// Approximations of the interior cycles
     pub interiors: BTreeSet<CycleApprox>,
 
+    /// The geometry of the approximated face's surface
+    pub surface: SurfaceGeometry,
+
     /// The color of the approximated face
     pub color: Option<Color>,
 
@@ -17,9 +17,12 @@ use std::{
     hash::{Hash, Hasher},
 };
 
-use fj_math::Point;
+use fj_math::{Point, Scalar};
 
-use crate::objects::Surface;
+use crate::{
+    objects::{Cycle, Face, HalfEdge, Surface},
+    storage::Handle,
+};
 
 pub use self::tolerance::{InvalidTolerance, Tolerance};
 
@@ -35,7 +38,13 @@ pub trait Approx: Sized {
     ///
     /// `tolerance` defines how far the approximation is allowed to deviate from
     /// the actual object.
-    fn approx(self, tolerance: impl Into<Tolerance>) -> Self::Approximation {
+    ///
+    /// Returns an error, if the object can't be approximated. See
+    /// [`ApproxError`].
+    fn approx(
+        self,
+        tolerance: impl Into<Tolerance>,
+    ) -> Result<Self::Approximation, ApproxError> {
         let mut cache = Self::Cache::default();
         self.approx_with_cache(tolerance, &mut cache)
     }
@@ -48,7 +57,82 @@ pub trait Approx: Sized {
         self,
         tolerance: impl Into<Tolerance>,
         cache: &mut Self::Cache,
-    ) -> Self::Approximation;
+    ) -> Result<Self::Approximation, ApproxError>;
+}
+
+/// Error approximating an object
+///
+/// Errors that occur within a specific face or half-edge are wrapped into
+/// [`ApproxError::Face`] or [`ApproxError::HalfEdge`] respectively, so the
+/// offending object can be identified.
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum ApproxError {
+    /// Approximating a circle on a curved surface is not supported
+    #[error("Approximating a circle on a curved surface is not supported yet")]
+    CircleOnCurvedSurface,
+
+    /// Approximating a sketch is not supported
+    #[error("Approximating a sketch is not supported yet")]
+    Sketch,
+
+    /// The winding of a face's exterior cycle can't be determined
+    ///
+    /// This is the case, if the cycle is empty, has no area, or consists of
+    /// less than three half-edges that aren't all circles.
+    #[error(
+        "Can't determine winding of exterior cycle\n\
+        - Cycle: {cycle:?}"
+    )]
+    UndefinedWinding {
+        /// The exterior cycle whose winding can't be determined
+        cycle: Handle<Cycle>,
+    },
+
+    /// The approximations of two faces contain points that are too close
+    #[error(
+        "Distinct points are too close\n\
+        - Point from {face_a:?}: {a:?}\n\
+        - Point from {face_b:?}: {b:?}\n\
+        - Distance: {distance}"
+    )]
+    DistinctPointsTooClose {
+        /// The face that the first point belongs to
+        face_a: Handle<Face>,
+
+        /// The face that the second point belongs to
+        face_b: Handle<Face>,
+
+        /// The first point
+        a: Point<3>,
+
+        /// The second point
+        b: Point<3>,
+
+        /// The distance between the points
+        distance: Scalar,
+    },
+
+    /// Error approximating a half-edge
+    #[error("Error approximating half-edge {half_edge:?}")]
+    HalfEdge {
+        /// The half-edge that failed to approximate
+        half_edge: Handle<HalfEdge>,
+
+        /// The error that occurred
+        #[source]
+        source: Box<ApproxError>,
+    },
+
+    /// Error approximating a face
+    #[error("Error approximating face {face:?}")]
+    Face {
+        /// The face that failed to approximate
+        face: Handle<Face>,
+
+        /// The error that occurred
+        #[source]
+        source: Box<ApproxError>,
+    },
 }
 
 /// A point from an approximation, with local and global forms
@@ -34,7 +34,7 @@ use fj_math::{Circle, Point, Scalar, Sign};
 
 use crate::geometry::{CurveBoundary, GlobalPath, SurfacePath};
 
-use super::{Approx, Tolerance};
+use super::{Approx, ApproxError, Tolerance};
 
 impl Approx for (&SurfacePath, CurveBoundary<Point<1>>) {
     type Approximation = Vec<(Point<1>, Point<2>)>;
@@ -44,15 +44,17 @@ impl Approx for (&SurfacePath, CurveBoundary<Point<1>>) {
         self,
         tolerance: impl Into<Tolerance>,
         (): &mut Self::Cache,
-    ) -> Self::Approximation {
+    ) -> Result<Self::Approximation, ApproxError> {
         let (path, range) = self;
 
-        match path {
+        let approx = match path {
             SurfacePath::Circle(circle) => {
                 approx_circle(circle, range, tolerance.into())
             }
             SurfacePath::Line(_) => vec![],
-        }
+        };
+
+        Ok(approx)
     }
 }
 
@@ -64,15 +66,17 @@ impl Approx for (GlobalPath, CurveBoundary<Point<1>>) {
         self,
         tolerance: impl Into<Tolerance>,
         (): &mut Self::Cache,
-    ) -> Self::Approximation {
+    ) -> Result<Self::Approximation, ApproxError> {
         let (path, range) = self;
 
-        match path {
+        let approx = match path {
             GlobalPath::Circle(circle) => {
                 approx_circle(&circle, range, tolerance.into())
             }
             GlobalPath::Line(_) => vec![],
-        }
+        };
+
+        Ok(approx)
     }
 }
 
@@ -137,8 +141,12 @@ impl PathApproxParams {
 
         // We can't generate a point exactly at the boundaries of the range as
         // part of the approximation. Make sure we stay inside the range.
-        let min = min.floor() + 1.;
-        let max = max.ceil() - 1.;
+        //
+        // Boundaries that are the result of a computation might be off by a
+        // tiny amount. Points that are that close to a boundary would be
+        // duplicates of it, for all practical purposes, so exclude them too.
+        let min = (min + BOUNDARY_EPSILON).floor() + 1.;
+        let max = (max - BOUNDARY_EPSILON).ceil() - 1.;
 
         let [start, end] = match direction {
             Sign::Negative => [max, min],
@@ -164,6 +172,11 @@ impl PathApproxParams {
     }
 }
 
+/// How close a point can be to a boundary, before it's considered a duplicate
+///
+/// This value is relative to the increment between approximation points.
+const BOUNDARY_EPSILON: f64 = 1e-9;
+
 #[cfg(test)]
 mod tests {
     use std::f64::consts::TAU;
@@ -208,6 +221,10 @@ mod tests {
         test_path([[1.], [TAU]], [1., 2., 3.]);
         test_path([[0.], [TAU - 1.]], [1., 2., 3.]);
 
+        // Boundaries that are a tiny bit off don't lead to points that almost
+        // coincide with them.
+        test_path([[-1e-15], [TAU + 1e-15]], [1., 2., 3.]);
+
         // Here the range is restricted to cut of the first or last increment.
         test_path([[2.], [TAU]], [2., 3.]);
         test_path([[0.], [TAU - 2.]], [1., 2.]);
@@ -4,7 +4,9 @@ use std::collections::BTreeSet;
 
 use crate::objects::Shell;
 
-use super::{edge::HalfEdgeApproxCache, face::FaceApprox, Approx, Tolerance};
+use super::{
+    edge::HalfEdgeApproxCache, face::FaceApprox, Approx, ApproxError, Tolerance,
+};
 
 impl Approx for &Shell {
     type Approximation = BTreeSet<FaceApprox>;
@@ -14,7 +16,7 @@ impl Approx for &Shell {
         self,
         tolerance: impl Into<Tolerance>,
         cache: &mut Self::Cache,
-    ) -> Self::Approximation {
+    ) -> Result<Self::Approximation, ApproxError> {
         self.faces().approx_with_cache(tolerance, cache)
     }
 }
@@ -4,7 +4,9 @@ use std::collections::BTreeSet;
 
 use crate::objects::Sketch;
 
-use super::{edge::HalfEdgeApproxCache, face::FaceApprox, Approx, Tolerance};
+use super::{
+    edge::HalfEdgeApproxCache, face::FaceApprox, Approx, ApproxError, Tolerance,
+};
 
 impl Approx for &Sketch {
     type Approximation = BTreeSet<FaceApprox>;
@@ -14,7 +16,7 @@ impl Approx for &Sketch {
         self,
         _tolerance: impl Into<Tolerance>,
         _cache: &mut Self::Cache,
-    ) -> Self::Approximation {
-        todo!()
+    ) -> Result<Self::Approximation, ApproxError> {
+        Err(ApproxError::Sketch)
     }
 }
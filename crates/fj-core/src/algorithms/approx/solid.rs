@@ -4,7 +4,9 @@ use std::collections::BTreeSet;
 
 use crate::objects::Solid;
 
-use super::{edge::HalfEdgeApproxCache, face::FaceApprox, Approx, Tolerance};
+use super::{
+    edge::HalfEdgeApproxCache, face::FaceApprox, Approx, ApproxError, Tolerance,
+};
 
 impl Approx for &Solid {
     type Approximation = BTreeSet<FaceApprox>;
@@ -14,12 +16,14 @@ impl Approx for &Solid {
         self,
         tolerance: impl Into<Tolerance>,
         cache: &mut Self::Cache,
-    ) -> Self::Approximation {
+    ) -> Result<Self::Approximation, ApproxError> {
         let tolerance = tolerance.into();
 
-        self.shells()
-            .iter()
-            .flat_map(|shell| shell.approx_with_cache(tolerance, cache))
-            .collect()
+        let mut approx = BTreeSet::new();
+        for shell in self.shells() {
+            approx.extend(shell.approx_with_cache(tolerance, cache)?);
+        }
+
+        Ok(approx)
     }
 }
@@ -5,30 +5,86 @@ use std::collections::BTreeMap;
 use fj_math::Point;
 
 use crate::{
+    geometry::SurfaceGeometry,
     objects::Vertex,
     storage::{Handle, HandleWrapper},
 };
 
 /// Cache for vertex approximations
+///
+/// The position of a vertex doesn't depend on the tolerance, but it does depend
+/// on the surface it is defined on, and its position on that surface. Cached
+/// positions are keyed by those, so they can be reused when approximating a
+/// changed shape, without going stale.
+///
+/// Within the approximation of a single shape, a vertex must have the same
+/// position in all faces it is part of, even if computing it from each of
+/// those faces' surfaces would result in slightly different positions. See
+/// [`VertexApproxCache::start_shape`].
 #[derive(Default)]
 pub struct VertexApproxCache {
-    inner: BTreeMap<HandleWrapper<Vertex>, Point<3>>,
+    current: BTreeMap<HandleWrapper<Vertex>, Point<3>>,
+    inner: BTreeMap<
+        (HandleWrapper<Vertex>, SurfaceGeometry, Point<2>),
+        (Point<3>, bool),
+    >,
 }
 
 impl VertexApproxCache {
     /// Get an approximated vertex from the cache
-    pub fn get(&self, handle: &Handle<Vertex>) -> Option<Point<3>> {
-        self.inner.get(&handle.clone().into()).cloned()
+    ///
+    /// Marks the vertex as used. See [`VertexApproxCache::evict_unused`].
+    pub fn get(
+        &mut self,
+        handle: &Handle<Vertex>,
+        surface: SurfaceGeometry,
+        position_surface: Point<2>,
+    ) -> Option<Point<3>> {
+        let handle = HandleWrapper::from(handle.clone());
+
+        if let Some(&position) = self.current.get(&handle) {
+            self.inner
+                .insert((handle, surface, position_surface), (position, true));
+            return Some(position);
+        }
+
+        let (position, used) =
+            self.inner
+                .get_mut(&(handle.clone(), surface, position_surface))?;
+        *used = true;
+        self.current.insert(handle, *position);
+
+        Some(*position)
     }
 
     /// Insert an approximated vertex into the cache
     pub fn insert(
         &mut self,
         handle: Handle<Vertex>,
+        surface: SurfaceGeometry,
+        position_surface: Point<2>,
         position: Point<3>,
     ) -> Point<3> {
+        let handle = HandleWrapper::from(handle);
+
+        self.current.insert(handle.clone(), position);
         self.inner
-            .insert(handle.clone().into(), position)
-            .unwrap_or(position)
+            .insert((handle, surface, position_surface), (position, true));
+
+        position
+    }
+
+    /// Start the approximation of a new shape
+    ///
+    /// Until this is called, vertices keep the position they got when they
+    /// were first approximated, regardless of the surface they are approximated
+    /// on.
+    pub fn start_shape(&mut self) {
+        self.current.clear();
+    }
+
+    /// Remove all vertices that weren't used since the last call
+    pub fn evict_unused(&mut self) {
+        self.inner.retain(|_, (_, used)| std::mem::take(used));
     }
 }
@@ -1,58 +1,195 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 
-use fj_math::{Point, Scalar, Triangle, Winding};
-use spade::HasPosition;
+use fj_math::{Point, Scalar, Triangle, Vector, Winding};
+use spade::{
+    handles::{FixedFaceHandle, InnerTag},
+    AngleLimit, HasPosition, RefinementParameters,
+};
 
-use crate::{algorithms::approx::cycle::CycleApprox, objects::Handedness};
+use crate::{
+    algorithms::approx::{cycle::CycleApprox, ApproxPoint},
+    geometry::{GlobalPath, SurfaceGeometry},
+    objects::Handedness,
+};
+
+use super::{Refinement, TriangulationError};
 
 /// Create a Delaunay triangulation of all points
-pub fn triangulate(
-    cycles: impl IntoIterator<Item = CycleApprox>,
+///
+/// If `refinement` is provided, additional points are inserted into the
+/// triangulation, until it meets the quality requirements defined there. The
+/// boundary of the triangulation keeps its shape.
+pub fn triangulate<'r>(
+    cycles: impl IntoIterator<Item = &'r CycleApprox>,
+    surface: &SurfaceGeometry,
     coord_handedness: Handedness,
-) -> Vec<[TriangulationPoint; 3]> {
+    refinement: Option<Refinement>,
+) -> Result<Vec<[TriangulationPoint; 3]>, TriangulationError> {
     use spade::Triangulation as _;
 
-    let mut triangulation = spade::ConstrainedDelaunayTriangulation::<_>::new();
+    let frame = LocalFrame::from_surface(surface);
+
+    let mut triangulation =
+        spade::ConstrainedDelaunayTriangulation::<Vertex>::new();
 
     let mut points = BTreeMap::new();
 
+    // If triangles must not exceed a maximum area, then the boundary segments
+    // must not exceed a maximum length either. We aren't allowed to split
+    // those during refinement, so we have to do it here. This is the side
+    // length of an equilateral triangle with the maximum area.
+    let max_segment_length = refinement
+        .and_then(|refinement| refinement.max_area)
+        .map(|max_area| {
+            Scalar::from_f64((max_area.into_f64() * 4. / 3f64.sqrt()).sqrt())
+        });
+
     for cycle_approx in cycles {
         let mut handle_prev = None;
+        let mut point_prev: Option<ApproxPoint<2>> = None;
 
         for point in cycle_approx.points() {
-            let handle = match points.get(&point) {
-                Some(handle) => *handle,
-                None => {
-                    let handle = triangulation
-                        .insert(TriangulationPoint {
-                            point_surface: point.local_form,
-                            point_global: point.global_form,
-                        })
-                        .expect("Inserted invalid point into triangulation");
-
-                    points.insert(point, handle);
-
-                    handle
+            let mut segment_points = Vec::new();
+
+            if let (Some(point_prev), Some(max_segment_length)) =
+                (point_prev, max_segment_length)
+            {
+                segment_points.extend(split_segment(
+                    point_prev,
+                    point,
+                    max_segment_length,
+                ));
+            }
+            segment_points.push(point);
+
+            for point in segment_points {
+                let handle = match points.get(&point) {
+                    Some(handle) => *handle,
+                    None => {
+                        let handle = triangulation
+                            .insert(Vertex {
+                                position: frame.point_from_surface_coords(
+                                    point.local_form,
+                                ),
+                                point: Some(TriangulationPoint {
+                                    point_surface: point.local_form,
+                                    point_global: point.global_form,
+                                }),
+                            })
+                            .map_err(|_| TriangulationError::InvalidPoint {
+                                point: point.local_form,
+                            })?;
+
+                        points.insert(point, handle);
+
+                        handle
+                    }
+                };
+
+                if let Some(handle_prev) = handle_prev {
+                    triangulation.add_constraint(handle_prev, handle);
                 }
-            };
 
-            if let Some(handle_prev) = handle_prev {
-                triangulation.add_constraint(handle_prev, handle);
+                handle_prev = Some(handle);
             }
 
-            handle_prev = Some(handle);
+            point_prev = Some(point);
+        }
+    }
+
+    if let Some(refinement) = refinement {
+        // Splitting a constraint edge would insert a point into the boundary
+        // of the face, which the neighboring face doesn't know about. This
+        // would leave a gap in the mesh, so we must not allow that.
+        let mut parameters = RefinementParameters::new()
+            .keep_constraint_edges()
+            .exclude_outer_faces(true);
+
+        parameters = match refinement.min_angle {
+            Some(min_angle) => parameters
+                .with_angle_limit(AngleLimit::from_rad(min_angle.into_f64())),
+            None => parameters.with_angle_limit(AngleLimit::from_rad(0.)),
+        };
+        if let Some(max_area) = refinement.max_area {
+            parameters = parameters.with_max_allowed_area(max_area);
+        }
+
+        // This is the same default that `spade` uses.
+        let num_vertices = triangulation.num_vertices();
+        let max_additional_vertices = refinement
+            .max_additional_vertices
+            .unwrap_or(num_vertices * 10);
+        parameters =
+            parameters.with_max_additional_vertices(max_additional_vertices);
+
+        triangulation.refine(parameters);
+
+        // Refinement won't insert a point, if that would require splitting a
+        // constraint edge. That means triangles at the boundary might still be
+        // too large. Let's take care of those by inserting their centroids,
+        // which can't be anywhere but inside the face.
+        if let Some(max_area) = refinement.max_area {
+            let mut budget = max_additional_vertices
+                .saturating_sub(triangulation.num_vertices() - num_vertices);
+
+            loop {
+                let centroids = faces_inside_cycles(&triangulation)
+                    .into_iter()
+                    .map(|face| triangulation.face(face))
+                    .filter(|face| face.area() > max_area)
+                    .map(|face| face.center())
+                    .collect::<Vec<_>>();
+
+                if centroids.is_empty() || budget == 0 {
+                    break;
+                }
+
+                for centroid in centroids {
+                    if budget == 0 {
+                        break;
+                    }
+
+                    let vertex = Vertex::from(centroid);
+                    triangulation.insert(vertex).map_err(|_| {
+                        TriangulationError::InvalidPoint {
+                            point: frame
+                                .point_to_surface_coords(vertex.position),
+                        }
+                    })?;
+
+                    budget -= 1;
+                }
+            }
         }
     }
 
     let mut triangles = Vec::new();
     for triangle in triangulation.inner_faces() {
-        let [v0, v1, v2] = triangle.vertices().map(|vertex| *vertex.data());
+        let [v0, v1, v2] = triangle.vertices().map(|vertex| {
+            let vertex = vertex.data();
+
+            vertex.point.unwrap_or_else(|| {
+                // This is a point that was inserted during refinement. We need
+                // to compute its surface and global forms.
+                let point_surface =
+                    frame.point_to_surface_coords(vertex.position);
+                let point_global =
+                    surface.point_from_surface_coords(point_surface);
+
+                TriangulationPoint {
+                    point_surface,
+                    point_global,
+                }
+            })
+        });
         let triangle_winding = Triangle::<2>::from_points([
             v0.point_surface,
             v1.point_surface,
             v2.point_surface,
         ])
-        .expect("invalid triangle")
+        .map_err(|_| TriangulationError::DegenerateTriangle {
+            points: [v0, v1, v2].map(|point| point.point_global),
+        })?
         .winding();
 
         let required_winding = match coord_handedness {
@@ -69,7 +206,88 @@ pub fn triangulate(
         triangles.push(triangle);
     }
 
-    triangles
+    Ok(triangles)
+}
+
+/// Find all faces of the triangulation that are inside of the cycles
+///
+/// This excludes the faces outside of the exterior cycle, as well as the ones
+/// within the holes that the interior cycles define. Starting from the outside,
+/// a face is inside, if reaching it requires crossing an odd number of
+/// constraint edges.
+fn faces_inside_cycles(
+    triangulation: &spade::ConstrainedDelaunayTriangulation<Vertex>,
+) -> Vec<FixedFaceHandle<InnerTag>> {
+    use spade::Triangulation as _;
+
+    let mut is_inside = BTreeMap::new();
+    let mut queue = VecDeque::new();
+
+    for edge in triangulation.convex_hull() {
+        for edge in [edge, edge.rev()] {
+            if let Some(face) = edge.face().as_inner() {
+                queue.push_back((face.fix(), edge.is_constraint_edge()));
+            }
+        }
+    }
+
+    while let Some((face, inside)) = queue.pop_front() {
+        if is_inside.contains_key(&face) {
+            continue;
+        }
+        is_inside.insert(face, inside);
+
+        for edge in triangulation.face(face).adjacent_edges() {
+            if let Some(neighbor) = edge.rev().face().as_inner() {
+                let crosses_boundary = edge.is_constraint_edge();
+                queue.push_back((neighbor.fix(), inside != crosses_boundary));
+            }
+        }
+    }
+
+    is_inside
+        .into_iter()
+        .filter_map(|(face, inside)| inside.then_some(face))
+        .collect()
+}
+
+/// Compute the points that split a segment into pieces of a maximum length
+///
+/// The returned points don't include the end points of the segment.
+///
+/// The same segment is shared by two neighboring faces, which must end up with
+/// exactly the same points, or there will be gaps in the mesh. This is why the
+/// points are computed from the global forms, in a canonical order.
+fn split_segment(
+    a: ApproxPoint<2>,
+    b: ApproxPoint<2>,
+    max_length: Scalar,
+) -> Vec<ApproxPoint<2>> {
+    let (start, end, reversed) = if a.global_form <= b.global_form {
+        (a, b, false)
+    } else {
+        (b, a, true)
+    };
+
+    let length = start.global_form.distance_to(&end.global_form);
+    let num_segments = (length / max_length).ceil().into_u64();
+
+    let mut points = (1..num_segments)
+        .map(|i| {
+            let t = Scalar::from_u64(i) / Scalar::from_u64(num_segments);
+
+            ApproxPoint::new(
+                start.local_form + (end.local_form - start.local_form) * t,
+                start.global_form + (end.global_form - start.global_form) * t,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if reversed {
+        points.reverse();
+    }
+
+    points
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
@@ -78,14 +296,99 @@ pub struct TriangulationPoint {
     pub point_global: Point<3>,
 }
 
-// Enables the use of `LocalPoint` in the triangulation.
-impl HasPosition for TriangulationPoint {
+/// A vertex in the triangulation
+///
+/// Vertices that come from the cycle approximations carry their original
+/// points. Vertices that are inserted during refinement only have a position.
+#[derive(Clone, Copy, Debug)]
+struct Vertex {
+    position: Point<2>,
+    point: Option<TriangulationPoint>,
+}
+
+// Enables the use of `Vertex` in the triangulation.
+impl HasPosition for Vertex {
     type Scalar = Scalar;
 
     fn position(&self) -> spade::Point2<Self::Scalar> {
         spade::Point2 {
-            x: self.point_surface.u,
-            y: self.point_surface.v,
+            x: self.position.u,
+            y: self.position.v,
         }
     }
 }
+
+// Required for refinement, which creates new vertices from positions.
+impl From<spade::Point2<Scalar>> for Vertex {
+    fn from(point: spade::Point2<Scalar>) -> Self {
+        Self {
+            position: Point::from([point.x, point.y]),
+            point: None,
+        }
+    }
+}
+
+/// A linear map from surface coordinates into a local, orthonormal frame
+///
+/// Surface coordinates can be arbitrarily scaled and sheared, compared to the
+/// global space they are embedded in. Triangulating in this frame instead,
+/// means that angles and areas are (close to) what they are in global space.
+///
+/// This is exact for planes. For curved surfaces, it is only exact as long as
+/// the surface's v-axis is perpendicular to its u-axis, which is the case for
+/// the cylinders that sweeping creates.
+#[derive(Clone, Copy, Debug)]
+struct LocalFrame {
+    uu: Scalar,
+    uv: Scalar,
+    vv: Scalar,
+}
+
+impl LocalFrame {
+    fn from_surface(surface: &SurfaceGeometry) -> Self {
+        let u: Vector<3> = match surface.u {
+            GlobalPath::Circle(circle) => circle.b(),
+            GlobalPath::Line(line) => line.direction(),
+        };
+        let v = surface.v;
+
+        let u_magnitude = u.magnitude();
+        if u_magnitude == Scalar::ZERO {
+            // Degenerate surface. Nothing sensible we can do here.
+            return Self::identity();
+        }
+        let e1 = u / u_magnitude;
+
+        let uv = v.dot(&e1);
+        let vv = (v - e1 * uv).magnitude();
+        if vv == Scalar::ZERO {
+            // Degenerate surface. Nothing sensible we can do here.
+            return Self::identity();
+        }
+
+        Self {
+            uu: u_magnitude,
+            uv,
+            vv,
+        }
+    }
+
+    fn identity() -> Self {
+        Self {
+            uu: Scalar::ONE,
+            uv: Scalar::ZERO,
+            vv: Scalar::ONE,
+        }
+    }
+
+    fn point_from_surface_coords(&self, point: Point<2>) -> Point<2> {
+        Point::from([self.uu * point.u + self.uv * point.v, self.vv * point.v])
+    }
+
+    fn point_to_surface_coords(&self, point: Point<2>) -> Point<2> {
+        let v = point.v / self.vv;
+        let u = (point.u - self.uv * v) / self.uu;
+
+        Point::from([u, v])
+    }
+}
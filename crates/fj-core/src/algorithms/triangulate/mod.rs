@@ -3,83 +3,318 @@
 mod delaunay;
 mod polygon;
 
+use std::collections::BTreeMap;
+
 use fj_interop::Mesh;
-use fj_math::Point;
+use fj_math::{Point, Scalar};
 
 use self::polygon::Polygon;
 
-use super::approx::{face::FaceApprox, Approx, Tolerance};
+use super::approx::{
+    edge::HalfEdgeApproxCache, face::FaceApprox, Approx, ApproxError, Tolerance,
+};
 
 /// Triangulate a shape
 pub trait Triangulate: Sized {
     /// Triangulate the shape
-    fn triangulate(self) -> Mesh<Point<3>> {
+    ///
+    /// Returns an error, if the shape can't be triangulated. See
+    /// [`TriangulationError`].
+    fn triangulate(self) -> Result<Mesh<Point<3>>, TriangulationError> {
+        self.triangulate_with_cache(None, &mut TriangulationCache::default())
+    }
+
+    /// Triangulate the shape, refining the result to meet quality requirements
+    ///
+    /// See [`Refinement`] for details.
+    fn triangulate_refined(
+        self,
+        refinement: Refinement,
+    ) -> Result<Mesh<Point<3>>, TriangulationError> {
+        self.triangulate_with_cache(
+            Some(refinement),
+            &mut TriangulationCache::default(),
+        )
+    }
+
+    /// Triangulate the shape, reusing results from previous triangulations
+    ///
+    /// Keep the cache around between calls, to only re-triangulate the parts
+    /// of a shape that changed since the last call. See [`TriangulationCache`].
+    fn triangulate_with_cache(
+        self,
+        refinement: Option<Refinement>,
+        cache: &mut TriangulationCache,
+    ) -> Result<Mesh<Point<3>>, TriangulationError> {
         let mut mesh = Mesh::new();
-        self.triangulate_into_mesh(&mut mesh);
-        mesh
+        self.triangulate_into_mesh_with_cache(&mut mesh, refinement, cache)?;
+        Ok(mesh)
     }
 
     /// Triangulate a partial shape into the provided mesh
     ///
+    /// This is a low-level method. Most callers should prefer
+    /// [`Triangulate::triangulate`] or [`Triangulate::triangulate_refined`].
+    ///
+    /// The shape is triangulated without refinement, and without reusing
+    /// results from previous triangulations. See
+    /// [`Triangulate::triangulate_into_mesh_with_cache`] for control over
+    /// that.
+    fn triangulate_into_mesh(
+        self,
+        mesh: &mut Mesh<Point<3>>,
+    ) -> Result<(), TriangulationError> {
+        self.triangulate_into_mesh_with_cache(
+            mesh,
+            None,
+            &mut TriangulationCache::default(),
+        )
+    }
+
+    /// Triangulate a partial shape into the provided mesh, refining the result
+    /// and reusing results from previous triangulations
+    ///
     /// This is a low-level method, intended for implementation of
-    /// `Triangulate`. Most callers should prefer [`Triangulate::triangulate`].
-    fn triangulate_into_mesh(self, mesh: &mut Mesh<Point<3>>);
+    /// `Triangulate`. Implementations used to provide
+    /// [`Triangulate::triangulate_into_mesh`] instead, which now calls this
+    /// method.
+    fn triangulate_into_mesh_with_cache(
+        self,
+        mesh: &mut Mesh<Point<3>>,
+        refinement: Option<Refinement>,
+        cache: &mut TriangulationCache,
+    ) -> Result<(), TriangulationError>;
+}
+
+/// Cache for triangulation results
+///
+/// A cache that is kept around between triangulations enables fast
+/// re-triangulation of a shape after small changes:
+///
+/// - Curve and vertex approximations are reused, based on object identity,
+///   tolerance, and the geometry they are defined by.
+/// - Faces are only re-triangulated, if their approximation changed.
+///
+/// Object identity alone is not enough to reuse an approximation: The same
+/// curve or vertex can be referenced by half-edges on a moved or otherwise
+/// changed surface, or through a different path. The cache takes this geometry
+/// into account, and only reuses approximations that still match it.
+///
+/// Call [`TriangulationCache::evict_unused`] after each triangulation, to
+/// forget results that are no longer needed. Otherwise, the cache keeps growing
+/// as the shape changes.
+#[derive(Default)]
+pub struct TriangulationCache {
+    approx: HalfEdgeApproxCache,
+    faces: BTreeMap<(FaceApprox, Option<Refinement>), CachedTriangles>,
+}
+
+/// Cached triangles of a face, and whether they were used since the last
+/// eviction
+type CachedTriangles = (Vec<[Point<3>; 3]>, bool);
+
+impl TriangulationCache {
+    /// Construct an empty instance of `TriangulationCache`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove all results that weren't used since the last call
+    ///
+    /// Results are used, when they are inserted into the cache, or when they
+    /// are reused from it.
+    pub fn evict_unused(&mut self) {
+        self.approx.evict_unused();
+        self.faces.retain(|_, (_, used)| std::mem::take(used));
+    }
+
+    /// Remove all cached results
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Error triangulating a shape
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum TriangulationError {
+    /// The shape could not be approximated
+    #[error(transparent)]
+    Approx(#[from] ApproxError),
+
+    /// A point could not be inserted into the triangulation
+    ///
+    /// This happens, if the coordinates of the point are not finite, or too
+    /// large to be handled.
+    #[error("Can't insert point into triangulation: {point:?}")]
+    InvalidPoint {
+        /// The point, in surface coordinates
+        point: Point<2>,
+    },
+
+    /// The triangulation produced a triangle that has no area
+    #[error("Triangulation produced degenerate triangle: {points:?}")]
+    DegenerateTriangle {
+        /// The points of the triangle
+        points: [Point<3>; 3],
+    },
+}
+
+/// Quality requirements for a refined triangulation
+///
+/// By default, a triangulation only contains the points of the approximated
+/// face boundaries, which can result in long and thin triangles. Refinement
+/// inserts additional points into the interior of each face, until all of its
+/// triangles meet the requirements defined here (Ruppert's algorithm).
+///
+/// Points on a face boundary are shared with the neighboring face. To keep the
+/// mesh free of gaps, boundary segments are only split to meet the maximum
+/// area, in a way that both faces agree on. As a consequence, triangles that
+/// are adjacent to the boundary might not meet the minimum angle.
+///
+/// Angles and areas are measured in a local frame that matches global space.
+/// This is exact for planar faces, and for the cylindrical faces created by
+/// sweeping.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Refinement {
+    /// The minimum angle of any triangle, in radians
+    ///
+    /// Values larger than about 30° (π/6) might cause the refinement to not
+    /// terminate, or exhaust [`Refinement::max_additional_vertices`].
+    pub min_angle: Option<Scalar>,
+
+    /// The maximum area of any triangle
+    pub max_area: Option<Scalar>,
+
+    /// The maximum number of points that may be inserted into a single face
+    ///
+    /// Defaults to ten times the number of points in the face's boundary, if
+    /// not specified.
+    pub max_additional_vertices: Option<usize>,
+}
+
+impl Refinement {
+    /// Construct an instance of `Refinement` that doesn't refine anything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require a minimum angle for all triangles, in radians
+    pub fn with_min_angle(mut self, min_angle: impl Into<Scalar>) -> Self {
+        self.min_angle = Some(min_angle.into());
+        self
+    }
+
+    /// Require a maximum area for all triangles
+    pub fn with_max_area(mut self, max_area: impl Into<Scalar>) -> Self {
+        self.max_area = Some(max_area.into());
+        self
+    }
+
+    /// Limit the number of points that may be inserted into a single face
+    pub fn with_max_additional_vertices(
+        mut self,
+        max_additional_vertices: usize,
+    ) -> Self {
+        self.max_additional_vertices = Some(max_additional_vertices);
+        self
+    }
 }
 
 impl<T> Triangulate for (T, Tolerance)
 where
-    T: Approx,
+    T: Approx<Cache = HalfEdgeApproxCache>,
     T::Approximation: IntoIterator<Item = FaceApprox>,
 {
-    fn triangulate_into_mesh(self, mesh: &mut Mesh<Point<3>>) {
+    fn triangulate_into_mesh_with_cache(
+        self,
+        mesh: &mut Mesh<Point<3>>,
+        refinement: Option<Refinement>,
+        cache: &mut TriangulationCache,
+    ) -> Result<(), TriangulationError> {
         let (approx, tolerance) = self;
 
-        let approx = approx.approx(tolerance);
+        cache.approx.start_shape();
+        let approx = approx.approx_with_cache(tolerance, &mut cache.approx)?;
 
         for approx in approx {
-            approx.triangulate_into_mesh(mesh);
+            approx.triangulate_into_mesh_with_cache(mesh, refinement, cache)?;
         }
+
+        Ok(())
     }
 }
 
 impl Triangulate for FaceApprox {
-    fn triangulate_into_mesh(self, mesh: &mut Mesh<Point<3>>) {
-        let face_as_polygon = Polygon::new()
-            .with_exterior(
-                self.exterior
-                    .points()
-                    .into_iter()
-                    .map(|point| point.local_form),
-            )
-            .with_interiors(self.interiors.iter().map(|interior| {
-                interior.points().into_iter().map(|point| point.local_form)
-            }));
-
-        let cycles = [self.exterior].into_iter().chain(self.interiors);
-        let mut triangles =
-            delaunay::triangulate(cycles, self.coord_handedness);
-        triangles.retain(|triangle| {
-            face_as_polygon
-                .contains_triangle(triangle.map(|point| point.point_surface))
-        });
-
+    fn triangulate_into_mesh_with_cache(
+        self,
+        mesh: &mut Mesh<Point<3>>,
+        refinement: Option<Refinement>,
+        cache: &mut TriangulationCache,
+    ) -> Result<(), TriangulationError> {
         let color = self.color.unwrap_or_default();
 
-        for triangle in triangles {
-            let points = triangle.map(|point| point.point_global);
+        let key = (self, refinement);
+        let triangles = match cache.faces.get_mut(&key) {
+            Some((triangles, used)) => {
+                *used = true;
+                triangles
+            }
+            None => {
+                let triangles = triangulate_face(&key.0, refinement)?;
+                &cache.faces.entry(key).or_insert((triangles, true)).0
+            }
+        };
+
+        mesh.start_face();
+        for &points in triangles {
             mesh.push_triangle(points, color);
         }
+
+        Ok(())
     }
 }
 
+fn triangulate_face(
+    face: &FaceApprox,
+    refinement: Option<Refinement>,
+) -> Result<Vec<[Point<3>; 3]>, TriangulationError> {
+    let face_as_polygon = Polygon::new()
+        .with_exterior(
+            face.exterior
+                .points()
+                .into_iter()
+                .map(|point| point.local_form),
+        )
+        .with_interiors(face.interiors.iter().map(|interior| {
+            interior.points().into_iter().map(|point| point.local_form)
+        }));
+
+    let cycles = [&face.exterior].into_iter().chain(&face.interiors);
+    let mut triangles = delaunay::triangulate(
+        cycles,
+        &face.surface,
+        face.coord_handedness,
+        refinement,
+    )?;
+    triangles.retain(|triangle| {
+        face_as_polygon
+            .contains_triangle(triangle.map(|point| point.point_surface))
+    });
+
+    Ok(triangles
+        .into_iter()
+        .map(|triangle| triangle.map(|point| point.point_global))
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use fj_interop::Mesh;
-    use fj_math::{Point, Scalar};
+    use fj_math::{Point, Scalar, Transform};
 
     use crate::{
         algorithms::approx::{Approx, Tolerance},
-        objects::{Cycle, Face},
+        objects::{Cycle, Face, Surface},
         operations::{
             build::{BuildCycle, BuildFace},
             insert::Insert,
@@ -88,7 +323,7 @@ mod tests {
         Instance,
     };
 
-    use super::Triangulate;
+    use super::{Refinement, Triangulate, TriangulationCache};
 
     #[test]
     fn simple() -> anyhow::Result<()> {
@@ -231,8 +466,244 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn refined_max_area() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let a = [0., 0.];
+        let b = [4., 0.];
+        let c = [4., 4.];
+        let d = [0., 4.];
+
+        let face =
+            Face::unbound(core.services.objects.surfaces.xy_plane(), &mut core)
+                .update_region(|region| {
+                    region
+                        .update_exterior(|_| {
+                            Cycle::polygon([a, b, c, d], &mut core)
+                                .insert(&mut core.services)
+                        })
+                        .insert(&mut core.services)
+                });
+
+        let tolerance = Tolerance::from_scalar(Scalar::ONE)?;
+        let max_area = Scalar::ONE;
+
+        let unrefined = face.approx(tolerance)?.triangulate()?;
+        let refined = face
+            .approx(tolerance)?
+            .triangulate_refined(Refinement::new().with_max_area(max_area))?;
+
+        assert_eq!(unrefined.triangles().count(), 2);
+
+        let mut total_area = Scalar::ZERO;
+        for triangle in refined.triangles() {
+            let [a, b, c] = triangle.inner.points();
+            let area = (b - a).cross(&(c - a)).magnitude() / 2.;
+
+            assert!(area <= max_area);
+            total_area += area;
+        }
+
+        // Refinement must not change the area that the triangles cover.
+        assert!((total_area - Scalar::from(16.)).abs() < Scalar::from(1e-9));
+
+        Ok(())
+    }
+
+    #[test]
+    fn refined_min_angle() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        // A square with a long, thin spike. The edges are split into many
+        // short segments, which the unrefined triangulation connects with long
+        // and thin triangles.
+        let corners = [
+            [0., 0.],
+            [8., 0.],
+            [8., 8.],
+            [4.2, 8.],
+            [4., 16.],
+            [3.8, 8.],
+            [0., 8.],
+        ]
+        .map(Point::from);
+        let points = corners
+            .iter()
+            .zip(corners.iter().cycle().skip(1))
+            .flat_map(|(&a, &b)| {
+                let n =
+                    ((b - a).magnitude() / Scalar::from(0.5)).ceil().into_u64();
+                (0..n).map(move |i| a + (b - a) * i as f64 / n as f64)
+            })
+            .collect::<Vec<_>>();
+
+        let face =
+            Face::unbound(core.services.objects.surfaces.xy_plane(), &mut core)
+                .update_region(|region| {
+                    region
+                        .update_exterior(|_| {
+                            Cycle::polygon(points, &mut core)
+                                .insert(&mut core.services)
+                        })
+                        .insert(&mut core.services)
+                });
+
+        let tolerance = Tolerance::from_scalar(Scalar::ONE)?;
+        let min_angle = Scalar::PI / 9.;
+
+        let refined = face
+            .approx(tolerance)?
+            .triangulate_refined(Refinement::new().with_min_angle(min_angle))?;
+
+        let boundary = corners.map(|point| point.to_xyz());
+        let is_on_boundary = |point: Point<3>| {
+            boundary.iter().zip(boundary.iter().cycle().skip(1)).any(
+                |(&a, &b)| {
+                    let ab = b - a;
+                    let t = ((point - a).dot(&ab) / ab.dot(&ab))
+                        .max(Scalar::ZERO)
+                        .min(Scalar::ONE);
+                    point.distance_to(&(a + ab * t)) < Scalar::from(1e-9)
+                },
+            )
+        };
+
+        // Triangles that are adjacent to the boundary might not meet the
+        // minimum angle, as the boundary isn't refined for that. All others
+        // must.
+        let mut num_interior = 0;
+        for triangle in refined.triangles() {
+            let points = triangle.inner.points();
+            if points.into_iter().any(is_on_boundary) {
+                continue;
+            }
+            num_interior += 1;
+
+            for i in 0..3 {
+                let [a, b, c] = [0, 1, 2].map(|j| points[(i + j) % 3]);
+                let [ab, ac] = [b - a, c - a];
+                let angle =
+                    (ab.dot(&ac) / (ab.magnitude() * ac.magnitude())).acos();
+                assert!(
+                    angle >= min_angle - Scalar::from(1e-9),
+                    "angle {angle:?} of {points:?} is too small",
+                );
+            }
+        }
+
+        // Without refinement, all triangles are adjacent to the boundary.
+        assert!(num_interior > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cache_reuses_triangulation() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let face =
+            Face::unbound(core.services.objects.surfaces.xy_plane(), &mut core)
+                .update_region(|region| {
+                    region
+                        .update_exterior(|_| {
+                            Cycle::polygon(
+                                [[0., 0.], [4., 0.], [4., 4.], [0., 4.]],
+                                &mut core,
+                            )
+                            .insert(&mut core.services)
+                        })
+                        .insert(&mut core.services)
+                });
+
+        let tolerance = Tolerance::from_scalar(Scalar::ONE)?;
+        let mut cache = TriangulationCache::new();
+
+        let first = face
+            .approx(tolerance)?
+            .triangulate_with_cache(None, &mut cache)?;
+        let second = face
+            .approx(tolerance)?
+            .triangulate_with_cache(None, &mut cache)?;
+
+        assert_eq!(
+            first.triangles().collect::<Vec<_>>(),
+            second.triangles().collect::<Vec<_>>(),
+        );
+        assert_eq!(cache.faces.len(), 1);
+
+        face.approx(tolerance)?.triangulate_with_cache(
+            Some(Refinement::new().with_max_area(1.)),
+            &mut cache,
+        )?;
+        assert_eq!(cache.faces.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cache_respects_changed_surface() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let surface = core.services.objects.surfaces.xy_plane();
+        let face =
+            Face::unbound(surface.clone(), &mut core).update_region(|region| {
+                region
+                    .update_exterior(|_| {
+                        Cycle::polygon(
+                            [[0., 0.], [4., 0.], [4., 4.], [0., 4.]],
+                            &mut core,
+                        )
+                        .insert(&mut core.services)
+                    })
+                    .insert(&mut core.services)
+            });
+
+        // Same region, and therefore the same curves and vertices, but on a
+        // surface that has been moved.
+        let moved_surface = Surface::new(
+            surface
+                .geometry()
+                .transform(&Transform::translation([0., 0., 1.])),
+        )
+        .insert(&mut core.services);
+        let moved_face = Face::new(moved_surface, face.region().clone());
+
+        let tolerance = Tolerance::from_scalar(Scalar::ONE)?;
+        let mut cache = TriangulationCache::new();
+
+        cache.approx.start_shape();
+        let approx = (&face).approx_with_cache(tolerance, &mut cache.approx)?;
+        approx.triangulate_with_cache(None, &mut cache)?;
+
+        cache.approx.start_shape();
+        let moved_approx =
+            (&moved_face).approx_with_cache(tolerance, &mut cache.approx)?;
+        assert_eq!(moved_approx, (&moved_face).approx(tolerance)?);
+
+        let mesh = moved_approx.triangulate_with_cache(None, &mut cache)?;
+        assert!(mesh
+            .triangles()
+            .flat_map(|triangle| triangle.inner.points())
+            .all(|point| point.z == Scalar::ONE));
+
+        cache.evict_unused();
+        assert_eq!(cache.faces.len(), 2);
+
+        // Only the moved face is used after this eviction, so the original one
+        // is forgotten on the next.
+        cache.approx.start_shape();
+        (&moved_face)
+            .approx_with_cache(tolerance, &mut cache.approx)?
+            .triangulate_with_cache(None, &mut cache)?;
+        cache.evict_unused();
+        assert_eq!(cache.faces.len(), 1);
+
+        Ok(())
+    }
+
     fn triangulate(face: Face) -> anyhow::Result<Mesh<Point<3>>> {
         let tolerance = Tolerance::from_scalar(Scalar::ONE)?;
-        Ok(face.approx(tolerance).triangulate())
+        Ok(face.approx(tolerance)?.triangulate()?)
     }
 }
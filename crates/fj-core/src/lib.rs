@@ -86,6 +86,7 @@ pub mod geometry;
 pub mod objects;
 pub mod operations;
 pub mod queries;
+pub mod serialize;
 pub mod services;
 pub mod storage;
 pub mod validate;
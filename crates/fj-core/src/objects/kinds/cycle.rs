@@ -29,32 +29,42 @@ impl Cycle {
     /// Please note that this is not *the* winding of the cycle, only one of the
     /// two possible windings, depending on the direction you look at the
     /// surface that the cycle is defined on from.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the winding can't be determined. See [`Cycle::try_winding`]
+    /// for a non-panicking version of this method.
     pub fn winding(&self) -> Winding {
+        self.try_winding()
+            .unwrap_or_else(|| panic!("Encountered invalid cycle: {self:#?}"))
+    }
+
+    /// Indicate the cycle's winding, if it can be determined
+    ///
+    /// Returns `None`, if the cycle is empty, if it has less than 3 edges that
+    /// aren't all circles, or if it doesn't have any area.
+    ///
+    /// See [`Cycle::winding`] for more information.
+    pub fn try_winding(&self) -> Option<Winding> {
         // The cycle could be made up of one or two circles. If that is the
         // case, the winding of the cycle is determined by the winding of the
         // first circle.
         if self.half_edges.len() < 3 {
-            let first = self
-                .half_edges()
-                .iter()
-                .next()
-                .expect("Invalid cycle: expected at least one edge");
+            let first = self.half_edges().iter().next()?;
 
             let [a, b] = first.boundary().inner;
             let edge_direction_positive = a < b;
 
             let circle = match first.path() {
                 SurfacePath::Circle(circle) => circle,
-                SurfacePath::Line(_) => unreachable!(
-                    "Invalid cycle: less than 3 edges, but not all are circles"
-                ),
+                SurfacePath::Line(_) => return None,
             };
             let cross_positive = circle.a().cross2d(&circle.b()) > Scalar::ZERO;
 
             if edge_direction_positive == cross_positive {
-                return Winding::Ccw;
+                return Some(Winding::Ccw);
             } else {
-                return Winding::Cw;
+                return Some(Winding::Cw);
             }
         }
 
@@ -71,12 +81,12 @@ impl Cycle {
         }
 
         if sum > Scalar::ZERO {
-            return Winding::Cw;
+            return Some(Winding::Cw);
         }
         if sum < Scalar::ZERO {
-            return Winding::Ccw;
+            return Some(Winding::Ccw);
         }
 
-        unreachable!("Encountered invalid cycle: {self:#?}");
+        None
     }
 }
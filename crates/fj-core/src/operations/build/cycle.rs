@@ -1,7 +1,8 @@
-use fj_math::{Point, Scalar};
+use fj_math::{Line, Point, Scalar, Vector};
 use itertools::Itertools;
 
 use crate::{
+    geometry::SurfacePath,
     objects::{Cycle, HalfEdge},
     operations::{build::BuildHalfEdge, insert::Insert, update::UpdateCycle},
     Instance,
@@ -30,6 +31,10 @@ pub trait BuildCycle {
     }
 
     /// Build a polygon
+    ///
+    /// Degenerate polygons, whose points are too close together or whose edges
+    /// intersect each other, result in validation errors when the cycle is
+    /// inserted. This includes polygons with identical consecutive points.
     fn polygon<P, Ps>(points: Ps, core: &mut Instance) -> Cycle
     where
         P: Into<Point<2>>,
@@ -41,8 +46,21 @@ pub trait BuildCycle {
             .map(Into::into)
             .circular_tuple_windows()
             .map(|(start, end)| {
-                HalfEdge::line_segment([start, end], None, core)
-                    .insert(&mut core.services)
+                let half_edge = if start == end {
+                    // There's no line through identical points. Build a
+                    // half-edge of zero length on an arbitrary line instead,
+                    // so validation can report it.
+                    let path =
+                        SurfacePath::Line(Line::from_origin_and_direction(
+                            start,
+                            Vector::unit_u(),
+                        ));
+                    HalfEdge::unjoined(path, [[0.], [0.]], core)
+                } else {
+                    HalfEdge::line_segment([start, end], None, core)
+                };
+
+                half_edge.insert(&mut core.services)
             });
 
         Cycle::new(edges)
@@ -31,7 +31,7 @@ pub use self::{
     face::{BuildFace, Polygon},
     half_edge::BuildHalfEdge,
     region::BuildRegion,
-    shell::{BuildShell, TetrahedronShell},
+    shell::{BuildShell, BuildShellError, TetrahedronShell},
     sketch::BuildSketch,
     solid::{BuildSolid, Tetrahedron},
     surface::BuildSurface,
@@ -1,13 +1,13 @@
 use std::collections::BTreeMap;
 
 use fj_interop::ext::ArrayExt;
-use fj_math::Point;
+use fj_math::{Point, Scalar, Vector};
 
 use crate::{
     geometry::CurveBoundary,
-    objects::{Curve, Face, HalfEdge, Shell, Surface, Vertex},
+    objects::{Curve, Cycle, Face, HalfEdge, Shell, Surface, Vertex},
     operations::{
-        build::{BuildFace, BuildHalfEdge, BuildSurface, Polygon},
+        build::{BuildCycle, BuildFace, BuildHalfEdge, BuildSurface, Polygon},
         insert::{Insert, IsInserted, IsInsertedNo, IsInsertedYes},
         join::JoinCycle,
         reverse::ReverseCurveCoordinateSystems,
@@ -120,6 +120,159 @@ pub trait BuildShell {
         Shell::empty().add_faces(faces)
     }
 
+    /// Build a polyhedron by specifying its vertices and polygonal faces
+    ///
+    /// Each face is specified by a list of cycles, each of which is a list of
+    /// indices into `vertices`. The first cycle is the exterior of the face, any
+    /// further cycles are holes within it.
+    ///
+    /// All vertices of a face must be coplanar. The exterior must be
+    /// counter-clockwise when viewed from the outside of the polyhedron, while
+    /// the holes must be clockwise.
+    ///
+    /// Returns an error, if a face refers to a vertex that doesn't exist, or if
+    /// its exterior doesn't span a plane.
+    fn from_vertices_and_polygons(
+        vertices: impl IntoIterator<Item = impl Into<Point<3>>>,
+        faces: impl IntoIterator<Item = Vec<Vec<usize>>>,
+        core: &mut Instance,
+    ) -> Result<Shell, BuildShellError> {
+        let vertices = vertices
+            .into_iter()
+            .enumerate()
+            .map(|(index, position)| {
+                let vertex = Vertex::new().insert(&mut core.services);
+                let position = position.into();
+
+                (index, (vertex, position))
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let mut curves = BTreeMap::new();
+        let mut shell_faces = Vec::new();
+
+        for (face, cycles) in faces.into_iter().enumerate() {
+            let position = |index: &usize| {
+                vertices.get(index).ok_or(BuildShellError::InvalidIndex {
+                    face,
+                    index: *index,
+                })
+            };
+
+            for index in cycles.iter().flatten() {
+                position(index)?;
+            }
+
+            let exterior = cycles
+                .first()
+                .map(|exterior| {
+                    exterior
+                        .iter()
+                        .map(|index| {
+                            position(index).map(|(_, position)| *position)
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .transpose()?
+                .unwrap_or_default();
+            if exterior.len() < 3 {
+                return Err(BuildShellError::DegenerateExterior { face });
+            }
+
+            // The surface is defined by the first two points of the exterior,
+            // and the one that is farthest from the line through them. The
+            // normal of the polygon is used to choose the side of that line,
+            // so the exterior ends up counter-clockwise on the surface.
+            let normal = exterior
+                .iter()
+                .zip(exterior.iter().cycle().skip(1))
+                .fold(Vector::from([0., 0., 0.]), |normal, (a, b)| {
+                    normal + a.coords.cross(&b.coords)
+                });
+            let [a, b] = [exterior[0], exterior[1]];
+            let c = exterior
+                .iter()
+                .copied()
+                .max_by_key(|c| (b - a).cross(&(c - a)).dot(&normal))
+                .unwrap_or(a);
+            if (b - a).cross(&(c - a)).magnitude() == Scalar::ZERO {
+                return Err(BuildShellError::DegenerateExterior { face });
+            }
+
+            let (surface, _) = Surface::plane_from_points([a, b, c]);
+            let geometry = surface.geometry();
+            let surface = surface.insert(&mut core.services);
+
+            let mut region_cycles = Vec::new();
+            for cycle in &cycles {
+                let mut half_edges = Vec::new();
+
+                for (a, b) in cycle.iter().zip(cycle.iter().cycle().skip(1)) {
+                    // All indices have been checked above.
+                    let [(a, a_pos), (b, b_pos)] =
+                        [a, b].map(|index| &vertices[index]);
+
+                    let boundary =
+                        CurveBoundary::<Vertex>::from([a, b].map(Clone::clone));
+                    let (curve, boundary) = curves
+                        .get(&boundary.clone().reverse())
+                        .cloned()
+                        .unwrap_or_else(|| {
+                            let curve = Curve::new().insert(&mut core.services);
+                            let curve_boundary =
+                                CurveBoundary::<Point<1>>::from([[0.], [1.]]);
+
+                            curves.insert(
+                                boundary,
+                                (curve.clone(), curve_boundary),
+                            );
+
+                            (curve, curve_boundary.reverse())
+                        });
+
+                    let positions = [a_pos, b_pos].map(|position| {
+                        geometry.project_global_point(*position)
+                    });
+
+                    let half_edge = HalfEdge::line_segment(
+                        positions,
+                        Some(boundary.reverse().inner),
+                        core,
+                    )
+                    .update_start_vertex(|_| a.clone())
+                    .update_curve(|_| curve)
+                    .insert(&mut core.services);
+
+                    half_edges.push(half_edge);
+                }
+
+                region_cycles.push(
+                    Cycle::empty()
+                        .add_half_edges(half_edges)
+                        .insert(&mut core.services),
+                );
+            }
+
+            let mut region_cycles = region_cycles.into_iter();
+            let Some(exterior) = region_cycles.next() else {
+                unreachable!("Exterior has been checked above");
+            };
+
+            let face = Face::unbound(surface, core)
+                .update_region(|region| {
+                    region
+                        .update_exterior(|_| exterior)
+                        .add_interiors(region_cycles)
+                        .insert(&mut core.services)
+                })
+                .insert(&mut core.services);
+
+            shell_faces.push(face);
+        }
+
+        Ok(Shell::empty().add_faces(shell_faces))
+    }
+
     /// Build a tetrahedron from the provided points
     ///
     /// Accepts 4 points, naturally. For the purposes of the following
@@ -273,6 +426,29 @@ pub trait BuildShell {
 
 impl BuildShell for Shell {}
 
+/// Error building a shell from vertices and polygons
+///
+/// Returned by [`BuildShell::from_vertices_and_polygons`].
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum BuildShellError {
+    /// A face refers to a vertex that doesn't exist
+    #[error("Face {face} refers to vertex {index}, which doesn't exist")]
+    InvalidIndex {
+        /// The index of the face
+        face: usize,
+
+        /// The index of the vertex
+        index: usize,
+    },
+
+    /// The exterior of a face is missing, or its vertices don't span a plane
+    #[error("Exterior of face {face} is missing or doesn't span a plane")]
+    DegenerateExterior {
+        /// The index of the face
+        face: usize,
+    },
+}
+
 /// A tetrahedron
 ///
 /// A tetrahedron is constructed from 4 points and has 4 faces. For the purpose
@@ -296,3 +472,79 @@ pub struct TetrahedronShell<I: IsInserted = IsInsertedNo> {
     /// The face formed by the points `c`, `b`, and `d`.
     pub cbd: Polygon<3, IsInsertedYes>,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        objects::{Shell, Solid},
+        operations::insert::Insert,
+        validate::Validate,
+        Instance,
+    };
+
+    use super::{BuildShell, BuildShellError};
+
+    #[test]
+    fn from_vertices_and_polygons() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        // A pyramid with a square base
+        let vertices = [
+            [0., 0., 0.],
+            [1., 0., 0.],
+            [1., 1., 0.],
+            [0., 1., 0.],
+            [0.5, 0.5, 1.],
+        ];
+        let faces = [
+            vec![vec![0, 3, 2, 1]],
+            vec![vec![0, 1, 4]],
+            vec![vec![1, 2, 4]],
+            vec![vec![2, 3, 4]],
+            vec![vec![3, 0, 4]],
+        ];
+
+        let shell =
+            Shell::from_vertices_and_polygons(vertices, faces, &mut core)?;
+        assert_eq!(shell.faces().len(), 5);
+        shell.validate_and_return_first_error()?;
+
+        let shell = shell.insert(&mut core.services);
+        let _solid = Solid::new([shell]).insert(&mut core.services);
+
+        core.services.drop_and_validate()?;
+        Ok(())
+    }
+
+    #[test]
+    fn from_invalid_polygons() {
+        let mut core = Instance::new();
+
+        let vertices = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [2., 0., 0.]];
+        let mut build = |faces: Vec<Vec<Vec<usize>>>| {
+            Shell::from_vertices_and_polygons(vertices, faces, &mut core)
+                .map(|_| ())
+        };
+
+        assert_eq!(
+            build(vec![vec![vec![0, 1, 2]], vec![vec![0, 1, 4]]]),
+            Err(BuildShellError::InvalidIndex { face: 1, index: 4 }),
+        );
+        assert_eq!(
+            build(vec![vec![vec![0, 1, 2], vec![7]]]),
+            Err(BuildShellError::InvalidIndex { face: 0, index: 7 }),
+        );
+        assert_eq!(
+            build(vec![vec![]]),
+            Err(BuildShellError::DegenerateExterior { face: 0 }),
+        );
+        assert_eq!(
+            build(vec![vec![vec![0, 1]]]),
+            Err(BuildShellError::DegenerateExterior { face: 0 }),
+        );
+        assert_eq!(
+            build(vec![vec![vec![0, 1, 3]]]),
+            Err(BuildShellError::DegenerateExterior { face: 0 }),
+        );
+    }
+}
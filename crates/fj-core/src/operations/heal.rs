@@ -0,0 +1,744 @@
+//! # Repair common validation failures
+//!
+//! See [`HealShell`], which is currently the only trait in this module, for
+//! more information.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use fj_math::{Aabb, Bvh, Circle, Line, Point, Scalar};
+
+use crate::{
+    geometry::{CurveBoundary, SurfacePath},
+    objects::{Cycle, Face, HalfEdge, Region, Shell, Surface, Vertex},
+    queries::AllHalfEdgesWithSurface,
+    services::ValidationCommand,
+    storage::{Handle, ObjectId},
+    validate::{ValidationCheck, ValidationConfig},
+    Instance,
+};
+
+use super::{
+    insert::Insert,
+    replace::ReplaceHalfEdge,
+    reverse::Reverse,
+    update::{UpdateFace, UpdateHalfEdge, UpdateRegion, UpdateShell},
+};
+
+/// Repair common validation failures of a [`Shell`]
+///
+/// Shells that come from imported meshes, or from models that were created
+/// before some validation checks existed, often fail validation in a few
+/// common ways. This trait repairs those, so the shell can be used without
+/// manual intervention.
+///
+/// The repairs are applied in the following order:
+///
+/// 1. Interior cycles that are wound the same way as the exterior cycle of
+///    their face are reversed.
+/// 2. Distinct vertices that coincide are merged. Vertices are not moved, so
+///    if their positions are further apart than
+///    [`ValidationConfig::identical_max_distance`], the result is still
+///    invalid.
+/// 3. Half-edges that coincide with a half-edge running in the opposite
+///    direction, and are bounded by the same vertices, are made its sibling.
+///    They are moved onto the curve of the other half-edge, if they are on a
+///    different one, and their curve coordinate system is aligned with it. If
+///    they are already on the same curve, this reverses their curve
+///    coordinate system, in the usual case.
+///
+/// The distances are judged according to the validation configuration of the
+/// provided [`Instance`].
+///
+/// Cycles whose winding can't be determined are left alone, as are the other
+/// problems of a shell. Validating the repaired shell reveals those.
+pub trait HealShell {
+    /// Repair the shell
+    ///
+    /// Faces whose interior cycles are reversed have already been validated,
+    /// when they were inserted. The resulting validation errors are resolved,
+    /// as those faces are replaced with repaired versions.
+    ///
+    /// If the shell itself has already been inserted, which is the case for
+    /// shells that were imported, the errors of the checks that the repairs
+    /// address are resolved for it too.
+    #[must_use]
+    fn heal(&self, core: &mut Instance) -> HealedShell;
+}
+
+impl HealShell for Shell {
+    fn heal(&self, core: &mut Instance) -> HealedShell {
+        let config = core.services.validation.config().clone();
+
+        let mut healed = HealedShell {
+            shell: self.clone(),
+            log: Vec::new(),
+        };
+
+        healed.reverse_interior_cycles(core);
+        healed.merge_vertices(&config, core);
+        healed.join_half_edges(&config, core);
+
+        healed
+    }
+}
+
+impl HealShell for Handle<Shell> {
+    fn heal(&self, core: &mut Instance) -> HealedShell {
+        let healed = (**self).heal(core);
+
+        let checks = healed
+            .log
+            .iter()
+            .flat_map(|repair| repair.checks().iter().copied())
+            .collect::<BTreeSet<_>>();
+        for check in checks {
+            core.services.validation.execute(
+                ValidationCommand::ResolveErrors {
+                    object: self.id(),
+                    check,
+                },
+                &mut Vec::new(),
+            );
+        }
+
+        healed
+    }
+}
+
+/// A shell that was repaired by [`HealShell::heal`]
+pub struct HealedShell {
+    /// The repaired shell
+    pub shell: Shell,
+
+    /// The repairs that were made, in the order they were made in
+    pub log: Vec<Repair>,
+}
+
+impl HealedShell {
+    fn reverse_interior_cycles(&mut self, core: &mut Instance) {
+        for face in self.shell.faces().clone() {
+            let Some(exterior_winding) = face.region().exterior().try_winding()
+            else {
+                continue;
+            };
+
+            let invalid = face
+                .region()
+                .interiors()
+                .iter()
+                .filter(|interior| {
+                    interior.try_winding() == Some(exterior_winding)
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            if invalid.is_empty() {
+                continue;
+            }
+
+            let mut region = face.region().clone_object();
+            for cycle in invalid {
+                let reversed = cycle.reverse(core).insert(&mut core.services);
+                region = region.update_interior(&cycle, |_| [reversed]);
+
+                self.log.push(Repair::ReversedInteriorCycle {
+                    face: face.clone(),
+                    cycle,
+                });
+            }
+            let region = region.insert(&mut core.services);
+
+            let repaired =
+                face.update_region(|_| region).insert(&mut core.services);
+            self.shell = self.shell.update_face(&face, |_| [repaired]);
+
+            core.services.validation.execute(
+                ValidationCommand::ResolveErrors {
+                    object: face.id(),
+                    check: ValidationCheck::FaceInteriorWinding,
+                },
+                &mut Vec::new(),
+            );
+        }
+    }
+
+    fn merge_vertices(
+        &mut self,
+        config: &ValidationConfig,
+        core: &mut Instance,
+    ) {
+        let mut half_edges = Vec::new();
+        self.shell.all_half_edges_with_surface(&mut half_edges);
+
+        // Every vertex is represented by the position of its first occurrence.
+        let mut seen = BTreeSet::new();
+        let vertices = half_edges
+            .iter()
+            .filter(|(half_edge, _)| seen.insert(half_edge.start_vertex().id()))
+            .map(|(half_edge, surface)| {
+                let position = surface
+                    .geometry()
+                    .point_from_surface_coords(half_edge.start_position());
+                (position, half_edge.start_vertex().clone())
+            })
+            .collect::<Vec<_>>();
+
+        // Comparing all vertices with each other would be O(N^2). Only those
+        // that are close to each other can be merged, and we find those using
+        // a spatial index.
+        let point_aabb = |&position: &Point<3>| Aabb {
+            min: position,
+            max: position,
+        };
+        let bvh =
+            Bvh::new(vertices.iter().map(|(position, _)| point_aabb(position)));
+
+        // A vertex is merged into the first earlier vertex that it coincides
+        // with, and which is kept itself.
+        let mut merged_into: Vec<Option<usize>> = Vec::new();
+        for (i, (position, _)) in vertices.iter().enumerate() {
+            let aabb =
+                point_aabb(position).loosened(config.distinct_min_distance);
+            let replacement = bvh
+                .intersecting(&aabb)
+                .into_iter()
+                .take_while(|&j| j < i)
+                .find(|&j| {
+                    merged_into[j].is_none()
+                        && vertices[j].0.distance_to(position)
+                            < config.distinct_min_distance
+                });
+
+            merged_into.push(replacement);
+        }
+
+        let mut replacements = BTreeMap::new();
+        for (i, replacement) in merged_into.into_iter().enumerate() {
+            let Some(j) = replacement else {
+                continue;
+            };
+            let vertex = vertices[i].1.clone();
+            let replacement = vertices[j].1.clone();
+
+            replacements.insert(vertex.id(), replacement.clone());
+            self.log.push(Repair::MergedVertices {
+                vertex,
+                replacement,
+            });
+        }
+
+        if !replacements.is_empty() {
+            self.shell = replace_vertices(&self.shell, &replacements, core);
+        }
+    }
+
+    fn join_half_edges(
+        &mut self,
+        config: &ValidationConfig,
+        core: &mut Instance,
+    ) {
+        let mut half_edges = Vec::new();
+        self.shell.all_half_edges_with_surface(&mut half_edges);
+
+        // Querying the bounding vertices of each half-edge separately would
+        // require a search through the whole shell every time. Determine them
+        // all in one pass instead. Replacing half-edges below doesn't change
+        // the bounding vertices of the others.
+        let mut bounding_vertices = BTreeMap::new();
+        for face in self.shell.faces() {
+            for cycle in face.region().all_cycles() {
+                for (half_edge, next) in cycle.half_edges().pairs() {
+                    bounding_vertices.entry(half_edge.id()).or_insert([
+                        half_edge.start_vertex().id(),
+                        next.start_vertex().id(),
+                    ]);
+                }
+            }
+        }
+
+        // Only half-edges with the same bounding vertices, in reverse order,
+        // can be siblings. Index them by their bounding vertices, to find the
+        // candidates without comparing all pairs.
+        let mut by_vertices = BTreeMap::<_, Vec<usize>>::new();
+        for (i, (half_edge, _)) in half_edges.iter().enumerate() {
+            if let Some(&vertices) = bounding_vertices.get(&half_edge.id()) {
+                by_vertices.entry(vertices).or_default().push(i);
+            }
+        }
+
+        // Half-edges that already have a sibling, or have been made one
+        let mut done = BTreeSet::<ObjectId>::new();
+
+        for (a, surface_a) in &half_edges {
+            if done.contains(&a.id()) {
+                continue;
+            }
+            let Some(&[start, end]) = bounding_vertices.get(&a.id()) else {
+                continue;
+            };
+            let Some(candidates) = by_vertices.get(&[end, start]) else {
+                continue;
+            };
+
+            for &i in candidates {
+                let (b, surface_b) = &half_edges[i];
+                if a.id() == b.id() || done.contains(&b.id()) {
+                    continue;
+                }
+
+                let are_siblings = a.curve().id() == b.curve().id()
+                    && a.boundary() == b.boundary().reverse();
+                if are_siblings {
+                    done.extend([a.id(), b.id()]);
+                    break;
+                }
+
+                let distance =
+                    midpoint(a, surface_a).distance_to(&midpoint(b, surface_b));
+                if distance >= config.distinct_min_distance {
+                    continue;
+                }
+
+                let Some(path) = align_path(b, a.boundary().reverse()) else {
+                    continue;
+                };
+                let sibling = HalfEdge::new(
+                    path,
+                    a.boundary().reverse(),
+                    a.curve().clone(),
+                    b.start_vertex().clone(),
+                )
+                .insert(&mut core.services);
+
+                self.shell = self
+                    .shell
+                    .replace_half_edge(b, [sibling], core)
+                    .into_inner();
+
+                let repair = if a.curve().id() == b.curve().id() {
+                    Repair::AlignedCurveCoordinates {
+                        half_edge: b.clone(),
+                        sibling: a.clone(),
+                    }
+                } else {
+                    Repair::JoinedHalfEdges {
+                        half_edge: b.clone(),
+                        sibling: a.clone(),
+                    }
+                };
+                self.log.push(repair);
+
+                done.extend([a.id(), b.id()]);
+                break;
+            }
+        }
+    }
+}
+
+/// A repair made by [`HealShell::heal`]
+///
+/// The handles refer to the objects as they were, when the repair was made.
+/// Those might be intermediate versions, that were created by earlier repairs,
+/// and are not referenced by the original or the repaired shell.
+#[derive(Clone, Debug)]
+pub enum Repair {
+    /// An interior cycle of a face was reversed
+    ///
+    /// This repairs [`FaceValidationError::InvalidInteriorWinding`].
+    ///
+    /// [`FaceValidationError::InvalidInteriorWinding`]: crate::validate::FaceValidationError::InvalidInteriorWinding
+    ReversedInteriorCycle {
+        /// The face that the cycle is an interior of
+        face: Handle<Face>,
+
+        /// The cycle that was reversed
+        cycle: Handle<Cycle>,
+    },
+
+    /// A vertex was replaced with a coincident one
+    ///
+    /// This repairs [`SolidValidationError::DistinctVerticesCoincide`], once
+    /// the shell is part of a solid.
+    ///
+    /// [`SolidValidationError::DistinctVerticesCoincide`]: crate::validate::SolidValidationError::DistinctVerticesCoincide
+    MergedVertices {
+        /// The vertex that was replaced
+        vertex: Handle<Vertex>,
+
+        /// The vertex it was replaced with
+        replacement: Handle<Vertex>,
+    },
+
+    /// A half-edge was moved onto the curve of a coincident half-edge
+    ///
+    /// This repairs
+    /// [`ShellValidationError::CoincidentHalfEdgesAreNotSiblings`].
+    ///
+    /// [`ShellValidationError::CoincidentHalfEdgesAreNotSiblings`]: crate::validate::ShellValidationError::CoincidentHalfEdgesAreNotSiblings
+    JoinedHalfEdges {
+        /// The half-edge that was replaced
+        half_edge: Handle<HalfEdge>,
+
+        /// The half-edge that it is now a sibling of
+        sibling: Handle<HalfEdge>,
+    },
+
+    /// The curve coordinate system of a half-edge was aligned with that of a
+    /// half-edge on the same curve
+    ///
+    /// This usually means that the curve coordinate system was reversed. It
+    /// repairs [`ShellValidationError::CurveCoordinateSystemMismatch`].
+    ///
+    /// [`ShellValidationError::CurveCoordinateSystemMismatch`]: crate::validate::ShellValidationError::CurveCoordinateSystemMismatch
+    AlignedCurveCoordinates {
+        /// The half-edge that was replaced
+        half_edge: Handle<HalfEdge>,
+
+        /// The half-edge that it is now a sibling of
+        sibling: Handle<HalfEdge>,
+    },
+}
+
+impl Repair {
+    /// The validation checks whose failures this repair addresses
+    pub fn checks(&self) -> &'static [ValidationCheck] {
+        match self {
+            Self::ReversedInteriorCycle { .. } => {
+                &[ValidationCheck::FaceInteriorWinding]
+            }
+            Self::MergedVertices { .. } => &[ValidationCheck::SolidVertices],
+            Self::JoinedHalfEdges { .. } => &[
+                ValidationCheck::ShellHalfEdgeCoincidence,
+                ValidationCheck::ShellHalfEdgePairs,
+            ],
+            Self::AlignedCurveCoordinates { .. } => &[
+                ValidationCheck::ShellCurveCoordinates,
+                ValidationCheck::ShellHalfEdgePairs,
+            ],
+        }
+    }
+}
+
+/// Replace the start vertices of all half-edges in the shell, in a single pass
+///
+/// Replacing vertices one by one would require a pass over the whole shell for
+/// each of them.
+fn replace_vertices(
+    shell: &Shell,
+    replacements: &BTreeMap<ObjectId, Handle<Vertex>>,
+    core: &mut Instance,
+) -> Shell {
+    let replace_in_cycle = |cycle: &Handle<Cycle>, core: &mut Instance| {
+        let mut replaced = false;
+        let half_edges = cycle
+            .half_edges()
+            .iter()
+            .map(|half_edge| {
+                match replacements.get(&half_edge.start_vertex().id()) {
+                    Some(vertex) => {
+                        replaced = true;
+                        half_edge
+                            .update_start_vertex(|_| vertex.clone())
+                            .insert(&mut core.services)
+                    }
+                    None => half_edge.clone(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        replaced.then(|| Cycle::new(half_edges).insert(&mut core.services))
+    };
+
+    let faces = shell
+        .faces()
+        .iter()
+        .map(|face| {
+            let region = face.region();
+
+            let exterior = replace_in_cycle(region.exterior(), core);
+            let interiors = region
+                .interiors()
+                .iter()
+                .map(|interior| replace_in_cycle(interior, core))
+                .collect::<Vec<_>>();
+
+            if exterior.is_none() && interiors.iter().all(Option::is_none) {
+                return face.clone();
+            }
+
+            let exterior =
+                exterior.unwrap_or_else(|| region.exterior().clone());
+            let interiors = interiors.into_iter().zip(region.interiors()).map(
+                |(replaced, original)| {
+                    replaced.unwrap_or_else(|| original.clone())
+                },
+            );
+            let region = Region::new(exterior, interiors, region.color())
+                .insert(&mut core.services);
+
+            Face::new(face.surface().clone(), region).insert(&mut core.services)
+        })
+        .collect::<Vec<_>>();
+
+    Shell::new(faces)
+}
+
+fn midpoint(
+    half_edge: &Handle<HalfEdge>,
+    surface: &Handle<Surface>,
+) -> Point<3> {
+    let [start, end] = half_edge.boundary().inner;
+    let point = half_edge
+        .path()
+        .point_from_path_coords(start + (end - start) / 2.);
+
+    surface.geometry().point_from_surface_coords(point)
+}
+
+/// Compute a path that covers the half-edge, when bounded by `boundary`
+///
+/// The new path is a linear re-parametrization of the half-edge's path. It
+/// maps the start of `boundary` to the start of the half-edge, and the end to
+/// its end. Returns `None`, if that can't be represented, because a circle
+/// would need to be traversed at a different speed.
+fn align_path(
+    half_edge: &HalfEdge,
+    boundary: CurveBoundary<Point<1>>,
+) -> Option<SurfacePath> {
+    let [from_start, from_end] =
+        half_edge.boundary().inner.map(|point| point.t);
+    let [to_start, to_end] = boundary.inner.map(|point| point.t);
+
+    // `path(t)` becomes `path(scale * t + offset)`.
+    let scale = (from_end - from_start) / (to_end - to_start);
+    let offset = from_start - to_start * scale;
+
+    let path = match half_edge.path() {
+        SurfacePath::Line(line) => {
+            SurfacePath::Line(Line::from_origin_and_direction(
+                line.point_from_line_coords([offset]),
+                line.direction() * scale,
+            ))
+        }
+        SurfacePath::Circle(circle) => {
+            if (scale.abs() - Scalar::ONE).abs() > Scalar::from_f64(1e-9) {
+                return None;
+            }
+            let sign = if scale.is_negative() {
+                -Scalar::ONE
+            } else {
+                Scalar::ONE
+            };
+
+            let (sin, cos) = offset.sin_cos();
+            let [a, b] = [circle.a(), circle.b()];
+
+            SurfacePath::Circle(Circle::new(
+                circle.center(),
+                a * cos + b * sin,
+                (b * cos - a * sin) * sign,
+            ))
+        }
+    };
+
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        objects::{Cycle, Face, Shell, Solid},
+        operations::{
+            build::{BuildCycle, BuildFace, BuildShell},
+            insert::Insert,
+            reverse::ReverseCurveCoordinateSystems,
+            update::{UpdateCycle, UpdateFace, UpdateRegion, UpdateShell},
+        },
+        validate::Validate,
+        Instance,
+    };
+
+    use super::{HealShell, Repair};
+
+    #[test]
+    fn triangle_soup() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        // Every face has its own vertices and curves, as if it was imported
+        // from a mesh file.
+        let [a, b, c, d] =
+            [[0., 0., 0.], [0., 1., 0.], [1., 0., 0.], [0., 0., 1.]];
+        let faces =
+            [[a, b, c], [b, a, d], [d, a, c], [c, b, d]].map(|points| {
+                Face::triangle(points, &mut core)
+                    .face
+                    .insert(&mut core.services)
+            });
+        let shell = Shell::new(faces);
+
+        let healed = shell.heal(&mut core);
+
+        let num_merged = healed
+            .log
+            .iter()
+            .filter(|repair| matches!(repair, Repair::MergedVertices { .. }))
+            .count();
+        let num_joined = healed
+            .log
+            .iter()
+            .filter(|repair| matches!(repair, Repair::JoinedHalfEdges { .. }))
+            .count();
+        assert_eq!(num_merged, 12 - 4);
+        assert_eq!(num_joined, 6);
+
+        healed.shell.validate_and_return_first_error()?;
+
+        let shell = healed.shell.insert(&mut core.services);
+        let _solid = Solid::new([shell]).insert(&mut core.services);
+        core.services.drop_and_validate()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn inserted_triangle_soup() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        // Imported shells are inserted before they are healed, so their
+        // validation errors have already been produced.
+        let [a, b, c, d] =
+            [[0., 0., 0.], [0., 1., 0.], [1., 0., 0.], [0., 0., 1.]];
+        let faces =
+            [[a, b, c], [b, a, d], [d, a, c], [c, b, d]].map(|points| {
+                Face::triangle(points, &mut core)
+                    .face
+                    .insert(&mut core.services)
+            });
+        let shell = Shell::new(faces).insert(&mut core.services);
+
+        let healed = shell.heal(&mut core);
+
+        let shell = healed.shell.insert(&mut core.services);
+        let _solid = Solid::new([shell]).insert(&mut core.services);
+        core.services.drop_and_validate()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn degenerate_interior_cycle() {
+        let mut core = Instance::new();
+
+        let face =
+            Face::unbound(core.services.objects.surfaces.xy_plane(), &mut core)
+                .update_region(|region| {
+                    region
+                        .update_exterior(|_| {
+                            Cycle::polygon(
+                                [[0., 0.], [3., 0.], [0., 3.]],
+                                &mut core,
+                            )
+                            .insert(&mut core.services)
+                        })
+                        .add_interiors([Cycle::polygon(
+                            [[1., 1.], [1.5, 1.], [2., 1.]],
+                            &mut core,
+                        )
+                        .insert(&mut core.services)])
+                        .insert(&mut core.services)
+                })
+                .insert(&mut core.services);
+
+        // The winding of the interior cycle can't be determined. It must be
+        // left alone, instead of causing a panic.
+        let healed = Shell::new([face]).heal(&mut core);
+        assert!(!healed.log.iter().any(|repair| matches!(
+            repair,
+            Repair::ReversedInteriorCycle { .. }
+        )));
+
+        core.services.validation.errors.clear();
+    }
+
+    #[test]
+    fn curve_coordinate_mismatch() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let tetrahedron = Shell::tetrahedron(
+            [[0., 0., 0.], [0., 1., 0.], [1., 0., 0.], [0., 0., 1.]],
+            &mut core,
+        );
+        let abc = tetrahedron.abc.face;
+        let [half_edge, ..] = tetrahedron.abc.half_edges;
+        let reversed = half_edge
+            .reverse_curve_coordinate_systems(&mut core)
+            .insert(&mut core.services);
+        let invalid = tetrahedron.shell.update_face(&abc, |face| {
+            [face
+                .update_region(|region| {
+                    region
+                        .update_exterior(|cycle| {
+                            cycle
+                                .update_half_edge(&half_edge, |_| [reversed])
+                                .insert(&mut core.services)
+                        })
+                        .insert(&mut core.services)
+                })
+                .insert(&mut core.services)]
+        });
+        assert!(invalid.validate_and_return_first_error().is_err());
+
+        let healed = invalid.heal(&mut core);
+
+        assert!(matches!(
+            healed.log.as_slice(),
+            [Repair::AlignedCurveCoordinates { .. }]
+        ));
+        healed.shell.validate_and_return_first_error()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn interior_winding() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let invalid =
+            Face::unbound(core.services.objects.surfaces.xy_plane(), &mut core)
+                .update_region(|region| {
+                    region
+                        .update_exterior(|_| {
+                            Cycle::polygon(
+                                [[0., 0.], [3., 0.], [0., 3.]],
+                                &mut core,
+                            )
+                            .insert(&mut core.services)
+                        })
+                        .add_interiors([Cycle::polygon(
+                            [[1., 1.], [1.5, 1.], [1., 1.5]],
+                            &mut core,
+                        )
+                        .insert(&mut core.services)])
+                        .insert(&mut core.services)
+                })
+                .insert(&mut core.services);
+        assert!(invalid.validate_and_return_first_error().is_err());
+
+        let healed = Shell::new([invalid]).heal(&mut core);
+
+        assert!(matches!(
+            healed.log.as_slice(),
+            [Repair::ReversedInteriorCycle { .. }]
+        ));
+        healed
+            .shell
+            .faces()
+            .only()
+            .validate_and_return_first_error()?;
+
+        // The error that was produced when inserting the invalid face has been
+        // resolved.
+        core.services.drop_and_validate()?;
+
+        Ok(())
+    }
+}
@@ -115,32 +115,39 @@ impl JoinCycle for Cycle {
             |cycle, (index, index_other)| {
                 let edge_other = other.half_edges().nth_circular(index_other);
 
-                cycle
-                    .update_half_edge(
-                        self.half_edges().nth_circular(index),
-                        |edge| {
-                            [edge
-                                .update_curve(|_| edge_other.curve().clone())
-                                .update_start_vertex(|_| {
-                                    other
-                                        .half_edges()
-                                        .nth_circular(index_other + 1)
-                                        .start_vertex()
-                                        .clone()
-                                })
-                                .insert(&mut core.services)]
-                        },
-                    )
-                    .update_half_edge(
-                        self.half_edges().nth_circular(index + 1),
-                        |edge| {
-                            [edge
-                                .update_start_vertex(|_| {
-                                    edge_other.start_vertex().clone()
-                                })
-                                .insert(&mut core.services)]
-                        },
-                    )
+                let cycle = cycle.update_half_edge(
+                    self.half_edges().nth_circular(index),
+                    |edge| {
+                        [edge
+                            .update_curve(|_| edge_other.curve().clone())
+                            .update_start_vertex(|_| {
+                                other
+                                    .half_edges()
+                                    .nth_circular(index_other + 1)
+                                    .start_vertex()
+                                    .clone()
+                            })
+                            .insert(&mut core.services)]
+                    },
+                );
+
+                // If the cycle consists of a single half-edge, its next
+                // half-edge is the one we just updated. Its start vertex has
+                // already been taken care of.
+                if self.half_edges().len() == 1 {
+                    return cycle;
+                }
+
+                cycle.update_half_edge(
+                    self.half_edges().nth_circular(index + 1),
+                    |edge| {
+                        [edge
+                            .update_start_vertex(|_| {
+                                edge_other.start_vertex().clone()
+                            })
+                            .insert(&mut core.services)]
+                    },
+                )
             },
         )
     }
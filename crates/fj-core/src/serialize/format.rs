@@ -0,0 +1,123 @@
+//! The structure of the serialized data
+//!
+//! Objects are stored in a list per object type. They refer to other objects
+//! by their index in the respective list, which preserves shared references.
+//! Referenced objects always appear in their list before the objects that
+//! reference them.
+
+use serde::{Deserialize, Serialize};
+
+/// The identifier of the file format
+pub const FORMAT: &str = "fornjot-objects";
+
+/// The current version of the file format
+///
+/// Increment this whenever the format changes in an incompatible way.
+pub const VERSION: u32 = 1;
+
+/// The part of the file that must be understood, to understand the rest
+#[derive(Deserialize)]
+pub struct Header {
+    pub format: String,
+    pub version: u32,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct File {
+    pub format: String,
+    pub version: u32,
+
+    /// The number of curves
+    ///
+    /// Curves don't contain any data. Only their identity matters.
+    pub curves: usize,
+
+    /// The number of vertices
+    ///
+    /// Vertices don't contain any data. Only their identity matters.
+    pub vertices: usize,
+
+    pub surfaces: Vec<Surface>,
+    pub half_edges: Vec<HalfEdge>,
+    pub cycles: Vec<Cycle>,
+    pub regions: Vec<Region>,
+    pub faces: Vec<Face>,
+    pub shells: Vec<Shell>,
+    pub sketches: Vec<Sketch>,
+    pub solids: Vec<Solid>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Surface {
+    pub u: GlobalPath,
+    pub v: [f64; 3],
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct HalfEdge {
+    pub path: SurfacePath,
+    pub boundary: [f64; 2],
+    pub curve: usize,
+    pub start_vertex: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Cycle {
+    pub half_edges: Vec<usize>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Region {
+    pub exterior: usize,
+    pub interiors: Vec<usize>,
+    pub color: Option<[u8; 4]>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Face {
+    pub surface: usize,
+    pub region: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Shell {
+    pub faces: Vec<usize>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Sketch {
+    pub regions: Vec<usize>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Solid {
+    pub shells: Vec<usize>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SurfacePath {
+    Circle {
+        center: [f64; 2],
+        a: [f64; 2],
+        b: [f64; 2],
+    },
+    Line {
+        origin: [f64; 2],
+        direction: [f64; 2],
+    },
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GlobalPath {
+    Circle {
+        center: [f64; 3],
+        a: [f64; 3],
+        b: [f64; 3],
+    },
+    Line {
+        origin: [f64; 3],
+        direction: [f64; 3],
+    },
+}
@@ -0,0 +1,291 @@
+use std::collections::BTreeSet;
+
+use fj_interop::Color;
+use fj_math::{Circle, InvalidCircle, Line, Point, Scalar, Vector};
+
+use crate::{
+    geometry::{GlobalPath, SurfaceGeometry, SurfacePath},
+    objects::{
+        Curve, Cycle, Face, HalfEdge, Region, Shell, Sketch, Solid, Surface,
+        Vertex,
+    },
+    operations::insert::Insert,
+    storage::Handle,
+    Instance,
+};
+
+use super::{format, LoadedObjects, SerializationError};
+
+/// Insert the objects from the file into the instance
+///
+/// Everything is checked before the first object is inserted, so the stores
+/// are left untouched, if the file turns out to be invalid.
+pub fn load(
+    file: format::File,
+    core: &mut Instance,
+) -> Result<LoadedObjects, SerializationError> {
+    let surfaces = file
+        .surfaces
+        .iter()
+        .enumerate()
+        .map(|(index, surface)| {
+            let u = global_path(&surface.u)
+                .map_err(|reason| invalid_geometry("surface", index, reason))?;
+            let v = Vector::from(surface.v);
+
+            Ok::<_, SerializationError>(SurfaceGeometry { u, v })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let paths = file
+        .half_edges
+        .iter()
+        .enumerate()
+        .map(|(index, half_edge)| {
+            surface_path(&half_edge.path)
+                .map_err(|reason| invalid_geometry("half-edge", index, reason))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    check_references(&file)?;
+
+    let mut loaded = LoadedObjects::default();
+    let services = &mut core.services;
+
+    for _ in 0..file.curves {
+        loaded.curves.push(Curve::new().insert(services));
+    }
+    for _ in 0..file.vertices {
+        loaded.vertices.push(Vertex::new().insert(services));
+    }
+    for geometry in surfaces {
+        loaded
+            .surfaces
+            .push(Surface::new(geometry).insert(services));
+    }
+    for (half_edge, path) in file.half_edges.iter().zip(paths) {
+        let half_edge = HalfEdge::new(
+            path,
+            half_edge.boundary.map(|t| Point::from([t])),
+            loaded.curves[half_edge.curve].clone(),
+            loaded.vertices[half_edge.start_vertex].clone(),
+        );
+        loaded.half_edges.push(half_edge.insert(services));
+    }
+    for cycle in &file.cycles {
+        let cycle = Cycle::new(handles(&loaded.half_edges, &cycle.half_edges));
+        loaded.cycles.push(cycle.insert(services));
+    }
+    for region in &file.regions {
+        let region = Region::new(
+            loaded.cycles[region.exterior].clone(),
+            handles(&loaded.cycles, &region.interiors),
+            region.color.map(Color),
+        );
+        loaded.regions.push(region.insert(services));
+    }
+    for face in &file.faces {
+        let face = Face::new(
+            loaded.surfaces[face.surface].clone(),
+            loaded.regions[face.region].clone(),
+        );
+        loaded.faces.push(face.insert(services));
+    }
+    for shell in &file.shells {
+        let shell = Shell::new(handles(&loaded.faces, &shell.faces));
+        loaded.shells.push(shell.insert(services));
+    }
+    for sketch in &file.sketches {
+        let sketch = Sketch::new(handles(&loaded.regions, &sketch.regions));
+        loaded.sketches.push(sketch.insert(services));
+    }
+    for solid in &file.solids {
+        let solid = Solid::new(handles(&loaded.shells, &solid.shells));
+        loaded.solids.push(solid.insert(services));
+    }
+
+    Ok(loaded)
+}
+
+fn check_references(file: &format::File) -> Result<(), SerializationError> {
+    for (index, half_edge) in file.half_edges.iter().enumerate() {
+        let references = References::new("half-edge", index);
+        references.single("curve", half_edge.curve, file.curves)?;
+        references.single("vertex", half_edge.start_vertex, file.vertices)?;
+    }
+    for (index, cycle) in file.cycles.iter().enumerate() {
+        References::new("cycle", index).set(
+            "half-edge",
+            &cycle.half_edges,
+            file.half_edges.len(),
+        )?;
+    }
+    for (index, region) in file.regions.iter().enumerate() {
+        let references = References::new("region", index);
+        references.single("cycle", region.exterior, file.cycles.len())?;
+        references.set("cycle", &region.interiors, file.cycles.len())?;
+    }
+    for (index, face) in file.faces.iter().enumerate() {
+        let references = References::new("face", index);
+        references.single("surface", face.surface, file.surfaces.len())?;
+        references.single("region", face.region, file.regions.len())?;
+    }
+    for (index, shell) in file.shells.iter().enumerate() {
+        References::new("shell", index).set(
+            "face",
+            &shell.faces,
+            file.faces.len(),
+        )?;
+    }
+    for (index, sketch) in file.sketches.iter().enumerate() {
+        References::new("sketch", index).set(
+            "region",
+            &sketch.regions,
+            file.regions.len(),
+        )?;
+    }
+    for (index, solid) in file.solids.iter().enumerate() {
+        References::new("solid", index).set(
+            "shell",
+            &solid.shells,
+            file.shells.len(),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Checks the references of a single object
+struct References {
+    kind: &'static str,
+    index: usize,
+}
+
+impl References {
+    fn new(kind: &'static str, index: usize) -> Self {
+        Self { kind, index }
+    }
+
+    fn single(
+        &self,
+        referenced_kind: &'static str,
+        reference: usize,
+        len: usize,
+    ) -> Result<(), SerializationError> {
+        if reference >= len {
+            return Err(SerializationError::InvalidReference {
+                kind: self.kind,
+                index: self.index,
+                referenced_kind,
+                reference,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Check references that are going to end up in an `ObjectSet`
+    ///
+    /// Those must not contain duplicates.
+    fn set(
+        &self,
+        referenced_kind: &'static str,
+        references: &[usize],
+        len: usize,
+    ) -> Result<(), SerializationError> {
+        let mut seen = BTreeSet::new();
+
+        for &reference in references {
+            self.single(referenced_kind, reference, len)?;
+
+            if !seen.insert(reference) {
+                return Err(SerializationError::DuplicateReference {
+                    kind: self.kind,
+                    index: self.index,
+                    referenced_kind,
+                    reference,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn handles<'r, T>(
+    objects: &'r [Handle<T>],
+    indices: &'r [usize],
+) -> impl Iterator<Item = Handle<T>> + 'r {
+    indices.iter().map(|&index| objects[index].clone())
+}
+
+fn surface_path(
+    path: &format::SurfacePath,
+) -> Result<SurfacePath, &'static str> {
+    let path = match *path {
+        format::SurfacePath::Circle { center, a, b } => {
+            SurfacePath::Circle(circle(center, a, b)?)
+        }
+        format::SurfacePath::Line { origin, direction } => {
+            SurfacePath::Line(line(origin, direction)?)
+        }
+    };
+
+    Ok(path)
+}
+
+fn global_path(path: &format::GlobalPath) -> Result<GlobalPath, &'static str> {
+    let path = match *path {
+        format::GlobalPath::Circle { center, a, b } => {
+            GlobalPath::Circle(circle(center, a, b)?)
+        }
+        format::GlobalPath::Line { origin, direction } => {
+            GlobalPath::Line(line(origin, direction)?)
+        }
+    };
+
+    Ok(path)
+}
+
+fn circle<const D: usize>(
+    center: [f64; D],
+    a: [f64; D],
+    b: [f64; D],
+) -> Result<Circle<D>, &'static str> {
+    Circle::try_new(center, a, b).map_err(|err| match err {
+        InvalidCircle::ZeroRadius => "circle radius is zero",
+        InvalidCircle::UnequalAxes => {
+            "circle vectors `a` and `b` are not of equal length"
+        }
+        InvalidCircle::NonPerpendicularAxes => {
+            "circle vectors `a` and `b` are not perpendicular"
+        }
+    })
+}
+
+fn line<const D: usize>(
+    origin: [f64; D],
+    direction: [f64; D],
+) -> Result<Line<D>, &'static str> {
+    let direction = Vector::from(direction);
+
+    if direction.magnitude() == Scalar::ZERO {
+        return Err("line direction is zero");
+    }
+
+    Ok(Line::from_origin_and_direction(
+        Point::from(origin),
+        direction,
+    ))
+}
+
+fn invalid_geometry(
+    kind: &'static str,
+    index: usize,
+    reason: &'static str,
+) -> SerializationError {
+    SerializationError::InvalidGeometry {
+        kind,
+        index,
+        reason,
+    }
+}
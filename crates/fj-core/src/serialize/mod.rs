@@ -0,0 +1,355 @@
+//! Native serialization of objects
+//!
+//! Objects only exist in memory, as they are created by the code that defines a
+//! model. The functions in this module can save them to a file, and load them
+//! back, for example to cache the result of an expensive operation, or to send
+//! it to a different process.
+//!
+//! The file contains the complete object graph. Objects that are referenced
+//! multiple times (like a [`Vertex`] that is shared between half-edges) are
+//! written once, and are shared again after loading. The loaded objects are
+//! identical to the saved ones in topology and geometry, but have new
+//! identities, as they are inserted into the stores like any other objects.
+//!
+//! The file format is versioned. Loading a file with a version that this
+//! release doesn't support results in an error.
+//!
+//! ```
+//! use fj_core::{
+//!     operations::{build::BuildSolid, insert::Insert},
+//!     objects::Solid,
+//!     serialize::{load, save_solid},
+//!     Instance,
+//! };
+//!
+//! let mut core = Instance::new();
+//! let solid = Solid::tetrahedron(
+//!     [[0., 0., 0.], [0., 1., 0.], [1., 0., 0.], [0., 0., 1.]],
+//!     &mut core,
+//! )
+//! .solid
+//! .insert(&mut core.services);
+//!
+//! let mut file = Vec::new();
+//! save_solid(&solid, &mut file)?;
+//!
+//! let mut other = Instance::new();
+//! let loaded = load(file.as_slice(), &mut other)?;
+//! assert_eq!(loaded.solids.len(), 1);
+//! # Ok::<(), fj_core::serialize::SerializationError>(())
+//! ```
+//!
+//! [`Vertex`]: crate::objects::Vertex
+
+mod format;
+mod load;
+mod save;
+
+use std::io::{Read, Write};
+
+use serde::Deserialize;
+
+use crate::{
+    objects::{
+        Curve, Cycle, Face, HalfEdge, Region, Shell, Sketch, Solid, Surface,
+        Vertex,
+    },
+    storage::Handle,
+    Instance,
+};
+
+use self::{
+    format::{Header, FORMAT, VERSION},
+    save::Saver,
+};
+
+/// Save the provided solid, and all objects it references
+pub fn save_solid(
+    solid: &Handle<Solid>,
+    writer: impl Write,
+) -> Result<(), SerializationError> {
+    let mut saver = Saver::new();
+    saver.solid(solid);
+
+    serde_json::to_writer(writer, &saver.finish())?;
+
+    Ok(())
+}
+
+/// Save all objects of the provided instance
+///
+/// This includes objects that aren't referenced by any other object, as well
+/// as the surfaces that every instance provides by default.
+pub fn save_instance(
+    core: &Instance,
+    writer: impl Write,
+) -> Result<(), SerializationError> {
+    let mut saver = Saver::new();
+    saver.objects(&core.services.objects);
+
+    serde_json::to_writer(writer, &saver.finish())?;
+
+    Ok(())
+}
+
+/// Load objects from the provided reader, inserting them into the instance
+///
+/// Loaded objects are validated, like all other objects that are inserted.
+/// Nothing is inserted, if the file is not valid.
+pub fn load(
+    reader: impl Read,
+    core: &mut Instance,
+) -> Result<LoadedObjects, SerializationError> {
+    let value: serde_json::Value = serde_json::from_reader(reader)?;
+
+    // Check the header first. If the version doesn't match, the rest of the
+    // file is likely to be structured differently, and trying to make sense of
+    // it would result in a confusing error.
+    let header = Header::deserialize(&value)?;
+    if header.format != FORMAT {
+        return Err(SerializationError::UnknownFormat(header.format));
+    }
+    if header.version != VERSION {
+        return Err(SerializationError::UnsupportedVersion(header.version));
+    }
+
+    let file = format::File::deserialize(value)?;
+    load::load(file, core)
+}
+
+/// The objects that were loaded by [`load()`]
+///
+/// Contains all loaded objects, in the order they appear in the file.
+#[derive(Debug, Default)]
+pub struct LoadedObjects {
+    /// The loaded curves
+    pub curves: Vec<Handle<Curve>>,
+
+    /// The loaded vertices
+    pub vertices: Vec<Handle<Vertex>>,
+
+    /// The loaded surfaces
+    pub surfaces: Vec<Handle<Surface>>,
+
+    /// The loaded half-edges
+    pub half_edges: Vec<Handle<HalfEdge>>,
+
+    /// The loaded cycles
+    pub cycles: Vec<Handle<Cycle>>,
+
+    /// The loaded regions
+    pub regions: Vec<Handle<Region>>,
+
+    /// The loaded faces
+    pub faces: Vec<Handle<Face>>,
+
+    /// The loaded shells
+    pub shells: Vec<Handle<Shell>>,
+
+    /// The loaded sketches
+    pub sketches: Vec<Handle<Sketch>>,
+
+    /// The loaded solids
+    pub solids: Vec<Handle<Solid>>,
+}
+
+/// An error that can occur while saving or loading objects
+#[derive(Debug, thiserror::Error)]
+pub enum SerializationError {
+    /// Error reading or writing the file
+    #[error("error reading or writing serialized objects")]
+    Json(#[from] serde_json::Error),
+
+    /// The file is not in the expected format
+    #[error("unknown format `{0}`; expected `{FORMAT}`")]
+    UnknownFormat(String),
+
+    /// The file has a version that is not supported
+    #[error("unsupported format version {0}; expected version {VERSION}")]
+    UnsupportedVersion(u32),
+
+    /// An object refers to an object that doesn't exist
+    #[error(
+        "{kind} {index} refers to {referenced_kind} {reference}, which doesn't \
+        exist"
+    )]
+    InvalidReference {
+        /// The kind of object that has the invalid reference
+        kind: &'static str,
+
+        /// The index of the object that has the invalid reference
+        index: usize,
+
+        /// The kind of object that is referenced
+        referenced_kind: &'static str,
+
+        /// The index of the referenced object
+        reference: usize,
+    },
+
+    /// An object refers to the same object multiple times, where not allowed
+    #[error("{kind} {index} refers to {referenced_kind} {reference} twice")]
+    DuplicateReference {
+        /// The kind of object that has the duplicate reference
+        kind: &'static str,
+
+        /// The index of the object that has the duplicate reference
+        index: usize,
+
+        /// The kind of object that is referenced
+        referenced_kind: &'static str,
+
+        /// The index of the referenced object
+        reference: usize,
+    },
+
+    /// An object has geometry that can't be represented
+    #[error("{kind} {index} has invalid geometry: {reason}")]
+    InvalidGeometry {
+        /// The kind of object that has invalid geometry
+        kind: &'static str,
+
+        /// The index of the object that has invalid geometry
+        index: usize,
+
+        /// Why the geometry is invalid
+        reason: &'static str,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{
+        objects::{Shell, Solid},
+        operations::{
+            build::{BuildShell, BuildSolid},
+            insert::Insert,
+        },
+        Instance,
+    };
+
+    use super::{load, save_instance, save_solid, SerializationError};
+
+    #[test]
+    fn solid_round_trip() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let solid = Solid::tetrahedron(
+            [[0., 0., 0.], [0., 1., 0.], [1., 0., 0.], [0., 0., 1.]],
+            &mut core,
+        )
+        .solid
+        .insert(&mut core.services);
+
+        let mut file = Vec::new();
+        save_solid(&solid, &mut file)?;
+
+        let mut other = Instance::new();
+        let loaded = load(file.as_slice(), &mut other)?;
+
+        // Every object is written once, no matter how many objects share it.
+        assert_eq!(loaded.solids.len(), 1);
+        assert_eq!(loaded.shells.len(), 1);
+        assert_eq!(loaded.faces.len(), 4);
+        assert_eq!(loaded.half_edges.len(), 12);
+        assert_eq!(loaded.curves.len(), 6);
+        assert_eq!(loaded.vertices.len(), 4);
+
+        let original = solid.shells().only().faces();
+        let loaded_faces = loaded.solids[0].shells().only().faces();
+        for (a, b) in original.iter().zip(loaded_faces) {
+            assert_eq!(a.surface().geometry(), b.surface().geometry());
+
+            let half_edges = [a, b].map(|face| face.region().exterior());
+            for (a, b) in half_edges[0]
+                .half_edges()
+                .iter()
+                .zip(half_edges[1].half_edges())
+            {
+                assert_eq!(a.path(), b.path());
+                assert_eq!(a.boundary(), b.boundary());
+            }
+        }
+
+        // The loaded objects reference each other, exactly as the original
+        // ones did.
+        let curves = loaded_faces
+            .iter()
+            .flat_map(|face| face.region().exterior().half_edges())
+            .map(|half_edge| half_edge.curve().id())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(curves.len(), 6);
+
+        other.services.drop_and_validate()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn instance_round_trip() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+        let _shell = Shell::tetrahedron(
+            [[0., 0., 0.], [0., 1., 0.], [1., 0., 0.], [0., 0., 1.]],
+            &mut core,
+        )
+        .shell
+        .insert(&mut core.services);
+
+        let mut file = Vec::new();
+        save_instance(&core, &mut file)?;
+
+        let mut other = Instance::new();
+        let loaded = load(file.as_slice(), &mut other)?;
+
+        let num_surfaces = core.services.objects.surfaces.iter().count();
+        assert_eq!(loaded.surfaces.len(), num_surfaces);
+        assert_eq!(loaded.shells.len(), 1);
+        assert_eq!(loaded.faces.len(), 4);
+
+        other.services.drop_and_validate()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let file = r#"{"format":"fornjot-objects","version":2}"#;
+
+        let result = load(file.as_bytes(), &mut Instance::new());
+        assert!(matches!(
+            result,
+            Err(SerializationError::UnsupportedVersion(2))
+        ));
+    }
+
+    #[test]
+    fn invalid_reference() {
+        let file = r#"{
+            "format": "fornjot-objects",
+            "version": 1,
+            "curves": 0,
+            "vertices": 0,
+            "surfaces": [],
+            "half_edges": [],
+            "cycles": [{ "half_edges": [0] }],
+            "regions": [],
+            "faces": [],
+            "shells": [],
+            "sketches": [],
+            "solids": []
+        }"#;
+
+        let result = load(file.as_bytes(), &mut Instance::new());
+        assert!(matches!(
+            result,
+            Err(SerializationError::InvalidReference {
+                kind: "cycle",
+                index: 0,
+                referenced_kind: "half-edge",
+                reference: 0,
+            })
+        ));
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::BTreeMap;
+
+use fj_math::{Point, Scalar, Vector};
+
+use crate::{
+    geometry::{GlobalPath, SurfacePath},
+    objects::{
+        Curve, Cycle, Face, HalfEdge, Objects, Region, Shell, Sketch, Solid,
+        Surface, Vertex,
+    },
+    storage::{Handle, ObjectId},
+};
+
+use super::format::{self, FORMAT, VERSION};
+
+/// Converts objects into their serialized form
+///
+/// Keeps track of the objects that have already been converted, so every
+/// object is only written once, regardless of how many objects refer to it.
+pub struct Saver {
+    file: format::File,
+
+    curves: BTreeMap<ObjectId, usize>,
+    vertices: BTreeMap<ObjectId, usize>,
+    surfaces: BTreeMap<ObjectId, usize>,
+    half_edges: BTreeMap<ObjectId, usize>,
+    cycles: BTreeMap<ObjectId, usize>,
+    regions: BTreeMap<ObjectId, usize>,
+    faces: BTreeMap<ObjectId, usize>,
+    shells: BTreeMap<ObjectId, usize>,
+    sketches: BTreeMap<ObjectId, usize>,
+    solids: BTreeMap<ObjectId, usize>,
+}
+
+impl Saver {
+    pub fn new() -> Self {
+        Self {
+            file: format::File {
+                format: FORMAT.to_string(),
+                version: VERSION,
+                ..format::File::default()
+            },
+
+            curves: BTreeMap::new(),
+            vertices: BTreeMap::new(),
+            surfaces: BTreeMap::new(),
+            half_edges: BTreeMap::new(),
+            cycles: BTreeMap::new(),
+            regions: BTreeMap::new(),
+            faces: BTreeMap::new(),
+            shells: BTreeMap::new(),
+            sketches: BTreeMap::new(),
+            solids: BTreeMap::new(),
+        }
+    }
+
+    pub fn finish(self) -> format::File {
+        self.file
+    }
+
+    /// Add all objects from the provided stores
+    pub fn objects(&mut self, objects: &Objects) {
+        for curve in &objects.curves {
+            self.curve(&curve);
+        }
+        for vertex in &objects.vertices {
+            self.vertex(&vertex);
+        }
+        for surface in objects.surfaces.iter() {
+            self.surface(&surface);
+        }
+        for half_edge in &objects.half_edges {
+            self.half_edge(&half_edge);
+        }
+        for cycle in &objects.cycles {
+            self.cycle(&cycle);
+        }
+        for region in &objects.regions {
+            self.region(&region);
+        }
+        for face in &objects.faces {
+            self.face(&face);
+        }
+        for shell in &objects.shells {
+            self.shell(&shell);
+        }
+        for sketch in &objects.sketches {
+            self.sketch(&sketch);
+        }
+        for solid in &objects.solids {
+            self.solid(&solid);
+        }
+    }
+
+    pub fn curve(&mut self, curve: &Handle<Curve>) -> usize {
+        let file = &mut self.file;
+        *self.curves.entry(curve.id()).or_insert_with(|| {
+            file.curves += 1;
+            file.curves - 1
+        })
+    }
+
+    pub fn vertex(&mut self, vertex: &Handle<Vertex>) -> usize {
+        let file = &mut self.file;
+        *self.vertices.entry(vertex.id()).or_insert_with(|| {
+            file.vertices += 1;
+            file.vertices - 1
+        })
+    }
+
+    pub fn surface(&mut self, surface: &Handle<Surface>) -> usize {
+        if let Some(index) = self.surfaces.get(&surface.id()) {
+            return *index;
+        }
+
+        let geometry = surface.geometry();
+        let record = format::Surface {
+            u: global_path(geometry.u),
+            v: vector(geometry.v),
+        };
+
+        push(&mut self.file.surfaces, &mut self.surfaces, surface, record)
+    }
+
+    pub fn half_edge(&mut self, half_edge: &Handle<HalfEdge>) -> usize {
+        if let Some(index) = self.half_edges.get(&half_edge.id()) {
+            return *index;
+        }
+
+        let record = format::HalfEdge {
+            path: surface_path(half_edge.path()),
+            boundary: half_edge
+                .boundary()
+                .inner
+                .map(|point| point.t.into_f64()),
+            curve: self.curve(half_edge.curve()),
+            start_vertex: self.vertex(half_edge.start_vertex()),
+        };
+
+        push(
+            &mut self.file.half_edges,
+            &mut self.half_edges,
+            half_edge,
+            record,
+        )
+    }
+
+    pub fn cycle(&mut self, cycle: &Handle<Cycle>) -> usize {
+        if let Some(index) = self.cycles.get(&cycle.id()) {
+            return *index;
+        }
+
+        let record = format::Cycle {
+            half_edges: cycle
+                .half_edges()
+                .iter()
+                .map(|half_edge| self.half_edge(half_edge))
+                .collect(),
+        };
+
+        push(&mut self.file.cycles, &mut self.cycles, cycle, record)
+    }
+
+    pub fn region(&mut self, region: &Handle<Region>) -> usize {
+        if let Some(index) = self.regions.get(&region.id()) {
+            return *index;
+        }
+
+        let record = format::Region {
+            exterior: self.cycle(region.exterior()),
+            interiors: region
+                .interiors()
+                .iter()
+                .map(|cycle| self.cycle(cycle))
+                .collect(),
+            color: region.color().map(|color| color.0),
+        };
+
+        push(&mut self.file.regions, &mut self.regions, region, record)
+    }
+
+    pub fn face(&mut self, face: &Handle<Face>) -> usize {
+        if let Some(index) = self.faces.get(&face.id()) {
+            return *index;
+        }
+
+        let record = format::Face {
+            surface: self.surface(face.surface()),
+            region: self.region(face.region()),
+        };
+
+        push(&mut self.file.faces, &mut self.faces, face, record)
+    }
+
+    pub fn shell(&mut self, shell: &Handle<Shell>) -> usize {
+        if let Some(index) = self.shells.get(&shell.id()) {
+            return *index;
+        }
+
+        let record = format::Shell {
+            faces: shell.faces().iter().map(|face| self.face(face)).collect(),
+        };
+
+        push(&mut self.file.shells, &mut self.shells, shell, record)
+    }
+
+    pub fn sketch(&mut self, sketch: &Handle<Sketch>) -> usize {
+        if let Some(index) = self.sketches.get(&sketch.id()) {
+            return *index;
+        }
+
+        let record = format::Sketch {
+            regions: sketch
+                .regions()
+                .iter()
+                .map(|region| self.region(region))
+                .collect(),
+        };
+
+        push(&mut self.file.sketches, &mut self.sketches, sketch, record)
+    }
+
+    pub fn solid(&mut self, solid: &Handle<Solid>) -> usize {
+        if let Some(index) = self.solids.get(&solid.id()) {
+            return *index;
+        }
+
+        let record = format::Solid {
+            shells: solid
+                .shells()
+                .iter()
+                .map(|shell| self.shell(shell))
+                .collect(),
+        };
+
+        push(&mut self.file.solids, &mut self.solids, solid, record)
+    }
+}
+
+fn push<T, R>(
+    records: &mut Vec<R>,
+    indices: &mut BTreeMap<ObjectId, usize>,
+    handle: &Handle<T>,
+    record: R,
+) -> usize {
+    let index = records.len();
+    records.push(record);
+    indices.insert(handle.id(), index);
+    index
+}
+
+fn surface_path(path: SurfacePath) -> format::SurfacePath {
+    match path {
+        SurfacePath::Circle(circle) => format::SurfacePath::Circle {
+            center: point(circle.center()),
+            a: vector(circle.a()),
+            b: vector(circle.b()),
+        },
+        SurfacePath::Line(line) => format::SurfacePath::Line {
+            origin: point(line.origin()),
+            direction: vector(line.direction()),
+        },
+    }
+}
+
+fn global_path(path: GlobalPath) -> format::GlobalPath {
+    match path {
+        GlobalPath::Circle(circle) => format::GlobalPath::Circle {
+            center: point(circle.center()),
+            a: vector(circle.a()),
+            b: vector(circle.b()),
+        },
+        GlobalPath::Line(line) => format::GlobalPath::Line {
+            origin: point(line.origin()),
+            direction: vector(line.direction()),
+        },
+    }
+}
+
+fn point<const D: usize>(point: Point<D>) -> [f64; D] {
+    vector(point.coords)
+}
+
+fn vector<const D: usize>(vector: Vector<D>) -> [f64; D] {
+    vector.components.map(Scalar::into_f64)
+}
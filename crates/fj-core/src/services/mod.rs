@@ -5,10 +5,13 @@
 mod objects;
 mod service;
 mod validation;
+mod validation_workers;
 
 use crate::{
     objects::{AboutToBeStored, AnyObject, Objects},
-    validate::{ValidationConfig, ValidationErrors},
+    validate::{
+        ValidationConfig, ValidationError, ValidationErrors, ValidationReport,
+    },
 };
 
 pub use self::{
@@ -67,10 +70,27 @@ impl Services {
         }
     }
 
+    /// Wait for objects that are validated in the background
+    ///
+    /// Afterwards, the results of all objects inserted so far are available
+    /// from the validation service. This is done automatically by the methods
+    /// that drop `Services`, so it is only required to inspect the results
+    /// before that.
+    pub fn collect_validation_results(&mut self) {
+        self.validation
+            .execute(ValidationCommand::CollectResults, &mut Vec::new());
+    }
+
     /// Drop `Services`; return any unhandled validation error
-    pub fn drop_and_validate(self) -> Result<(), ValidationErrors> {
+    pub fn drop_and_validate(mut self) -> Result<(), ValidationErrors> {
+        self.collect_validation_results();
+
         let errors = ValidationErrors(
-            self.validation.errors.values().cloned().collect(),
+            self.validation
+                .errors
+                .iter()
+                .map(|(_, err)| err.clone())
+                .collect(),
         );
 
         if errors.0.is_empty() {
@@ -79,6 +99,68 @@ impl Services {
             Err(errors)
         }
     }
+
+    /// Drop `Services`, applying the provided severities to the validation
+    /// results; return any unhandled validation error
+    ///
+    /// The severities that are configured in `config` override those that were
+    /// in effect when the objects were validated. This can be used to turn
+    /// errors into warnings, or ignore them, after the fact. Other settings of
+    /// `config` are ignored, and checks that were disabled during validation
+    /// can't be enabled here.
+    ///
+    /// Returns the validation warnings, if there are no validation errors.
+    pub fn drop_and_validate_with_config(
+        mut self,
+        config: &ValidationConfig,
+    ) -> Result<Vec<ValidationError>, ValidationErrors> {
+        self.collect_validation_results();
+
+        let mut validation = self.validation.into_state();
+        validation.apply_severities(config);
+
+        let errors = ValidationErrors(
+            validation
+                .errors
+                .iter()
+                .map(|(_, err)| err.clone())
+                .collect(),
+        );
+
+        if errors.0.is_empty() {
+            Ok(validation
+                .warnings
+                .iter()
+                .map(|(_, err)| err.clone())
+                .collect())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Drop `Services`; return a report of all validation results
+    ///
+    /// Applies the provided severities, like
+    /// [`Services::drop_and_validate_with_config`]. Unlike that method, this
+    /// one doesn't panic, if there are validation errors. They are only
+    /// returned as part of the report.
+    pub fn drop_and_report(
+        mut self,
+        config: &ValidationConfig,
+    ) -> ValidationReport {
+        self.collect_validation_results();
+
+        let mut validation = self.validation.into_state();
+        validation.apply_severities(config);
+
+        let report = validation.report();
+
+        // All errors are contained in the report. They have been handled, and
+        // must not cause a panic when `validation` is dropped.
+        validation.errors.clear();
+
+        report
+    }
 }
 
 impl Default for Services {
@@ -41,6 +41,11 @@ impl<S: State> Service<S> {
         }
     }
 
+    /// Consume the service, returning the wrapped state
+    pub fn into_state(self) -> S {
+        self.state
+    }
+
     /// Replay the provided events on the given state
     pub fn replay<'event>(
         state: &mut S,
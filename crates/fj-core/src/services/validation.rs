@@ -1,39 +1,130 @@
-use std::{collections::BTreeMap, error::Error, thread};
+use std::{error::Error, mem, thread};
 
 use crate::{
     objects::{AnyObject, Stored},
     storage::ObjectId,
-    validate::{ValidationConfig, ValidationError},
+    validate::{
+        Severity, ValidationCheck, ValidationConfig, ValidationError,
+        ValidationReport, ValidationReportEntry,
+    },
 };
 
-use super::State;
+use super::{validation_workers::ValidationWorkers, State};
 
 /// Errors that occurred while validating the objects inserted into the stores
+///
+/// If [`ValidationConfig::worker_threads`] is not zero, objects are validated
+/// in the background. Their results only show up in [`Validation::errors`] and
+/// [`Validation::warnings`], once they have been collected using
+/// [`ValidationCommand::CollectResults`]. This happens automatically, before
+/// `Validation` is dropped.
 pub struct Validation {
-    /// All unhandled validation errors
-    pub errors: BTreeMap<ObjectId, ValidationError>,
+    /// All unhandled validation errors, with the objects they are about
+    ///
+    /// An object can have multiple errors, if it fails multiple checks, or the
+    /// same check in multiple ways. The errors are in the order in which the
+    /// objects were inserted, regardless of whether they were validated in the
+    /// background.
+    pub errors: Vec<(ObjectId, ValidationError)>,
+    /// All validation warnings, with the objects they are about
+    ///
+    /// These are produced by validation checks that are configured as
+    /// [`Severity::Warning`]. They are ordered like [`Validation::errors`].
+    pub warnings: Vec<(ObjectId, ValidationError)>,
     /// Validation configuration for the validation service
     config: ValidationConfig,
+    /// The threads that validate objects in the background, if any
+    workers: Option<ValidationWorkers>,
 }
 
 impl Validation {
     /// A constructor for the validation service that allows a validation configuration to be set for the service
     pub fn with_validation_config(config: ValidationConfig) -> Self {
-        let errors = BTreeMap::new();
-        Self { errors, config }
+        let errors = Vec::new();
+        let warnings = Vec::new();
+        let workers = (config.worker_threads > 0).then(|| {
+            ValidationWorkers::new(config.worker_threads, config.clone())
+        });
+        Self {
+            errors,
+            warnings,
+            config,
+            workers,
+        }
+    }
+
+    /// Access the validation configuration of the validation service
+    pub fn config(&self) -> &ValidationConfig {
+        &self.config
+    }
+
+    /// Create a report of all validation errors and warnings
+    pub fn report(&self) -> ValidationReport {
+        let entries = |errors: &[(ObjectId, ValidationError)]| {
+            errors
+                .iter()
+                .map(|(object, err)| ValidationReportEntry::new(*object, err))
+                .collect()
+        };
+
+        ValidationReport {
+            errors: entries(&self.errors),
+            warnings: entries(&self.warnings),
+        }
+    }
+
+    /// Apply the severities that are configured in `config`
+    ///
+    /// Errors and warnings that were produced by a validation check that is
+    /// listed in `config` are re-classified according to its severity. All
+    /// others keep the severity that was in effect when they were produced.
+    pub fn apply_severities(&mut self, config: &ValidationConfig) {
+        let errors = mem::take(&mut self.errors)
+            .into_iter()
+            .map(|entry| (entry, Severity::Error));
+        let warnings = mem::take(&mut self.warnings)
+            .into_iter()
+            .map(|entry| (entry, Severity::Warning));
+
+        for ((id, err), severity) in errors.chain(warnings) {
+            let severity = config
+                .severities
+                .get(&err.check())
+                .copied()
+                .unwrap_or(severity);
+
+            match severity {
+                Severity::Disabled => {}
+                Severity::Warning => {
+                    self.warnings.push((id, err));
+                }
+                Severity::Error => {
+                    self.errors.push((id, err));
+                }
+            }
+        }
     }
 }
 
 impl Default for Validation {
     fn default() -> Self {
-        let errors = BTreeMap::new();
-        let config: ValidationConfig = ValidationConfig::default();
-        Self { errors, config }
+        Self::with_validation_config(ValidationConfig::default())
     }
 }
 
 impl Drop for Validation {
     fn drop(&mut self) {
+        // Errors from objects that are still being validated must not go
+        // unnoticed. If we're already panicking, collecting them could cause
+        // another panic, which would abort.
+        if !thread::panicking() {
+            let mut events = Vec::new();
+            self.decide(ValidationCommand::CollectResults, &mut events);
+            for event in &events {
+                self.evolve(event);
+            }
+        }
+
         let num_errors = self.errors.len();
         if num_errors > 0 {
             println!(
@@ -41,7 +132,7 @@ impl Drop for Validation {
                 errors:"
             );
 
-            for err in self.errors.values() {
+            for (_, err) in &self.errors {
                 println!("{}", err);
 
                 // Once `Report` is stable, we can replace this:
@@ -71,12 +162,48 @@ impl State for Validation {
 
         match command {
             ValidationCommand::ValidateObject { object } => {
+                if self.workers.is_some() {
+                    events
+                        .push(ValidationEvent::ValidationSubmitted { object });
+                    return;
+                }
+
                 object.validate_with_config(&self.config, &mut errors);
+                self.classify_errors(object, errors, events);
+            }
+            ValidationCommand::CollectResults => {
+                self.collect_results(events);
+            }
+            ValidationCommand::ResolveErrors { object, check } => {
+                // Results that are still outstanding might include errors that
+                // are about to be resolved.
+                self.collect_results(events);
 
-                for err in errors {
-                    events.push(ValidationEvent::ValidationFailed {
-                        object: object.clone(),
+                let is_affected = |errors: &[(ObjectId, ValidationError)]| {
+                    errors
+                        .iter()
+                        .any(|(id, err)| *id == object && err.check() == check)
+                };
+                let is_collected = events.iter().any(|event| match event {
+                    ValidationEvent::ValidationFailed {
+                        object: collected,
                         err,
+                    }
+                    | ValidationEvent::ValidationWarning {
+                        object: collected,
+                        err,
+                    } => collected.id() == object && err.check() == check,
+                    ValidationEvent::ValidationSubmitted { .. }
+                    | ValidationEvent::ErrorsResolved { .. } => false,
+                });
+
+                if is_affected(&self.errors)
+                    || is_affected(&self.warnings)
+                    || is_collected
+                {
+                    events.push(ValidationEvent::ErrorsResolved {
+                        object,
+                        check,
                     });
                 }
             }
@@ -85,8 +212,62 @@ impl State for Validation {
 
     fn evolve(&mut self, event: &Self::Event) {
         match event {
+            ValidationEvent::ValidationSubmitted { object } => {
+                if let Some(workers) = &self.workers {
+                    workers.submit(object.clone());
+                }
+            }
             ValidationEvent::ValidationFailed { object, err } => {
-                self.errors.insert(object.id(), err.clone());
+                self.errors.push((object.id(), err.clone()));
+            }
+            ValidationEvent::ValidationWarning { object, err } => {
+                self.warnings.push((object.id(), err.clone()));
+            }
+            ValidationEvent::ErrorsResolved { object, check } => {
+                for errors in [&mut self.errors, &mut self.warnings] {
+                    errors.retain(|(id, err)| {
+                        !(id == object && err.check() == *check)
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Validation {
+    fn collect_results(&self, events: &mut Vec<ValidationEvent>) {
+        let Some(workers) = &self.workers else {
+            return;
+        };
+
+        for (object, errors) in workers.collect() {
+            self.classify_errors(object, errors, events);
+        }
+    }
+
+    fn classify_errors(
+        &self,
+        object: AnyObject<Stored>,
+        errors: Vec<ValidationError>,
+        events: &mut Vec<ValidationEvent>,
+    ) {
+        for err in errors {
+            let object = object.clone();
+
+            match self.config.severity(err.check()) {
+                Severity::Disabled => {}
+                Severity::Warning => {
+                    events.push(ValidationEvent::ValidationWarning {
+                        object,
+                        err,
+                    });
+                }
+                Severity::Error => {
+                    events.push(ValidationEvent::ValidationFailed {
+                        object,
+                        err,
+                    });
+                }
             }
         }
     }
@@ -95,15 +276,46 @@ impl State for Validation {
 /// The command accepted by the validation service
 pub enum ValidationCommand {
     /// Validate the provided object
+    ///
+    /// If validation runs in the background, the results are only available
+    /// after [`ValidationCommand::CollectResults`].
     ValidateObject {
         /// The object to validate
         object: AnyObject<Stored>,
     },
+
+    /// Wait for objects that are validated in the background; collect results
+    ///
+    /// Results are collected in the order in which the objects were submitted
+    /// for validation, which makes them independent of the number of threads.
+    /// Does nothing, if objects are validated synchronously.
+    CollectResults,
+
+    /// Resolve the errors that a validation check produced for an object
+    ///
+    /// This is used by operations that replace an invalid object with a
+    /// repaired version. The original object is still invalid, but it is no
+    /// longer used, so its errors don't need to be handled.
+    ResolveErrors {
+        /// The object whose errors are resolved
+        object: ObjectId,
+
+        /// The validation check whose errors are resolved
+        check: ValidationCheck,
+    },
 }
 
 /// The event produced by the validation service
 #[derive(Clone)]
 pub enum ValidationEvent {
+    /// An object was submitted for validation in the background
+    ///
+    /// Its results are produced as separate events, once they are collected.
+    ValidationSubmitted {
+        /// The object that is validated
+        object: AnyObject<Stored>,
+    },
+
     /// Validation of an object failed
     ValidationFailed {
         /// The object for which validation failed
@@ -112,4 +324,163 @@ pub enum ValidationEvent {
         /// The validation error
         err: ValidationError,
     },
+
+    /// Validation of an object produced a warning
+    ValidationWarning {
+        /// The object that the warning is about
+        object: AnyObject<Stored>,
+
+        /// The validation error that is treated as a warning
+        err: ValidationError,
+    },
+
+    /// The errors that a validation check produced for an object were resolved
+    ErrorsResolved {
+        /// The object whose errors were resolved
+        object: ObjectId,
+
+        /// The validation check whose errors were resolved
+        check: ValidationCheck,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        objects::Cycle,
+        operations::{build::BuildCycle, insert::Insert},
+        services::ValidationCommand,
+        validate::{Severity, ValidationCheck, ValidationConfig},
+        Instance,
+    };
+
+    #[test]
+    fn multiple_errors_per_object() {
+        let mut core = Instance::new();
+
+        // Crossing edges and a very short edge.
+        let cycle = Cycle::polygon(
+            [[0., 0.], [3., 3.], [3., 0.], [3., 1e-9], [0., 2.]],
+            &mut core,
+        )
+        .insert(&mut core.services);
+
+        let checks = |core: &Instance| {
+            core.services
+                .validation
+                .errors
+                .iter()
+                .filter(|(object, _)| *object == cycle.id())
+                .map(|(_, err)| err.check())
+                .collect::<Vec<_>>()
+        };
+
+        let before = checks(&core);
+        assert!(before.contains(&ValidationCheck::CycleHalfEdgeLength));
+        assert!(before.contains(&ValidationCheck::CycleSelfIntersection));
+
+        core.services.validation.execute(
+            ValidationCommand::ResolveErrors {
+                object: cycle.id(),
+                check: ValidationCheck::CycleSelfIntersection,
+            },
+            &mut Vec::new(),
+        );
+
+        let after = checks(&core);
+        assert!(!after.is_empty());
+        assert!(after
+            .iter()
+            .all(|&check| check == ValidationCheck::CycleHalfEdgeLength));
+
+        core.services.validation.errors.clear();
+    }
+
+    #[test]
+    fn results_in_insertion_order() {
+        for worker_threads in [0, 1, 4] {
+            let mut core = Instance::with_validation_config(ValidationConfig {
+                worker_threads,
+                ..ValidationConfig::default()
+            });
+
+            let cycles = (0..16)
+                .map(|i| {
+                    let offset = f64::from(i);
+                    Cycle::polygon(
+                        [[0., 0.], [3., 3.], [3., 0.], [0., 2.]]
+                            .map(|[u, v]| [u + offset, v]),
+                        &mut core,
+                    )
+                    .insert(&mut core.services)
+                    .id()
+                })
+                .collect::<Vec<_>>();
+
+            core.services.collect_validation_results();
+
+            let mut objects = core
+                .services
+                .validation
+                .errors
+                .iter()
+                .map(|(object, _)| *object)
+                .collect::<Vec<_>>();
+            objects.dedup();
+            assert_eq!(objects, cycles);
+
+            core.services.validation.errors.clear();
+        }
+    }
+
+    #[test]
+    fn background_validation() {
+        let config = ValidationConfig::default().with_severity(
+            ValidationCheck::CycleHalfEdgeLength,
+            Severity::Warning,
+        );
+
+        let validate = |config: ValidationConfig| {
+            let mut core = Instance::with_validation_config(config.clone());
+
+            for i in 0..32 {
+                let offset = f64::from(i);
+                let points = if i % 3 == 0 {
+                    // Crossing edges
+                    [[0., 0.], [3., 3.], [3., 0.], [0., 2.]]
+                } else if i % 3 == 1 {
+                    // Very short edge
+                    [[0., 0.], [1., 0.], [1., 1e-9], [0., 1.]]
+                } else {
+                    [[0., 0.], [1., 0.], [1., 1.], [0., 1.]]
+                };
+                let _cycle = Cycle::polygon(
+                    points.map(|[u, v]| [u + offset, v]),
+                    &mut core,
+                )
+                .insert(&mut core.services);
+            }
+
+            let report = core.services.drop_and_report(&config);
+
+            [report.errors, report.warnings].map(|entries| {
+                entries
+                    .into_iter()
+                    .map(|entry| (entry.check, entry.kind, entry.positions))
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        let [errors, warnings] = validate(config.clone());
+        assert_eq!(errors.len(), 11);
+        assert_eq!(warnings.len(), 11);
+
+        for worker_threads in [1, 4] {
+            let config = ValidationConfig {
+                worker_threads,
+                ..config.clone()
+            };
+            assert_eq!(validate(config), [errors.clone(), warnings.clone()]);
+        }
+    }
 }
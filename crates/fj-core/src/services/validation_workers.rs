@@ -0,0 +1,158 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    objects::{AnyObject, Stored},
+    validate::{ValidationConfig, ValidationError},
+};
+
+/// Threads that validate objects in the background
+///
+/// Objects are validated in whatever order the threads get to them. To keep the
+/// results independent of that, each object is assigned an index when it is
+/// submitted, and results are returned in the order of those indices.
+pub struct ValidationWorkers {
+    jobs: Option<mpsc::Sender<Job>>,
+    results: Mutex<mpsc::Receiver<JobResult>>,
+    num_submitted: AtomicU64,
+    num_collected: AtomicU64,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl ValidationWorkers {
+    /// Start the provided number of threads
+    pub fn new(num_threads: usize, config: ValidationConfig) -> Self {
+        let (jobs, jobs_rx) = mpsc::channel::<Job>();
+        let (results_tx, results) = mpsc::channel();
+
+        let jobs_rx = Arc::new(Mutex::new(jobs_rx));
+        let config = Arc::new(config);
+
+        let threads = (0..num_threads)
+            .map(|_| {
+                let jobs = jobs_rx.clone();
+                let results = results_tx.clone();
+                let config = config.clone();
+
+                thread::Builder::new()
+                    .name("validation".to_string())
+                    .spawn(move || loop {
+                        // The lock is released at the end of the statement, as
+                        // soon as a job has been received.
+                        let Ok(Job { index, object }) = jobs.lock().recv()
+                        else {
+                            // All senders have been dropped. No more jobs are
+                            // coming.
+                            break;
+                        };
+
+                        // A panicking check must not take the thread down with
+                        // it. Otherwise, nobody would ever receive a result for
+                        // this job. The panic is resumed, when the results are
+                        // collected.
+                        let errors =
+                            panic::catch_unwind(AssertUnwindSafe(|| {
+                                let mut errors = Vec::new();
+                                object
+                                    .validate_with_config(&config, &mut errors);
+                                errors
+                            }));
+
+                        let result = JobResult {
+                            index,
+                            object,
+                            errors,
+                        };
+                        if results.send(result).is_err() {
+                            break;
+                        }
+                    })
+                    .expect("Failed to spawn validation thread")
+            })
+            .collect();
+
+        Self {
+            jobs: Some(jobs),
+            results: Mutex::new(results),
+            num_submitted: AtomicU64::new(0),
+            num_collected: AtomicU64::new(0),
+            threads,
+        }
+    }
+
+    /// Submit an object for validation
+    pub fn submit(&self, object: AnyObject<Stored>) {
+        let index = self.num_submitted.fetch_add(1, Ordering::SeqCst);
+
+        self.jobs
+            .as_ref()
+            .expect("Jobs are only taken when dropping")
+            .send(Job { index, object })
+            .expect("Validation threads stopped unexpectedly");
+    }
+
+    /// Wait for all submitted objects to be validated; return the results
+    ///
+    /// Results are returned in the order that the objects were submitted in.
+    /// Only results that haven't been returned by a previous call are returned.
+    ///
+    /// # Panics
+    ///
+    /// Resumes the panic, if validating any of the objects panicked.
+    pub fn collect(&self) -> Vec<(AnyObject<Stored>, Vec<ValidationError>)> {
+        let results_rx = self.results.lock();
+        let num_submitted = self.num_submitted.load(Ordering::SeqCst);
+
+        let mut results = Vec::new();
+        while self.num_collected.load(Ordering::SeqCst) < num_submitted {
+            let result = results_rx
+                .recv()
+                .expect("Validation threads stopped unexpectedly");
+            self.num_collected.fetch_add(1, Ordering::SeqCst);
+            results.push(result);
+        }
+
+        results.sort_by_key(|result| result.index);
+
+        results
+            .into_iter()
+            .map(|result| match result.errors {
+                Ok(errors) => (result.object, errors),
+                Err(payload) => panic::resume_unwind(payload),
+            })
+            .collect()
+    }
+}
+
+impl Drop for ValidationWorkers {
+    fn drop(&mut self) {
+        // Dropping the sender causes the threads to exit, once they have
+        // finished their current job.
+        self.jobs.take();
+
+        for thread in self.threads.drain(..) {
+            // Panics while validating are caught, and there's nothing else in
+            // the threads that is expected to panic.
+            let _ = thread.join();
+        }
+    }
+}
+
+struct Job {
+    index: u64,
+    object: AnyObject<Stored>,
+}
+
+struct JobResult {
+    index: u64,
+    object: AnyObject<Stored>,
+    errors: thread::Result<Vec<ValidationError>>,
+}
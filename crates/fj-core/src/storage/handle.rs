@@ -2,6 +2,8 @@ use std::{
     any::type_name, borrow::Borrow, cmp::Ordering, fmt, hash::Hash, ops::Deref,
 };
 
+use serde::{Serialize, Serializer};
+
 use super::{blocks::Index, store::StoreInner};
 
 /// # A handle that references a stored object
@@ -210,6 +212,17 @@ impl ObjectId {
     }
 }
 
+impl Serialize for ObjectId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // Serialized as a string, as the ID can exceed the range of integers
+        // that many JSON parsers can represent exactly.
+        serializer.collect_str(&format_args!("{:#x}", self.0))
+    }
+}
+
 impl fmt::Debug for ObjectId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let id = self.0;
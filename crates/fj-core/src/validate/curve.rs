@@ -8,5 +8,9 @@ impl Validate for Curve {
         _: &ValidationConfig,
         _: &mut Vec<ValidationError>,
     ) {
+        // `Curve` holds no data that could be validated. Its geometry is
+        // defined by the half-edges that refer to it, and those don't exist in
+        // isolation. Whether they agree on that geometry is checked where they
+        // come together, by the validation of `Shell`.
     }
 }
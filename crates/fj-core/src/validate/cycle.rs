@@ -1,11 +1,15 @@
 use fj_math::{Point, Scalar};
 
 use crate::{
+    geometry::SurfacePath,
     objects::{Cycle, HalfEdge},
     storage::Handle,
 };
 
-use super::{Validate, ValidationConfig, ValidationError};
+use super::{
+    polyline::Polyline, report::Details, Validate, ValidationCheck,
+    ValidationConfig, ValidationError,
+};
 
 impl Validate for Cycle {
     fn validate_with_config(
@@ -13,7 +17,17 @@ impl Validate for Cycle {
         config: &ValidationConfig,
         errors: &mut Vec<ValidationError>,
     ) {
-        CycleValidationError::check_half_edge_connections(self, config, errors);
+        if config.is_enabled(ValidationCheck::CycleHalfEdgeConnections) {
+            CycleValidationError::check_half_edge_connections(
+                self, config, errors,
+            );
+        }
+        if config.is_enabled(ValidationCheck::CycleHalfEdgeLength) {
+            CycleValidationError::check_half_edge_length(self, config, errors);
+        }
+        if config.is_enabled(ValidationCheck::CycleSelfIntersection) {
+            CycleValidationError::check_self_intersection(self, config, errors);
+        }
     }
 }
 
@@ -41,9 +55,80 @@ pub enum CycleValidationError {
         /// The edges
         half_edges: [Handle<HalfEdge>; 2],
     },
+
+    /// [`Cycle`] has a half-edge whose length is zero
+    #[error(
+        "`Cycle` has a `HalfEdge` whose length is zero\n\
+        - Length of `HalfEdge`: {length}\n\
+        - `HalfEdge`: {half_edge:#?}"
+    )]
+    ZeroLengthHalfEdge {
+        /// The length of the half-edge, in surface coordinates
+        length: Scalar,
+
+        /// The half-edge
+        half_edge: Handle<HalfEdge>,
+    },
+
+    /// Half-edges of [`Cycle`] cross or overlap each other
+    ///
+    /// Half-edges may touch in single points, as adjacent half-edges do at the
+    /// vertex they share.
+    #[error(
+        "`HalfEdge`s of `Cycle` intersect each other\n\
+        - Intersection: {point:?}\n\
+        - Intersecting `HalfEdge`s: {half_edges:#?}"
+    )]
+    IntersectingHalfEdges {
+        /// The point where the half-edges intersect, in surface coordinates
+        point: Point<2>,
+
+        /// The intersecting half-edges
+        half_edges: [Handle<HalfEdge>; 2],
+    },
 }
 
 impl CycleValidationError {
+    /// The validation check that produced this error
+    pub fn check(&self) -> ValidationCheck {
+        match self {
+            Self::HalfEdgesNotConnected { .. } => {
+                ValidationCheck::CycleHalfEdgeConnections
+            }
+            Self::ZeroLengthHalfEdge { .. } => {
+                ValidationCheck::CycleHalfEdgeLength
+            }
+            Self::IntersectingHalfEdges { .. } => {
+                ValidationCheck::CycleSelfIntersection
+            }
+        }
+    }
+
+    /// The details of this error, for use in a [`ValidationReport`]
+    ///
+    /// [`ValidationReport`]: super::ValidationReport
+    pub(super) fn details(&self) -> Details {
+        match self {
+            Self::HalfEdgesNotConnected {
+                end_of_first,
+                start_of_second,
+                half_edges,
+                ..
+            } => Details::new("HalfEdgesNotConnected")
+                .objects(half_edges)
+                .position(*end_of_first)
+                .position(*start_of_second),
+            Self::ZeroLengthHalfEdge { half_edge, .. } => {
+                Details::new("ZeroLengthHalfEdge").object(half_edge)
+            }
+            Self::IntersectingHalfEdges { point, half_edges } => {
+                Details::new("IntersectingHalfEdges")
+                    .objects(half_edges)
+                    .position(*point)
+            }
+        }
+    }
+
     fn check_half_edge_connections(
         cycle: &Cycle,
         config: &ValidationConfig,
@@ -71,11 +156,75 @@ impl CycleValidationError {
             }
         }
     }
+
+    fn check_half_edge_length(
+        cycle: &Cycle,
+        config: &ValidationConfig,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        for half_edge in cycle.half_edges() {
+            let [start, end] = half_edge.boundary().inner;
+
+            // Distances along the path are proportional to its coordinates.
+            // The factor is the length of a line's direction vector, or a
+            // circle's radius.
+            let scale = match half_edge.path() {
+                SurfacePath::Circle(circle) => circle.radius(),
+                SurfacePath::Line(line) => line.direction().magnitude(),
+            };
+            let length = (end - start).magnitude() * scale;
+
+            if length < config.distinct_min_distance {
+                errors.push(
+                    Self::ZeroLengthHalfEdge {
+                        length,
+                        half_edge: half_edge.clone(),
+                    }
+                    .into(),
+                );
+            }
+        }
+    }
+
+    fn check_self_intersection(
+        cycle: &Cycle,
+        config: &ValidationConfig,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        // In a cycle of less than three half-edges, each half-edge shares both
+        // of its vertices with the other one. That's a valid cycle, if it's
+        // made up of circles (see `Cycle::winding`). Half-edges that share both
+        // vertices can't cross, and whether they overlap each other is not
+        // decided here.
+        if cycle.half_edges().len() < 3 {
+            return;
+        }
+
+        // Adjacent half-edges are checked too. They always touch at the vertex
+        // they share, which is allowed, but they must not overlap.
+        let polylines = Polyline::from_cycle(cycle);
+
+        for (i, a) in polylines.iter().enumerate() {
+            for b in polylines.iter().skip(i + 1) {
+                if let Some(point) = a.intersection(b, config) {
+                    errors.push(
+                        Self::IntersectingHalfEdges {
+                            point,
+                            half_edges: [
+                                a.half_edge.clone(),
+                                b.half_edge.clone(),
+                            ],
+                        }
+                        .into(),
+                    );
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-
     use crate::{
         assert_contains_err,
         objects::{Cycle, HalfEdge},
@@ -84,7 +233,10 @@ mod tests {
             insert::Insert,
             update::UpdateCycle,
         },
-        validate::{cycle::CycleValidationError, Validate, ValidationError},
+        validate::{
+            cycle::CycleValidationError, Severity, Validate, ValidationCheck,
+            ValidationConfig, ValidationError,
+        },
         Instance,
     };
 
@@ -116,4 +268,118 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn zero_length_half_edge() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let valid = Cycle::polygon(
+            [[0., 0.], [1., 0.], [1., 1e-3], [1., 1.], [0., 1.]],
+            &mut core,
+        );
+        let invalid = Cycle::polygon(
+            [[0., 0.], [1., 0.], [1., 1e-9], [1., 1.], [0., 1.]],
+            &mut core,
+        );
+
+        valid.validate_and_return_first_error()?;
+        assert_contains_err!(
+            invalid,
+            ValidationError::Cycle(
+                CycleValidationError::ZeroLengthHalfEdge { .. }
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn identical_polygon_points() {
+        let mut core = Instance::new();
+
+        let _cycle = Cycle::polygon(
+            [[0., 0.], [1., 0.], [1., 0.], [1., 1.], [0., 1.]],
+            &mut core,
+        )
+        .insert(&mut core.services);
+
+        let report =
+            core.services.drop_and_report(&ValidationConfig::default());
+        assert!(report
+            .errors
+            .iter()
+            .any(|entry| entry.check == ValidationCheck::CycleHalfEdgeLength
+                && entry.kind == "ZeroLengthHalfEdge"));
+    }
+
+    #[test]
+    fn self_intersecting() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let valid =
+            Cycle::polygon([[0., 0.], [3., 0.], [3., 3.], [0., 2.]], &mut core);
+        let crossing =
+            Cycle::polygon([[0., 0.], [3., 3.], [3., 0.], [0., 2.]], &mut core);
+        let folding_back =
+            Cycle::polygon([[0., 0.], [2., 0.], [1., 0.], [1., 1.]], &mut core);
+        let collinear =
+            Cycle::polygon([[0., 0.], [1., 0.], [2., 0.]], &mut core);
+
+        valid.validate_and_return_first_error()?;
+        for invalid in [crossing, folding_back, collinear] {
+            assert_contains_err!(
+                invalid,
+                ValidationError::Cycle(
+                    CycleValidationError::IntersectingHalfEdges { .. }
+                )
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn self_intersection_severity() -> anyhow::Result<()> {
+        let self_intersecting_cycle = |core: &mut Instance| {
+            Cycle::polygon([[0., 0.], [3., 3.], [3., 0.], [0., 2.]], core)
+                .insert(&mut core.services)
+        };
+
+        let config = ValidationConfig::default().with_severity(
+            ValidationCheck::CycleSelfIntersection,
+            Severity::Warning,
+        );
+        let mut core = Instance::with_validation_config(config.clone());
+        self_intersecting_cycle(&mut core);
+        let warnings = core.services.drop_and_validate_with_config(&config)?;
+        assert!(matches!(
+            warnings.as_slice(),
+            [ValidationError::Cycle(
+                CycleValidationError::IntersectingHalfEdges { .. }
+            )]
+        ));
+
+        let config = ValidationConfig::default().with_severity(
+            ValidationCheck::CycleSelfIntersection,
+            Severity::Disabled,
+        );
+        let mut core = Instance::with_validation_config(config.clone());
+        self_intersecting_cycle(&mut core);
+        let warnings = core.services.drop_and_validate_with_config(&config)?;
+        assert!(warnings.is_empty());
+
+        // Severities can also be overridden, after the objects have been
+        // validated.
+        let mut core = Instance::new();
+        self_intersecting_cycle(&mut core);
+        let warnings = core.services.drop_and_validate_with_config(
+            &ValidationConfig::default().with_severity(
+                ValidationCheck::CycleSelfIntersection,
+                Severity::Warning,
+            ),
+        )?;
+        assert_eq!(warnings.len(), 1);
+
+        Ok(())
+    }
 }
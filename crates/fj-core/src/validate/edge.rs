@@ -2,7 +2,10 @@ use fj_math::{Point, Scalar};
 
 use crate::objects::HalfEdge;
 
-use super::{Validate, ValidationConfig, ValidationError};
+use super::{
+    report::Details, Validate, ValidationCheck, ValidationConfig,
+    ValidationError,
+};
 
 impl Validate for HalfEdge {
     fn validate_with_config(
@@ -10,7 +13,9 @@ impl Validate for HalfEdge {
         config: &ValidationConfig,
         errors: &mut Vec<ValidationError>,
     ) {
-        EdgeValidationError::check_vertex_coincidence(self, config, errors);
+        if config.is_enabled(ValidationCheck::HalfEdgeVertexCoincidence) {
+            EdgeValidationError::check_vertex_coincidence(self, config, errors);
+        }
     }
 }
 
@@ -40,6 +45,30 @@ pub enum EdgeValidationError {
 }
 
 impl EdgeValidationError {
+    /// The validation check that produced this error
+    pub fn check(&self) -> ValidationCheck {
+        match self {
+            Self::VerticesAreCoincident { .. } => {
+                ValidationCheck::HalfEdgeVertexCoincidence
+            }
+        }
+    }
+
+    /// The details of this error, for use in a [`ValidationReport`]
+    ///
+    /// [`ValidationReport`]: super::ValidationReport
+    pub(super) fn details(&self) -> Details {
+        match self {
+            Self::VerticesAreCoincident {
+                back_position,
+                front_position,
+                ..
+            } => Details::new("VerticesAreCoincident")
+                .position(*back_position)
+                .position(*front_position),
+        }
+    }
+
     fn check_vertex_coincidence(
         edge: &HalfEdge,
         config: &ValidationConfig,
@@ -1,17 +1,30 @@
-use fj_math::Winding;
+use fj_math::{Point, Winding};
 
-use crate::objects::Face;
+use crate::{
+    objects::{Face, HalfEdge},
+    storage::Handle,
+};
 
-use super::{Validate, ValidationConfig, ValidationError};
+use super::{
+    polyline::Polyline, report::Details, Validate, ValidationCheck,
+    ValidationConfig, ValidationError,
+};
 
 impl Validate for Face {
     fn validate_with_config(
         &self,
-        _: &ValidationConfig,
+        config: &ValidationConfig,
         errors: &mut Vec<ValidationError>,
     ) {
-        FaceValidationError::check_boundary(self, errors);
-        FaceValidationError::check_interior_winding(self, errors);
+        if config.is_enabled(ValidationCheck::FaceBoundary) {
+            FaceValidationError::check_boundary(self, errors);
+        }
+        if config.is_enabled(ValidationCheck::FaceInteriorWinding) {
+            FaceValidationError::check_interior_winding(self, errors);
+        }
+        if config.is_enabled(ValidationCheck::FaceSelfIntersection) {
+            FaceValidationError::check_self_intersection(self, config, errors);
+        }
     }
 }
 
@@ -39,9 +52,56 @@ pub enum FaceValidationError {
         /// The face
         face: Face,
     },
+
+    /// Two cycles of [`Face`] cross or overlap each other
+    ///
+    /// Interior cycles must be inside the exterior cycle, and must not cross
+    /// each other. Touching in a single point is allowed.
+    #[error(
+        "Two cycles of `Face` intersect each other\n\
+        - Intersection: {point:?}\n\
+        - Intersecting `HalfEdge`s: {half_edges:#?}"
+    )]
+    IntersectingCycles {
+        /// The point where the half-edges intersect, in surface coordinates
+        point: Point<2>,
+
+        /// The intersecting half-edges, one from each cycle
+        half_edges: [Handle<HalfEdge>; 2],
+    },
 }
 
 impl FaceValidationError {
+    /// The validation check that produced this error
+    pub fn check(&self) -> ValidationCheck {
+        match self {
+            Self::MissingBoundary => ValidationCheck::FaceBoundary,
+            Self::InvalidInteriorWinding { .. } => {
+                ValidationCheck::FaceInteriorWinding
+            }
+            Self::IntersectingCycles { .. } => {
+                ValidationCheck::FaceSelfIntersection
+            }
+        }
+    }
+
+    /// The details of this error, for use in a [`ValidationReport`]
+    ///
+    /// [`ValidationReport`]: super::ValidationReport
+    pub(super) fn details(&self) -> Details {
+        match self {
+            Self::MissingBoundary => Details::new("MissingBoundary"),
+            Self::InvalidInteriorWinding { .. } => {
+                Details::new("InvalidInteriorWinding")
+            }
+            Self::IntersectingCycles { point, half_edges } => {
+                Details::new("IntersectingCycles")
+                    .objects(half_edges)
+                    .position(*point)
+            }
+        }
+    }
+
     fn check_boundary(face: &Face, errors: &mut Vec<ValidationError>) {
         if face.region().exterior().half_edges().is_empty() {
             errors.push(ValidationError::from(Self::MissingBoundary));
@@ -52,21 +112,17 @@ impl FaceValidationError {
     }
 
     fn check_interior_winding(face: &Face, errors: &mut Vec<ValidationError>) {
-        if face.region().exterior().half_edges().is_empty() {
-            // Can't determine winding, if the cycle has no edges. Sounds like a
-            // job for a different validation check.
+        // Can't determine winding, if a cycle has no edges or doesn't have any
+        // area. Sounds like a job for a different validation check.
+        let Some(exterior_winding) = face.region().exterior().try_winding()
+        else {
             return;
-        }
-
-        let exterior_winding = face.region().exterior().winding();
+        };
 
         for interior in face.region().interiors() {
-            if interior.half_edges().is_empty() {
-                // Can't determine winding, if the cycle has no edges. Sounds
-                // like a job for a different validation check.
+            let Some(interior_winding) = interior.try_winding() else {
                 continue;
-            }
-            let interior_winding = interior.winding();
+            };
 
             if exterior_winding == interior_winding {
                 errors.push(
@@ -80,6 +136,45 @@ impl FaceValidationError {
             }
         }
     }
+
+    fn check_self_intersection(
+        face: &Face,
+        config: &ValidationConfig,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        // Cycles are allowed to touch each other in single points, for example
+        // where a vertex of an interior cycle lies on the exterior cycle. Only
+        // crossing or overlapping half-edges are reported.
+        //
+        // Whether a cycle intersects itself is checked by the validation of
+        // `Cycle`.
+        let cycles = face
+            .region()
+            .all_cycles()
+            .map(|cycle| Polyline::from_cycle(cycle))
+            .collect::<Vec<_>>();
+
+        for (i, cycle) in cycles.iter().enumerate() {
+            for other in cycles.iter().skip(i + 1) {
+                for a in cycle {
+                    for b in other {
+                        if let Some(point) = a.intersection(b, config) {
+                            errors.push(
+                                Self::IntersectingCycles {
+                                    point,
+                                    half_edges: [
+                                        a.half_edge.clone(),
+                                        b.half_edge.clone(),
+                                    ],
+                                }
+                                .into(),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -93,7 +188,10 @@ mod tests {
             reverse::Reverse,
             update::{UpdateCycle, UpdateFace, UpdateRegion},
         },
-        validate::{FaceValidationError, Validate, ValidationError},
+        validate::{
+            FaceValidationError, Validate, ValidationCheck, ValidationConfig,
+            ValidationError,
+        },
         Instance,
     };
 
@@ -143,7 +241,7 @@ mod tests {
                             .insert(&mut core.services)
                         })
                         .add_interiors([Cycle::polygon(
-                            [[1., 1.], [1., 2.], [2., 1.]],
+                            [[1., 1.], [1., 1.5], [1.5, 1.]],
                             &mut core,
                         )
                         .insert(&mut core.services)])
@@ -180,4 +278,113 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn degenerate_exterior() {
+        let mut core = Instance::new();
+
+        // The exterior cycle has no area, so its winding can't be determined.
+        // This must be reported by the validation of `Cycle`, instead of
+        // causing a panic while validating the face.
+        let _face =
+            Face::unbound(core.services.objects.surfaces.xy_plane(), &mut core)
+                .update_region(|region| {
+                    region
+                        .update_exterior(|_| {
+                            Cycle::polygon(
+                                [[0., 0.], [1., 0.], [2., 0.]],
+                                &mut core,
+                            )
+                            .insert(&mut core.services)
+                        })
+                        .insert(&mut core.services)
+                })
+                .insert(&mut core.services);
+
+        let report =
+            core.services.drop_and_report(&ValidationConfig::default());
+        assert!(
+            report
+                .errors
+                .iter()
+                .all(|entry| entry.check
+                    == ValidationCheck::CycleSelfIntersection)
+        );
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn intersecting_cycles() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let exterior =
+            Cycle::polygon([[0., 0.], [3., 0.], [0., 3.]], &mut core)
+                .insert(&mut core.services);
+        let valid =
+            Face::unbound(core.services.objects.surfaces.xy_plane(), &mut core)
+                .update_region(|region| {
+                    region
+                        .update_exterior(|_| exterior.clone())
+                        .add_interiors([
+                            Cycle::polygon(
+                                [[0.5, 0.5], [0.5, 1.], [1., 0.5]],
+                                &mut core,
+                            )
+                            .insert(&mut core.services),
+                            Cycle::polygon(
+                                [[1., 1.], [1., 1.5], [1.5, 1.]],
+                                &mut core,
+                            )
+                            .insert(&mut core.services),
+                        ])
+                        .insert(&mut core.services)
+                });
+        let crossing_exterior =
+            Face::unbound(core.services.objects.surfaces.xy_plane(), &mut core)
+                .update_region(|region| {
+                    region
+                        .update_exterior(|_| exterior.clone())
+                        .add_interiors([Cycle::polygon(
+                            [[1., 1.], [1., 4.], [2., 1.]],
+                            &mut core,
+                        )
+                        .insert(&mut core.services)])
+                        .insert(&mut core.services)
+                });
+        let crossing_interior =
+            Face::unbound(core.services.objects.surfaces.xy_plane(), &mut core)
+                .update_region(|region| {
+                    region
+                        .update_exterior(|_| exterior.clone())
+                        .add_interiors([
+                            Cycle::polygon(
+                                [[0.5, 0.5], [0.5, 1.5], [1.5, 0.5]],
+                                &mut core,
+                            )
+                            .insert(&mut core.services),
+                            Cycle::polygon(
+                                [[1., 0.25], [1., 1.], [1.75, 0.25]],
+                                &mut core,
+                            )
+                            .insert(&mut core.services),
+                        ])
+                        .insert(&mut core.services)
+                });
+
+        valid.validate_and_return_first_error()?;
+        assert_contains_err!(
+            crossing_exterior,
+            ValidationError::Face(
+                FaceValidationError::IntersectingCycles { .. }
+            )
+        );
+        assert_contains_err!(
+            crossing_interior,
+            ValidationError::Face(
+                FaceValidationError::IntersectingCycles { .. }
+            )
+        );
+
+        Ok(())
+    }
 }
@@ -45,6 +45,10 @@
 //! handle validation errors in a different way, you don't have to do anything
 //! special to use the validation infrastructure.
 //!
+//! If you need to process validation results programmatically, for example in
+//! CI, [`Services::drop_and_report`] returns a [`ValidationReport`] instead of
+//! panicking. The report can be serialized as JSON.
+//!
 //!
 //! ## Configuration
 //!
@@ -59,25 +63,32 @@
 //! on, and this fact is taken into account by allowing for configuration via
 //! [`Validate::validate_with_config`] and [`ValidationConfig`].
 //!
-//!
-//! ## Implementation Note
-//!
-//! It is currently not possible to define this configuration for the background
-//! validation done by the [`Services`] API. If this is a problem for you,
-//! please comment on this issue:
-//! <https://github.com/hannobraun/fornjot/issues/2060>
+//! The configuration for the background validation done by the [`Services`]
+//! API is set when constructing it, using [`Instance::with_validation_config`]
+//! or [`Services::with_validation_config`]. The severities of validation
+//! checks can also be overridden when dropping [`Services`], using
+//! [`Services::drop_and_validate_with_config`] or
+//! [`Services::drop_and_report`]. Models that use the `fj` crate can build a
+//! configuration from their command-line arguments, using
+//! `Args::validation_config`.
 //!
 //!
 //! [`fj-export`]: https://crates.io/crates/fj-export
 //! [issue tracker]: https://github.com/hannobraun/fornjot/issues
+//! [`Instance::with_validation_config`]: crate::Instance::with_validation_config
 //! [`Services`]: crate::services::Services
+//! [`Services::with_validation_config`]: crate::services::Services::with_validation_config
+//! [`Services::drop_and_validate_with_config`]: crate::services::Services::drop_and_validate_with_config
+//! [`Services::drop_and_report`]: crate::services::Services::drop_and_report
 
 mod curve;
 mod cycle;
 mod edge;
 mod face;
+mod polyline;
 mod references;
 mod region;
+mod report;
 mod shell;
 mod sketch;
 mod solid;
@@ -85,14 +96,20 @@ mod surface;
 mod vertex;
 
 pub use self::{
-    cycle::CycleValidationError, edge::EdgeValidationError,
-    face::FaceValidationError, shell::ShellValidationError,
-    sketch::SketchValidationError, solid::SolidValidationError,
+    cycle::CycleValidationError,
+    edge::EdgeValidationError,
+    face::FaceValidationError,
+    report::{ReportedObject, ValidationReport, ValidationReportEntry},
+    shell::ShellValidationError,
+    sketch::SketchValidationError,
+    solid::SolidValidationError,
+    surface::SurfaceValidationError,
 };
 
-use std::{convert::Infallible, fmt};
+use std::{collections::BTreeMap, convert::Infallible, fmt};
 
 use fj_math::Scalar;
+use serde::{Serialize, Serializer};
 
 /// Assert that some object has a validation error which matches a specific
 /// pattern. This is preferred to matching on [`Validate::validate_and_return_first_error`], since usually we don't care about the order.
@@ -138,7 +155,7 @@ pub trait Validate: Sized {
 }
 
 /// Configuration required for the validation process
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct ValidationConfig {
     /// The minimum distance between distinct objects
     ///
@@ -153,6 +170,41 @@ pub struct ValidationConfig {
     /// that distance is less than the one defined in this field, can not be
     /// considered identical.
     pub identical_max_distance: Scalar,
+
+    /// How individual validation checks are treated
+    ///
+    /// Checks that are not listed here are treated as [`Severity::Error`].
+    pub severities: BTreeMap<ValidationCheck, Severity>,
+
+    /// The number of threads that validate objects in the background
+    ///
+    /// If this is zero, objects are validated synchronously, when they are
+    /// inserted. Otherwise, validation happens in parallel with the
+    /// construction of the shape, and results are collected later. Either way,
+    /// the results are the same.
+    pub worker_threads: usize,
+}
+
+impl ValidationConfig {
+    /// Treat the provided validation check with the provided severity
+    pub fn with_severity(
+        mut self,
+        check: ValidationCheck,
+        severity: Severity,
+    ) -> Self {
+        self.severities.insert(check, severity);
+        self
+    }
+
+    /// Access the severity of the provided validation check
+    pub fn severity(&self, check: ValidationCheck) -> Severity {
+        self.severities.get(&check).copied().unwrap_or_default()
+    }
+
+    /// Indicate whether the provided validation check should run
+    pub fn is_enabled(&self, check: ValidationCheck) -> bool {
+        self.severity(check) != Severity::Disabled
+    }
 }
 
 impl Default for ValidationConfig {
@@ -165,10 +217,166 @@ impl Default for ValidationConfig {
             // false positives due to floating-point accuracy issues), we can
             // adjust it.
             identical_max_distance: Scalar::from_f64(5e-14),
+
+            severities: BTreeMap::new(),
+
+            worker_threads: 0,
         }
     }
 }
 
+/// A validation check
+///
+/// Each variant identifies one of the validation checks, which can be
+/// configured individually using [`ValidationConfig`]. The errors that a check
+/// produces can be identified using [`ValidationError::check`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum ValidationCheck {
+    /// Adjacent half-edges of a cycle must be connected
+    CycleHalfEdgeConnections,
+
+    /// The half-edges of a cycle must not have zero length
+    CycleHalfEdgeLength,
+
+    /// The half-edges of a cycle must not intersect or overlap each other
+    CycleSelfIntersection,
+
+    /// The vertices of a half-edge must not be coincident
+    HalfEdgeVertexCoincidence,
+
+    /// A face must have a boundary
+    FaceBoundary,
+
+    /// The interior cycles of a face must be wound opposite to its exterior
+    FaceInteriorWinding,
+
+    /// The cycles of a face must not intersect each other
+    FaceSelfIntersection,
+
+    /// Half-edges on the same curve must use matching coordinate systems
+    ShellCurveCoordinates,
+
+    /// Each half-edge of a shell must have a sibling
+    ShellHalfEdgePairs,
+
+    /// Neighboring faces of a shell must be oriented the same way
+    ShellOrientation,
+
+    /// Half-edges of a shell that refer to the same curve must coincide
+    ShellCurveGeometry,
+
+    /// Half-edges of a shell that are coincident must be siblings
+    ShellHalfEdgeCoincidence,
+
+    /// Faces of a shell must not intersect each other
+    ShellFaceIntersections,
+
+    /// Objects within a sketch must only be referenced once
+    SketchObjectReferences,
+
+    /// Vertices of a solid must be identical, if and only if they coincide
+    SolidVertices,
+
+    /// Objects within a solid must only be referenced once
+    SolidObjectReferences,
+
+    /// Shells of a solid must not intersect each other
+    SolidShellIntersections,
+
+    /// Shells of a solid must point outward, or inward if they bound a cavity
+    SolidShellOrientation,
+
+    /// The geometry of a surface must not be degenerate
+    SurfaceGeometry,
+}
+
+impl ValidationCheck {
+    /// All validation checks
+    pub const ALL: [Self; 19] = [
+        Self::CycleHalfEdgeConnections,
+        Self::CycleHalfEdgeLength,
+        Self::CycleSelfIntersection,
+        Self::HalfEdgeVertexCoincidence,
+        Self::FaceBoundary,
+        Self::FaceInteriorWinding,
+        Self::FaceSelfIntersection,
+        Self::ShellCurveCoordinates,
+        Self::ShellHalfEdgePairs,
+        Self::ShellOrientation,
+        Self::ShellCurveGeometry,
+        Self::ShellHalfEdgeCoincidence,
+        Self::ShellFaceIntersections,
+        Self::SketchObjectReferences,
+        Self::SolidVertices,
+        Self::SolidObjectReferences,
+        Self::SolidShellIntersections,
+        Self::SolidShellOrientation,
+        Self::SurfaceGeometry,
+    ];
+
+    /// The name of the validation check, for use in configuration files or
+    /// command-line arguments
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CycleHalfEdgeConnections => "cycle-half-edge-connections",
+            Self::CycleHalfEdgeLength => "cycle-half-edge-length",
+            Self::CycleSelfIntersection => "cycle-self-intersection",
+            Self::HalfEdgeVertexCoincidence => "half-edge-vertex-coincidence",
+            Self::FaceBoundary => "face-boundary",
+            Self::FaceInteriorWinding => "face-interior-winding",
+            Self::FaceSelfIntersection => "face-self-intersection",
+            Self::ShellCurveCoordinates => "shell-curve-coordinates",
+            Self::ShellHalfEdgePairs => "shell-half-edge-pairs",
+            Self::ShellOrientation => "shell-orientation",
+            Self::ShellCurveGeometry => "shell-curve-geometry",
+            Self::ShellHalfEdgeCoincidence => "shell-half-edge-coincidence",
+            Self::ShellFaceIntersections => "shell-face-intersections",
+            Self::SketchObjectReferences => "sketch-object-references",
+            Self::SolidVertices => "solid-vertices",
+            Self::SolidObjectReferences => "solid-object-references",
+            Self::SolidShellIntersections => "solid-shell-intersections",
+            Self::SolidShellOrientation => "solid-shell-orientation",
+            Self::SurfaceGeometry => "surface-geometry",
+        }
+    }
+
+    /// Find the validation check with the provided name
+    ///
+    /// See [`ValidationCheck::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|check| check.name() == name)
+    }
+}
+
+impl Serialize for ValidationCheck {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl fmt::Display for ValidationCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// How a validation check is treated
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Severity {
+    /// The check doesn't run
+    Disabled,
+
+    /// The check runs, but its errors are only reported as warnings
+    Warning,
+
+    /// The check runs, and its errors are reported as errors
+    #[default]
+    Error,
+}
+
 /// An error that can occur during a validation
 #[derive(Clone, Debug, thiserror::Error)]
 pub enum ValidationError {
@@ -195,6 +403,25 @@ pub enum ValidationError {
     /// `Sketch` validation error
     #[error("`Sketch` validation error")]
     Sketch(#[from] SketchValidationError),
+
+    /// `Surface` validation error
+    #[error("`Surface` validation error")]
+    Surface(#[from] SurfaceValidationError),
+}
+
+impl ValidationError {
+    /// The validation check that produced this error
+    pub fn check(&self) -> ValidationCheck {
+        match self {
+            Self::Cycle(err) => err.check(),
+            Self::Edge(err) => err.check(),
+            Self::Face(err) => err.check(),
+            Self::Shell(err) => err.check(),
+            Self::Solid(err) => err.check(),
+            Self::Sketch(err) => err.check(),
+            Self::Surface(err) => err.check(),
+        }
+    }
 }
 
 impl From<Infallible> for ValidationError {
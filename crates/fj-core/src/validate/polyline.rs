@@ -0,0 +1,498 @@
+//! Approximations of half-edges and faces, used by intersection checks
+//!
+//! Intersection checks need to work with all kinds of curves, but most of the
+//! intersection algorithms only support lines and line segments. The types in
+//! this module approximate half-edges as chains of line segments, and curved
+//! faces as triangles, so those algorithms can be used.
+
+use std::collections::BTreeSet;
+
+use fj_math::{Aabb, Line, Plane, Point, Scalar, Segment, Vector};
+
+use crate::{
+    algorithms::{
+        approx::{Approx, Tolerance},
+        intersect::LineSegmentIntersection,
+        triangulate::Triangulate,
+    },
+    geometry::{GlobalPath, SurfaceGeometry, SurfacePath},
+    objects::{Cycle, Face, HalfEdge},
+    storage::{Handle, ObjectId},
+};
+
+use super::ValidationConfig;
+
+/// A half-edge, approximated as a chain of line segments in surface coordinates
+pub struct Polyline<'r> {
+    pub half_edge: &'r Handle<HalfEdge>,
+    pub points: Vec<Point<2>>,
+    pub aabb: Aabb<2>,
+}
+
+impl<'r> Polyline<'r> {
+    /// Approximate all half-edges of the provided cycle
+    pub fn from_cycle(cycle: &'r Cycle) -> Vec<Self> {
+        cycle
+            .half_edges()
+            .iter()
+            .map(|half_edge| {
+                let [_, end] = half_edge.boundary().inner;
+
+                let mut points = vec![half_edge.start_position()];
+
+                if let SurfacePath::Circle(circle) = half_edge.path() {
+                    // The approximation deviates from the arc by a small
+                    // fraction of its radius. Intersections that are closer to
+                    // the arc than that can be missed.
+                    let tolerance =
+                        circle.radius() * Scalar::from_f64(RELATIVE_TOLERANCE);
+                    if let Ok(tolerance) = Tolerance::from_scalar(tolerance) {
+                        let approx = (&half_edge.path(), half_edge.boundary())
+                            .approx(tolerance)
+                            .unwrap_or_default();
+                        points
+                            .extend(approx.into_iter().map(|(_, point)| point));
+                    }
+                }
+
+                points.push(half_edge.path().point_from_path_coords(end));
+
+                let aabb = Aabb::<2>::from_points(points.iter().copied());
+
+                Self {
+                    half_edge,
+                    points,
+                    aabb,
+                }
+            })
+            .collect()
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = Segment<2>> + '_ {
+        self.points
+            .windows(2)
+            .filter(|points| points[0] != points[1])
+            .map(|points| Segment::from_points([points[0], points[1]]))
+    }
+
+    /// Find a point where this polyline crosses or overlaps the other one
+    pub fn intersection(
+        &self,
+        other: &Self,
+        config: &ValidationConfig,
+    ) -> Option<Point<2>> {
+        let margin = config.distinct_min_distance;
+        if !self.aabb.loosened(margin).intersects(&other.aabb) {
+            return None;
+        }
+
+        self.segments().find_map(|a| {
+            other
+                .segments()
+                .find_map(|b| intersect_segments(&a, &b, margin))
+        })
+    }
+}
+
+/// A half-edge, approximated as a chain of line segments in global coordinates
+pub struct GlobalPolyline<'r> {
+    pub half_edge: &'r Handle<HalfEdge>,
+    pub points: Vec<Point<3>>,
+    pub aabb: Aabb<3>,
+}
+
+impl<'r> GlobalPolyline<'r> {
+    /// Approximate all half-edges of the provided face
+    pub fn from_face(face: &'r Face) -> Vec<Self> {
+        let surface = face.surface().geometry();
+
+        // A straight line in the coordinates of a curved surface is curved in
+        // global coordinates. Subdivide those lines, so they deviate from the
+        // surface as little as arcs deviate from their circle.
+        let max_angle = match surface.u {
+            GlobalPath::Circle(_) => {
+                Some(Scalar::from_f64((1. - RELATIVE_TOLERANCE).acos() * 2.))
+            }
+            GlobalPath::Line(_) => None,
+        };
+
+        face.region()
+            .all_cycles()
+            .flat_map(|cycle| Polyline::from_cycle(cycle))
+            .map(|polyline| {
+                let mut points_surface = polyline
+                    .points
+                    .first()
+                    .into_iter()
+                    .copied()
+                    .collect::<Vec<_>>();
+                for points in polyline.points.windows(2) {
+                    let [a, b] = [points[0], points[1]];
+
+                    if let Some(max_angle) = max_angle {
+                        let n =
+                            ((b.u - a.u).abs() / max_angle).ceil().into_f64()
+                                as usize;
+                        points_surface.extend(
+                            (1..n).map(|i| a + (b - a) * (i as f64 / n as f64)),
+                        );
+                    }
+
+                    points_surface.push(b);
+                }
+
+                let points = points_surface
+                    .into_iter()
+                    .map(|point| surface.point_from_surface_coords(point))
+                    .collect::<Vec<_>>();
+                let aabb = Aabb::<3>::from_points(points.iter().copied());
+
+                Self {
+                    half_edge: polyline.half_edge,
+                    points,
+                    aabb,
+                }
+            })
+            .collect()
+    }
+
+    /// Compute the AABB of the provided half-edges
+    ///
+    /// Returns a degenerate AABB at the origin, if there are no half-edges.
+    pub fn aabb_of(half_edges: &[Self]) -> Aabb<3> {
+        half_edges
+            .iter()
+            .map(|half_edge| half_edge.aabb)
+            .reduce(|a, b| a.merged(&b))
+            .unwrap_or_default()
+    }
+}
+
+/// A face that half-edges can pierce
+pub enum PierceableFace<'r> {
+    Planar(PlanarFace<'r>),
+    Curved(CurvedFace),
+}
+
+impl<'r> PierceableFace<'r> {
+    /// Approximate the provided face
+    ///
+    /// Returns `None`, if the face is curved and can't be triangulated.
+    pub fn new(face: &'r Face) -> Option<Self> {
+        match PlanarFace::new(face) {
+            Some(face) => Some(Self::Planar(face)),
+            None => CurvedFace::new(face).map(Self::Curved),
+        }
+    }
+
+    /// Find a point where the provided half-edge pierces the face
+    ///
+    /// See [`PlanarFace::pierced_by`] and [`CurvedFace::pierced_by`].
+    pub fn pierced_by(
+        &self,
+        half_edge: &GlobalPolyline,
+        config: &ValidationConfig,
+    ) -> Option<Point<3>> {
+        match self {
+            Self::Planar(face) => face.pierced_by(half_edge, config),
+            Self::Curved(face) => face.pierced_by(half_edge, config),
+        }
+    }
+}
+
+/// A face that lies in a plane, approximated as polygons in surface coordinates
+pub struct PlanarFace<'r> {
+    plane: Plane,
+    normal: Vector<3>,
+    curves: BTreeSet<ObjectId>,
+    polylines: Vec<Polyline<'r>>,
+    aabb: Aabb<3>,
+}
+
+impl<'r> PlanarFace<'r> {
+    /// Approximate the provided face
+    ///
+    /// Returns `None`, if the face's surface is not a plane.
+    pub fn new(face: &'r Face) -> Option<Self> {
+        let SurfaceGeometry {
+            u: GlobalPath::Line(line),
+            v,
+        } = face.surface().geometry()
+        else {
+            return None;
+        };
+
+        let plane = Plane::from_parametric(line.origin(), line.direction(), v);
+        let normal = plane.normal().normalize();
+
+        let polylines = face
+            .region()
+            .all_cycles()
+            .flat_map(|cycle| Polyline::from_cycle(cycle))
+            .collect::<Vec<_>>();
+        let curves = polylines
+            .iter()
+            .map(|polyline| polyline.half_edge.curve().id())
+            .collect();
+        let surface = face.surface().geometry();
+        let aabb =
+            Aabb::<3>::from_points(polylines.iter().flat_map(|polyline| {
+                polyline
+                    .points
+                    .iter()
+                    .map(|&point| surface.point_from_surface_coords(point))
+            }));
+
+        Some(Self {
+            plane,
+            normal,
+            curves,
+            polylines,
+            aabb,
+        })
+    }
+
+    /// Find a point where the provided half-edge pierces the face
+    ///
+    /// Half-edges that only touch the face, meaning they intersect it within
+    /// the configured distance of the face's boundary or of one of their own
+    /// vertices, do not pierce it. Neither do half-edges that lie on one of the
+    /// face's curves. A half-edge that lies in the face's plane pierces it, if
+    /// it runs through the inside of the face.
+    pub fn pierced_by(
+        &self,
+        half_edge: &GlobalPolyline,
+        config: &ValidationConfig,
+    ) -> Option<Point<3>> {
+        let margin = config.distinct_min_distance;
+
+        if self.curves.contains(&half_edge.half_edge.curve().id()) {
+            return None;
+        }
+        if !self.aabb.loosened(margin).intersects(&half_edge.aabb) {
+            return None;
+        }
+
+        half_edge.points.windows(2).find_map(|points| {
+            let [a, b] = [points[0], points[1]];
+            let [distance_a, distance_b] = [a, b]
+                .map(|point| (point - self.plane.origin()).dot(&self.normal));
+
+            let point =
+                if distance_a.abs() <= margin && distance_b.abs() <= margin {
+                    a + (b - a) / 2.
+                } else if distance_a > margin && distance_b < -margin
+                    || distance_a < -margin && distance_b > margin
+                {
+                    a + (b - a) * (distance_a / (distance_a - distance_b))
+                } else {
+                    return None;
+                };
+
+            self.contains(self.plane.project_point(point), margin)
+                .then_some(point)
+        })
+    }
+
+    /// Determine whether the point is inside the face, and not near its edge
+    fn contains(&self, point: Point<2>, margin: Scalar) -> bool {
+        let mut num_crossings = 0;
+
+        for segment in self.polylines.iter().flat_map(Polyline::segments) {
+            let [a, b] = segment.points();
+
+            if distance_to_segment(point, a, b) <= margin {
+                return false;
+            }
+
+            // Count the crossings of a ray that starts at the point and extends
+            // in positive u-direction.
+            if (a.v > point.v) != (b.v > point.v) {
+                let u = a.u + (b.u - a.u) * (point.v - a.v) / (b.v - a.v);
+                if u > point.u {
+                    num_crossings += 1;
+                }
+            }
+        }
+
+        num_crossings % 2 == 1
+    }
+}
+
+/// A curved face, approximated as triangles in global coordinates
+///
+/// The triangles deviate from the surface by a small fraction of the face's
+/// size. Half-edges that pierce the face closer to its boundary than that can
+/// be missed. Half-edges that lie within the surface don't pierce it.
+pub struct CurvedFace {
+    curves: BTreeSet<ObjectId>,
+    triangles: Vec<[Point<3>; 3]>,
+    boundary: Vec<Segment<3>>,
+    tolerance: Scalar,
+    aabb: Aabb<3>,
+}
+
+impl CurvedFace {
+    /// Approximate the provided face
+    ///
+    /// Returns `None`, if the face can't be triangulated.
+    pub fn new(face: &Face) -> Option<Self> {
+        let polylines = GlobalPolyline::from_face(face);
+
+        let size = GlobalPolyline::aabb_of(&polylines)
+            .size()
+            .components
+            .into_iter()
+            .max()?;
+        let tolerance = size * Scalar::from_f64(1e-3);
+
+        let mesh = face
+            .approx(Tolerance::from_scalar(tolerance).ok()?)
+            .ok()?
+            .triangulate()
+            .ok()?;
+        let triangles = mesh
+            .triangles()
+            .map(|triangle| triangle.inner.points())
+            .collect::<Vec<_>>();
+
+        let curves = polylines
+            .iter()
+            .map(|polyline| polyline.half_edge.curve().id())
+            .collect();
+        let boundary = polylines
+            .iter()
+            .flat_map(|polyline| {
+                polyline
+                    .points
+                    .windows(2)
+                    .filter(|points| points[0] != points[1])
+                    .map(|points| Segment::from_points([points[0], points[1]]))
+            })
+            .collect();
+        let aabb = Aabb::<3>::from_points(triangles.iter().flatten().copied());
+
+        Some(Self {
+            curves,
+            triangles,
+            boundary,
+            tolerance,
+            aabb,
+        })
+    }
+
+    /// Find a point where the provided half-edge pierces the face
+    ///
+    /// Works like [`PlanarFace::pierced_by`], except that half-edges which lie
+    /// within the surface never pierce the face. Distances smaller than the
+    /// deviation of the approximation from the surface are ignored.
+    pub fn pierced_by(
+        &self,
+        half_edge: &GlobalPolyline,
+        config: &ValidationConfig,
+    ) -> Option<Point<3>> {
+        let margin = config.distinct_min_distance.max(self.tolerance);
+
+        if self.curves.contains(&half_edge.half_edge.curve().id()) {
+            return None;
+        }
+        if !self.aabb.loosened(margin).intersects(&half_edge.aabb) {
+            return None;
+        }
+
+        half_edge.points.windows(2).find_map(|points| {
+            let [a, b] = [points[0], points[1]];
+
+            self.triangles.iter().find_map(|&[p0, p1, p2]| {
+                let normal = (p1 - p0).cross(&(p2 - p0));
+                if normal.magnitude() == Scalar::ZERO {
+                    return None;
+                }
+                let normal = normal.normalize();
+
+                let [distance_a, distance_b] =
+                    [a, b].map(|point| (point - p0).dot(&normal));
+                let crosses = distance_a > margin && distance_b < -margin
+                    || distance_a < -margin && distance_b > margin;
+                if !crosses {
+                    return None;
+                }
+
+                let point =
+                    a + (b - a) * (distance_a / (distance_a - distance_b));
+
+                let is_inside_triangle =
+                    [[p0, p1], [p1, p2], [p2, p0]].into_iter().all(|[p, q]| {
+                        (q - p).cross(&(point - p)).dot(&normal) >= Scalar::ZERO
+                    });
+                let is_near_boundary = self.boundary.iter().any(|segment| {
+                    let [p, q] = segment.points();
+                    distance_to_segment(point, p, q) <= margin
+                });
+
+                (is_inside_triangle && !is_near_boundary).then_some(point)
+            })
+        })
+    }
+}
+
+/// Determine whether two segments cross or overlap
+///
+/// Returns a point where they do. Segments that only touch, meaning they
+/// intersect within `margin` of one of their end points, are not considered to
+/// cross. Collinear segments are considered to overlap, if the overlap is
+/// longer than `margin`.
+fn intersect_segments(
+    a: &Segment<2>,
+    b: &Segment<2>,
+    margin: Scalar,
+) -> Option<Point<2>> {
+    let (line, _) = Line::from_points(a.points());
+
+    match LineSegmentIntersection::compute(&line, b)? {
+        LineSegmentIntersection::Point { point_on_line } => {
+            if point_on_line.t < Scalar::ZERO || point_on_line.t > Scalar::ONE {
+                return None;
+            }
+
+            let point = line.point_from_line_coords(point_on_line);
+            let is_end_point = a
+                .points()
+                .into_iter()
+                .chain(b.points())
+                .any(|end| point.distance_to(&end) <= margin);
+
+            (!is_end_point).then_some(point)
+        }
+        LineSegmentIntersection::Coincident { points_on_line } => {
+            let [b0, b1] = points_on_line.map(|point| point.t);
+            let [min, max] = if b0 < b1 { [b0, b1] } else { [b1, b0] };
+
+            let start = min.max(Scalar::ZERO);
+            let end = max.min(Scalar::ONE);
+
+            let overlap = (end - start) * line.direction().magnitude();
+            if overlap <= margin {
+                return None;
+            }
+
+            Some(line.point_from_line_coords([(start + end) / 2.]))
+        }
+    }
+}
+
+fn distance_to_segment<const D: usize>(
+    point: Point<D>,
+    a: Point<D>,
+    b: Point<D>,
+) -> Scalar {
+    let ab = b - a;
+    let t = ((point - a).dot(&ab) / ab.dot(&ab))
+        .max(Scalar::ZERO)
+        .min(Scalar::ONE);
+
+    point.distance_to(&(a + ab * t))
+}
+
+/// The deviation of approximated arcs from their circle, relative to its radius
+const RELATIVE_TOLERANCE: f64 = 1e-3;
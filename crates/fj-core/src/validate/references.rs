@@ -4,6 +4,8 @@ use std::hash::Hash;
 use crate::objects::{Cycle, Face, HalfEdge, Region, Shell};
 use crate::storage::Handle;
 
+use super::report::Details;
+
 #[derive(Default)]
 pub struct ReferenceCounter<T, U>(HashMap<Handle<T>, Vec<Handle<U>>>);
 
@@ -80,11 +82,29 @@ pub enum ReferenceCountError {
     },
 }
 
+impl ReferenceCountError {
+    /// Add the objects that this error refers to
+    pub(super) fn add_objects(&self, details: Details) -> Details {
+        match self {
+            Self::Region { references } => references.add_objects(details),
+            Self::Face { references } => references.add_objects(details),
+            Self::HalfEdge { references } => references.add_objects(details),
+            Self::Cycle { references } => references.add_objects(details),
+        }
+    }
+}
+
 pub struct MultipleReferences<T, U> {
     referenced: Handle<T>,
     references: Vec<Handle<U>>,
 }
 
+impl<T, U> MultipleReferences<T, U> {
+    fn add_objects(&self, details: Details) -> Details {
+        details.object(&self.referenced).objects(&self.references)
+    }
+}
+
 use std::fmt::Debug;
 
 impl<T: Debug, U: Debug> Debug for MultipleReferences<T, U> {
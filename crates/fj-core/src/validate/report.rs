@@ -0,0 +1,212 @@
+use std::{any::type_name, error::Error, io};
+
+use fj_math::Point;
+use serde::Serialize;
+
+use crate::storage::{Handle, ObjectId};
+
+use super::{ValidationCheck, ValidationError};
+
+/// A machine-readable report of validation results
+///
+/// Can be serialized using `serde`, or written as JSON using
+/// [`ValidationReport::write_json`].
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ValidationReport {
+    /// The validation errors
+    pub errors: Vec<ValidationReportEntry>,
+
+    /// The validation warnings
+    ///
+    /// These are produced by validation checks that are configured as
+    /// [`Severity::Warning`].
+    ///
+    /// [`Severity::Warning`]: super::Severity::Warning
+    pub warnings: Vec<ValidationReportEntry>,
+}
+
+impl ValidationReport {
+    /// Indicate whether the report contains no errors
+    ///
+    /// Warnings don't make the validated objects invalid.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Write the report as JSON
+    pub fn write_json(&self, writer: impl io::Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+}
+
+/// A validation error, as described in a [`ValidationReport`]
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidationReportEntry {
+    /// The object that was validated
+    pub object: ReportedObject,
+
+    /// The validation check that produced the error
+    pub check: ValidationCheck,
+
+    /// The kind of error, as named by the variant of the validation error
+    pub kind: &'static str,
+
+    /// The error message
+    pub message: String,
+
+    /// The objects that the error refers to
+    pub objects: Vec<ReportedObject>,
+
+    /// The positions that the error refers to
+    ///
+    /// Positions are given in the coordinates that the validation check works
+    /// with: curve coordinates for half-edges, surface coordinates for faces,
+    /// and global coordinates for shells and solids. Their number of
+    /// components reflects that.
+    pub positions: Vec<Vec<f64>>,
+
+    /// The messages of the errors that caused this one, outermost first
+    pub sources: Vec<String>,
+}
+
+impl ValidationReportEntry {
+    /// Describe the provided validation error of the provided object
+    pub fn new(object: ObjectId, err: &ValidationError) -> Self {
+        let (object_kind, details) = match err {
+            ValidationError::Cycle(err) => ("Cycle", err.details()),
+            ValidationError::Edge(err) => ("HalfEdge", err.details()),
+            ValidationError::Face(err) => ("Face", err.details()),
+            ValidationError::Shell(err) => ("Shell", err.details()),
+            ValidationError::Solid(err) => ("Solid", err.details()),
+            ValidationError::Sketch(err) => ("Sketch", err.details()),
+            ValidationError::Surface(err) => ("Surface", err.details()),
+        };
+
+        let mut sources = Vec::new();
+        let mut source = err.source();
+        while let Some(err) = source {
+            sources.push(err.to_string());
+            source = err.source();
+        }
+
+        Self {
+            object: ReportedObject {
+                kind: object_kind,
+                id: object,
+            },
+            check: err.check(),
+            kind: details.kind,
+            message: err.to_string(),
+            objects: details.objects,
+            positions: details.positions,
+            sources,
+        }
+    }
+}
+
+/// An object that is referred to in a [`ValidationReport`]
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct ReportedObject {
+    /// The kind of object, for example `HalfEdge`
+    pub kind: &'static str,
+
+    /// The ID of the object
+    pub id: ObjectId,
+}
+
+/// The parts of a [`ValidationReportEntry`] that depend on the kind of error
+pub(super) struct Details {
+    kind: &'static str,
+    objects: Vec<ReportedObject>,
+    positions: Vec<Vec<f64>>,
+}
+
+impl Details {
+    pub fn new(kind: &'static str) -> Self {
+        Self {
+            kind,
+            objects: Vec::new(),
+            positions: Vec::new(),
+        }
+    }
+
+    pub fn object<T>(self, handle: &Handle<T>) -> Self {
+        self.objects([handle])
+    }
+
+    pub fn objects<'r, T: 'r>(
+        mut self,
+        handles: impl IntoIterator<Item = &'r Handle<T>>,
+    ) -> Self {
+        // Type names are qualified by their module path, which isn't relevant
+        // here.
+        let kind = type_name::<T>().rsplit("::").next().unwrap_or_default();
+
+        self.objects
+            .extend(handles.into_iter().map(|handle| ReportedObject {
+                kind,
+                id: handle.id(),
+            }));
+        self
+    }
+
+    pub fn position<const D: usize>(mut self, point: Point<D>) -> Self {
+        self.positions.push(
+            point
+                .coords
+                .components
+                .iter()
+                .map(|component| component.into_f64())
+                .collect(),
+        );
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        objects::Cycle,
+        operations::{build::BuildCycle, insert::Insert},
+        validate::{ValidationCheck, ValidationConfig},
+        Instance,
+    };
+
+    #[test]
+    fn report() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let cycle =
+            Cycle::polygon([[0., 0.], [3., 3.], [3., 0.], [0., 2.]], &mut core)
+                .insert(&mut core.services);
+
+        let report =
+            core.services.drop_and_report(&ValidationConfig::default());
+        assert!(!report.is_valid());
+
+        let [entry] = report.errors.as_slice() else {
+            panic!("Expected exactly one error: {report:#?}");
+        };
+        assert_eq!(entry.object.kind, "Cycle");
+        assert_eq!(entry.object.id, cycle.id());
+        assert_eq!(entry.check, ValidationCheck::CycleSelfIntersection);
+        assert_eq!(entry.kind, "IntersectingHalfEdges");
+        assert_eq!(entry.objects.len(), 2);
+        assert!(entry.objects.iter().all(|object| object.kind == "HalfEdge"));
+        let [position] = entry.positions.as_slice() else {
+            panic!("Expected exactly one position: {entry:#?}");
+        };
+        assert_eq!(position.len(), 2);
+        assert!(position.iter().all(|&c| (c - 1.2f64).abs() < 1e-12));
+        assert_eq!(entry.sources.len(), 1);
+
+        let mut json = Vec::new();
+        report.write_json(&mut json)?;
+        let json: serde_json::Value = serde_json::from_slice(&json)?;
+        assert_eq!(json["errors"][0]["check"], "cycle-self-intersection");
+        assert_eq!(json["warnings"].as_array().map(Vec::len), Some(0));
+
+        Ok(())
+    }
+}
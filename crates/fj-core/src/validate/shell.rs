@@ -1,17 +1,21 @@
 use std::{collections::BTreeMap, fmt};
 
-use fj_math::{Point, Scalar};
+use fj_math::{Aabb, Bvh, Point, Scalar};
 
 use crate::{
-    geometry::{CurveBoundary, SurfaceGeometry},
-    objects::{Curve, HalfEdge, Shell, Surface, Vertex},
+    geometry::{CurveBoundary, GlobalPath, SurfaceGeometry, SurfacePath},
+    objects::{Curve, Face, HalfEdge, Shell, Surface, Vertex},
     queries::{
         AllHalfEdgesWithSurface, BoundingVerticesOfHalfEdge, SiblingOfHalfEdge,
     },
-    storage::{Handle, HandleWrapper},
+    storage::{Handle, HandleWrapper, ObjectId},
 };
 
-use super::{Validate, ValidationConfig, ValidationError};
+use super::{
+    polyline::{GlobalPolyline, PierceableFace},
+    report::Details,
+    Validate, ValidationCheck, ValidationConfig, ValidationError,
+};
 
 impl Validate for Shell {
     fn validate_with_config(
@@ -19,9 +23,28 @@ impl Validate for Shell {
         config: &ValidationConfig,
         errors: &mut Vec<ValidationError>,
     ) {
-        ShellValidationError::check_curve_coordinates(self, config, errors);
-        ShellValidationError::check_half_edge_pairs(self, errors);
-        ShellValidationError::check_half_edge_coincidence(self, config, errors);
+        if config.is_enabled(ValidationCheck::ShellCurveCoordinates) {
+            ShellValidationError::check_curve_coordinates(self, config, errors);
+        }
+        if config.is_enabled(ValidationCheck::ShellCurveGeometry) {
+            ShellValidationError::check_curve_geometry(self, config, errors);
+        }
+        if config.is_enabled(ValidationCheck::ShellHalfEdgePairs) {
+            ShellValidationError::check_half_edge_pairs(self, errors);
+        }
+        if config.is_enabled(ValidationCheck::ShellOrientation) {
+            ShellValidationError::check_orientation(self, errors);
+        }
+        if config.is_enabled(ValidationCheck::ShellHalfEdgeCoincidence) {
+            ShellValidationError::check_half_edge_coincidence(
+                self, config, errors,
+            );
+        }
+        if config.is_enabled(ValidationCheck::ShellFaceIntersections) {
+            ShellValidationError::check_face_intersections(
+                self, config, errors,
+            );
+        }
     }
 }
 
@@ -36,6 +59,31 @@ pub enum ShellValidationError {
     )]
     CurveCoordinateSystemMismatch(Vec<CurveCoordinateSystemMismatch>),
 
+    /// [`Shell`] contains half-edges that refer to the same curve, but whose
+    /// paths don't coincide
+    #[error(
+        "`HalfEdge`s refer to the same `Curve`, but their paths don't \
+        coincide\n\
+        - Point on first `HalfEdge`: {point:?}\n\
+        - Distance to path of second `HalfEdge`: {distance}\n\
+        - `Curve`: {curve:?}\n\
+        - `HalfEdge`s: {half_edges:#?}"
+    )]
+    CurvePathsNotCoincident {
+        /// The curve that both half-edges refer to
+        curve: Handle<Curve>,
+
+        /// The half-edges
+        half_edges: [Handle<HalfEdge>; 2],
+
+        /// A point on the first half-edge that is not on the path of the
+        /// second, in global coordinates
+        point: Point<3>,
+
+        /// The distance between `point` and the path of the second half-edge
+        distance: Scalar,
+    },
+
     /// [`Shell`] contains a half-edge that is not part of a pair
     #[error("Half-edge has no sibling: {half_edge:#?}")]
     HalfEdgeHasNoSibling {
@@ -43,6 +91,19 @@ pub enum ShellValidationError {
         half_edge: Handle<HalfEdge>,
     },
 
+    /// [`Shell`] contains faces that are oriented inconsistently
+    ///
+    /// Neighboring faces must be oriented the same way, meaning the half-edges
+    /// they share must run in opposite directions.
+    #[error(
+        "`Shell` contains `Face`s that are oriented inconsistently\n\
+        - `HalfEdge`s running in the same direction: {half_edges:#?}"
+    )]
+    InconsistentOrientation {
+        /// The half-edges that run in the same direction
+        half_edges: [Handle<HalfEdge>; 2],
+    },
+
     /// [`Shell`] contains half-edges that are coincident, but aren't siblings
     #[error(
         "`Shell` contains `HalfEdge`s that are coincident but are not \
@@ -69,97 +130,236 @@ pub enum ShellValidationError {
         /// The second half-edge
         half_edge_b: Handle<HalfEdge>,
     },
+
+    /// [`Shell`] contains faces that pierce each other
+    ///
+    /// Faces are allowed to meet at their shared half-edges, and to touch in
+    /// single points.
+    #[error(
+        "`Shell` contains `Face`s that intersect each other\n\
+        - Intersection: {point:?}\n\
+        - Piercing `HalfEdge`: {half_edge:#?}"
+    )]
+    IntersectingFaces {
+        /// A point where the faces intersect
+        point: Point<3>,
+
+        /// The half-edge of the first face that pierces the second face
+        half_edge: Handle<HalfEdge>,
+
+        /// The intersecting faces
+        faces: [Handle<Face>; 2],
+    },
 }
 
 impl ShellValidationError {
+    /// The validation check that produced this error
+    pub fn check(&self) -> ValidationCheck {
+        match self {
+            Self::CurveCoordinateSystemMismatch(_) => {
+                ValidationCheck::ShellCurveCoordinates
+            }
+            Self::CurvePathsNotCoincident { .. } => {
+                ValidationCheck::ShellCurveGeometry
+            }
+            Self::HalfEdgeHasNoSibling { .. } => {
+                ValidationCheck::ShellHalfEdgePairs
+            }
+            Self::InconsistentOrientation { .. } => {
+                ValidationCheck::ShellOrientation
+            }
+            Self::CoincidentHalfEdgesAreNotSiblings { .. } => {
+                ValidationCheck::ShellHalfEdgeCoincidence
+            }
+            Self::IntersectingFaces { .. } => {
+                ValidationCheck::ShellFaceIntersections
+            }
+        }
+    }
+
+    /// The details of this error, for use in a [`ValidationReport`]
+    ///
+    /// [`ValidationReport`]: super::ValidationReport
+    pub(super) fn details(&self) -> Details {
+        match self {
+            Self::CurveCoordinateSystemMismatch(mismatches) => {
+                mismatches.iter().fold(
+                    Details::new("CurveCoordinateSystemMismatch"),
+                    |details, mismatch| {
+                        details
+                            .objects([
+                                &mismatch.half_edge_a,
+                                &mismatch.half_edge_b,
+                            ])
+                            .position(mismatch.point_a)
+                            .position(mismatch.point_b)
+                    },
+                )
+            }
+            Self::CurvePathsNotCoincident {
+                curve,
+                half_edges,
+                point,
+                ..
+            } => Details::new("CurvePathsNotCoincident")
+                .object(curve)
+                .objects(half_edges)
+                .position(*point),
+            Self::HalfEdgeHasNoSibling { half_edge } => {
+                Details::new("HalfEdgeHasNoSibling").object(half_edge)
+            }
+            Self::InconsistentOrientation { half_edges } => {
+                Details::new("InconsistentOrientation").objects(half_edges)
+            }
+            Self::CoincidentHalfEdgesAreNotSiblings {
+                curves,
+                half_edge_a,
+                half_edge_b,
+                ..
+            } => Details::new("CoincidentHalfEdgesAreNotSiblings")
+                .objects([half_edge_a, half_edge_b])
+                .objects(&curves.curves),
+            Self::IntersectingFaces {
+                point,
+                half_edge,
+                faces,
+            } => Details::new("IntersectingFaces")
+                .object(half_edge)
+                .objects(faces)
+                .position(*point),
+        }
+    }
+
     /// Check that local curve definitions that refer to the same curve match
     fn check_curve_coordinates(
         shell: &Shell,
         config: &ValidationConfig,
         errors: &mut Vec<ValidationError>,
     ) {
-        let mut edges_and_surfaces = Vec::new();
-        shell.all_half_edges_with_surface(&mut edges_and_surfaces);
+        for edges in half_edges_by_curve(shell).values() {
+            for (i, (edge_a, surface_a)) in edges.iter().enumerate() {
+                for (edge_b, surface_b) in edges.iter().skip(i + 1) {
+                    // No need to check an edge against itself.
+                    if edge_a.id() == edge_b.id() {
+                        continue;
+                    }
 
-        for (edge_a, surface_a) in &edges_and_surfaces {
-            for (edge_b, surface_b) in &edges_and_surfaces {
-                // We only care about edges referring to the same curve.
-                if edge_a.curve().id() != edge_b.curve().id() {
-                    continue;
-                }
+                    // If the paths of the half-edges don't coincide, their
+                    // coordinate systems can't match either. That is reported
+                    // by `Self::check_curve_geometry` already.
+                    if config.is_enabled(ValidationCheck::ShellCurveGeometry)
+                        && path_deviation(
+                            edge_a, surface_a, edge_b, surface_b, config,
+                        )
+                        .is_some()
+                    {
+                        continue;
+                    }
 
-                // No need to check an edge against itself.
-                if edge_a.id() == edge_b.id() {
-                    continue;
-                }
+                    let mut mismatches = Vec::new();
 
-                fn compare_curve_coords(
-                    edge_a: &Handle<HalfEdge>,
-                    surface_a: &Handle<Surface>,
-                    edge_b: &Handle<HalfEdge>,
-                    surface_b: &Handle<Surface>,
-                    config: &ValidationConfig,
-                    mismatches: &mut Vec<CurveCoordinateSystemMismatch>,
-                ) {
-                    // Let's check 4 points. Given that the most complex curves
-                    // we have right now are circles, 3 would be enough to check
-                    // for coincidence. But the first and last might be
-                    // identical, so let's add an extra one.
-                    let [a, d] = edge_a.boundary().inner;
-                    let b = a + (d - a) * 1. / 3.;
-                    let c = a + (d - a) * 2. / 3.;
-
-                    for point_curve in [a, b, c, d] {
-                        let a_surface =
-                            edge_a.path().point_from_path_coords(point_curve);
-                        let b_surface =
-                            edge_b.path().point_from_path_coords(point_curve);
-
-                        let a_global = surface_a
-                            .geometry()
-                            .point_from_surface_coords(a_surface);
-                        let b_global = surface_b
-                            .geometry()
-                            .point_from_surface_coords(b_surface);
-
-                        let distance = (a_global - b_global).magnitude();
-
-                        if distance > config.identical_max_distance {
-                            mismatches.push(CurveCoordinateSystemMismatch {
-                                half_edge_a: edge_a.clone(),
-                                half_edge_b: edge_b.clone(),
-                                point_curve,
-                                point_a: a_global,
-                                point_b: b_global,
-                                distance,
-                            });
-                        }
+                    compare_curve_coords(
+                        edge_a,
+                        surface_a,
+                        edge_b,
+                        surface_b,
+                        config,
+                        &mut mismatches,
+                    );
+                    compare_curve_coords(
+                        edge_b,
+                        surface_b,
+                        edge_a,
+                        surface_a,
+                        config,
+                        &mut mismatches,
+                    );
+
+                    if !mismatches.is_empty() {
+                        errors.push(
+                            Self::CurveCoordinateSystemMismatch(mismatches)
+                                .into(),
+                        );
                     }
                 }
+            }
+        }
 
-                let mut mismatches = Vec::new();
-
-                compare_curve_coords(
-                    edge_a,
-                    surface_a,
-                    edge_b,
-                    surface_b,
-                    config,
-                    &mut mismatches,
-                );
-                compare_curve_coords(
-                    edge_b,
-                    surface_b,
-                    edge_a,
-                    surface_a,
-                    config,
-                    &mut mismatches,
-                );
-
-                if !mismatches.is_empty() {
-                    errors.push(
-                        Self::CurveCoordinateSystemMismatch(mismatches).into(),
+        fn compare_curve_coords(
+            edge_a: &Handle<HalfEdge>,
+            surface_a: &Handle<Surface>,
+            edge_b: &Handle<HalfEdge>,
+            surface_b: &Handle<Surface>,
+            config: &ValidationConfig,
+            mismatches: &mut Vec<CurveCoordinateSystemMismatch>,
+        ) {
+            // Let's check 4 points. Given that the most complex curves we have
+            // right now are circles, 3 would be enough to check for
+            // coincidence. But the first and last might be identical, so let's
+            // add an extra one.
+            let [a, d] = edge_a.boundary().inner;
+            let b = a + (d - a) * 1. / 3.;
+            let c = a + (d - a) * 2. / 3.;
+
+            for point_curve in [a, b, c, d] {
+                let a_surface =
+                    edge_a.path().point_from_path_coords(point_curve);
+                let b_surface =
+                    edge_b.path().point_from_path_coords(point_curve);
+
+                let a_global =
+                    surface_a.geometry().point_from_surface_coords(a_surface);
+                let b_global =
+                    surface_b.geometry().point_from_surface_coords(b_surface);
+
+                let distance = (a_global - b_global).magnitude();
+
+                if distance > config.identical_max_distance {
+                    mismatches.push(CurveCoordinateSystemMismatch {
+                        half_edge_a: edge_a.clone(),
+                        half_edge_b: edge_b.clone(),
+                        point_curve,
+                        point_a: a_global,
+                        point_b: b_global,
+                        distance,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Check that half-edges that refer to the same curve are coincident
+    ///
+    /// Unlike [`Self::check_curve_coordinates`], this doesn't require the
+    /// half-edges to use the same coordinate system for the curve. It only
+    /// checks that their paths describe the same curve in global space.
+    ///
+    /// Half-edges that fail this check are skipped by
+    /// [`Self::check_curve_coordinates`], so a mismatched curve is only
+    /// reported once.
+    fn check_curve_geometry(
+        shell: &Shell,
+        config: &ValidationConfig,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        for edges in half_edges_by_curve(shell).values() {
+            for (i, (edge_a, surface_a)) in edges.iter().enumerate() {
+                for (edge_b, surface_b) in edges.iter().skip(i + 1) {
+                    let deviation = path_deviation(
+                        edge_a, surface_a, edge_b, surface_b, config,
                     );
+
+                    if let Some((point, distance)) = deviation {
+                        errors.push(
+                            Self::CurvePathsNotCoincident {
+                                curve: edge_a.curve().clone(),
+                                half_edges: [edge_a.clone(), edge_b.clone()],
+                                point,
+                                distance,
+                            }
+                            .into(),
+                        );
+                    }
                 }
             }
         }
@@ -207,6 +407,37 @@ impl ShellValidationError {
         }
     }
 
+    /// Check that neighboring faces are oriented the same way
+    fn check_orientation(shell: &Shell, errors: &mut Vec<ValidationError>) {
+        let mut half_edges = BTreeMap::new();
+
+        for face in shell.faces() {
+            for cycle in face.region().all_cycles() {
+                for half_edge in cycle.half_edges() {
+                    let curve = HandleWrapper::from(half_edge.curve().clone());
+                    let boundary = half_edge.boundary();
+                    let vertices =
+                        cycle.bounding_vertices_of_half_edge(half_edge).expect(
+                            "`half_edge` came from `cycle`, must exist there",
+                        );
+
+                    // Half-edges that have the same key run along the same
+                    // edge, in the same direction. That means the faces they
+                    // bound are oriented differently.
+                    let key = (curve, boundary, vertices);
+                    if let Some(other) = half_edges.insert(key, half_edge) {
+                        errors.push(
+                            Self::InconsistentOrientation {
+                                half_edges: [other.clone(), half_edge.clone()],
+                            }
+                            .into(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
     /// Check that non-sibling half-edges are not coincident
     fn check_half_edge_coincidence(
         shell: &Shell,
@@ -216,11 +447,27 @@ impl ShellValidationError {
         let mut edges_and_surfaces = Vec::new();
         shell.all_half_edges_with_surface(&mut edges_and_surfaces);
 
-        // This is O(N^2) which isn't great, but we can't use a HashMap since we
-        // need to deal with float inaccuracies. Maybe we could use some smarter
-        // data-structure like an octree.
-        for (half_edge_a, surface_a) in &edges_and_surfaces {
-            for (half_edge_b, surface_b) in &edges_and_surfaces {
+        // Comparing all half-edges with each other would be O(N^2). But if all
+        // samples of two half-edges are close to each other, so are the AABBs
+        // of those samples. We use a spatial index to find those.
+        let aabbs = edges_and_surfaces
+            .iter()
+            .map(|(half_edge, surface)| {
+                Aabb::<3>::from_points(
+                    SAMPLES.map(|percent| sample(percent, half_edge, surface)),
+                )
+            })
+            .collect::<Vec<_>>();
+        let bvh = Bvh::new(aabbs.iter().copied());
+
+        for ((half_edge_a, surface_a), aabb) in
+            edges_and_surfaces.iter().zip(&aabbs)
+        {
+            let aabb = aabb.loosened(config.distinct_min_distance);
+
+            for b in bvh.intersecting(&aabb) {
+                let (half_edge_b, surface_b) = &edges_and_surfaces[b];
+
                 // No need to check a half-edge against itself.
                 if half_edge_a.id() == half_edge_b.id() {
                     continue;
@@ -275,6 +522,70 @@ impl ShellValidationError {
             }
         }
     }
+
+    /// Check that faces don't pierce each other
+    ///
+    /// Curved faces are approximated, which can cause intersections close to
+    /// their boundary to be missed. See [`CurvedFace`].
+    ///
+    /// [`CurvedFace`]: super::polyline::CurvedFace
+    fn check_face_intersections(
+        shell: &Shell,
+        config: &ValidationConfig,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let faces = shell
+            .faces()
+            .iter()
+            .map(|face| {
+                (
+                    face,
+                    PierceableFace::new(face),
+                    GlobalPolyline::from_face(face),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Only faces whose AABBs intersect can pierce each other. We use a
+        // spatial index to find those.
+        let aabbs = faces
+            .iter()
+            .map(|(_, _, half_edges)| GlobalPolyline::aabb_of(half_edges))
+            .collect::<Vec<_>>();
+        let bvh = Bvh::new(aabbs.iter().copied());
+
+        for ((face_a, _, half_edges), aabb) in faces.iter().zip(&aabbs) {
+            let aabb = aabb.loosened(config.distinct_min_distance);
+
+            for b in bvh.intersecting(&aabb) {
+                let (face_b, pierceable_face, _) = &faces[b];
+
+                if face_a.id() == face_b.id() {
+                    continue;
+                }
+                let Some(pierceable_face) = pierceable_face else {
+                    continue;
+                };
+
+                let intersection = half_edges.iter().find_map(|half_edge| {
+                    pierceable_face
+                        .pierced_by(half_edge, config)
+                        .map(|point| (point, half_edge.half_edge))
+                });
+
+                if let Some((point, half_edge)) = intersection {
+                    errors.push(
+                        Self::IntersectingFaces {
+                            point,
+                            half_edge: half_edge.clone(),
+                            faces: [(*face_a).clone(), (*face_b).clone()],
+                        }
+                        .into(),
+                    );
+                }
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -356,6 +667,167 @@ impl fmt::Display for CoincidentHalfEdgeVertices {
     }
 }
 
+/// Find the point on the path that is closest to the provided point
+///
+/// Paths extend beyond the boundaries of the half-edges that they belong to.
+/// Lines are infinite, and circles are always complete.
+type HalfEdgeWithSurface = (Handle<HalfEdge>, Handle<Surface>);
+
+/// Group the half-edges of a shell, and their surfaces, by their curve
+///
+/// Only half-edges that refer to the same curve need to be compared with each
+/// other, which this makes possible without comparing all pairs of half-edges.
+fn half_edges_by_curve(
+    shell: &Shell,
+) -> BTreeMap<ObjectId, Vec<HalfEdgeWithSurface>> {
+    let mut edges_and_surfaces = Vec::new();
+    shell.all_half_edges_with_surface(&mut edges_and_surfaces);
+
+    let mut edges_by_curve = BTreeMap::new();
+    for (half_edge, surface) in edges_and_surfaces {
+        edges_by_curve
+            .entry(half_edge.curve().id())
+            .or_insert_with(Vec::new)
+            .push((half_edge, surface));
+    }
+
+    edges_by_curve
+}
+
+/// Find a point on one half-edge that is not on the path of the other
+///
+/// Returns the point, in global coordinates, and its distance to the other
+/// path. Returns `None`, if the paths coincide, or if that can't be determined.
+fn path_deviation(
+    edge_a: &Handle<HalfEdge>,
+    surface_a: &Handle<Surface>,
+    edge_b: &Handle<HalfEdge>,
+    surface_b: &Handle<Surface>,
+    config: &ValidationConfig,
+) -> Option<(Point<3>, Scalar)> {
+    // Points can't be projected into degenerate surfaces. Try the other
+    // direction, if that's the case for the second surface. Degenerate
+    // surfaces are caught by the validation of `Surface`.
+    let deviations = path_deviations(edge_a, surface_a, edge_b, surface_b)
+        .or_else(|| path_deviations(edge_b, surface_b, edge_a, surface_a))?;
+
+    // Rounding errors from the projection make requiring identical points
+    // impractical. Paths that are closer than the minimum distance can't be
+    // distinct.
+    deviations
+        .into_iter()
+        .find(|(_, distance)| *distance > config.distinct_min_distance)
+}
+
+fn closest_point_on_path(path: SurfacePath, point: Point<2>) -> Point<2> {
+    match path {
+        SurfacePath::Circle(circle) => {
+            let center_to_point = point - circle.center();
+            if center_to_point.magnitude() == Scalar::ZERO {
+                // All points on the circle are equally close.
+                return circle.point_from_circle_coords([0.]);
+            }
+            circle.center() + center_to_point.normalize() * circle.radius()
+        }
+        SurfacePath::Line(line) => {
+            line.point_from_line_coords(line.point_to_line_coords(point))
+        }
+    }
+}
+
+/// Compute how far points on the first half-edge are from the path of the second
+///
+/// The paths are lines or circles. Lines are determined by 2 points, circles by
+/// 3, and the first and last point might be identical. If 4 points on the first
+/// half-edge are on the path of the second, the paths coincide.
+///
+/// Returns the points and their distances, or `None`, if the points can't be
+/// projected into the surface of the second half-edge.
+fn path_deviations(
+    edge_a: &Handle<HalfEdge>,
+    surface_a: &Handle<Surface>,
+    edge_b: &Handle<HalfEdge>,
+    surface_b: &Handle<Surface>,
+) -> Option<Vec<(Point<3>, Scalar)>> {
+    let [a, d] = edge_a.boundary().inner;
+    let b = a + (d - a) * 1. / 3.;
+    let c = a + (d - a) * 2. / 3.;
+
+    [a, b, c, d]
+        .into_iter()
+        .map(|point_curve| {
+            let point = surface_a.geometry().point_from_surface_coords(
+                edge_a.path().point_from_path_coords(point_curve),
+            );
+            let distance =
+                distance_to_path(point, edge_b.path(), &surface_b.geometry())?;
+
+            Some((point, distance))
+        })
+        .collect()
+}
+
+/// Compute the distance of a point from a path on a surface
+///
+/// Projects the point into the surface, finds the closest point on the path in
+/// surface coordinates, and measures the distance to that in 3D. For points
+/// that are on the path, or very close to it, this is the distance to the path.
+/// For other points, it might be larger than that.
+///
+/// Returns `None`, if the point can't be projected into the surface, because
+/// the surface is degenerate.
+fn distance_to_path(
+    point: Point<3>,
+    path: SurfacePath,
+    surface: &SurfaceGeometry,
+) -> Option<Scalar> {
+    let distance_to = |point_surface: Point<2>| {
+        let closest = closest_point_on_path(path, point_surface);
+        point.distance_to(&surface.point_from_surface_coords(closest))
+    };
+
+    match surface.u {
+        GlobalPath::Line(u) => {
+            if u.direction().cross(&surface.v).magnitude() == Scalar::ZERO {
+                return None;
+            }
+
+            Some(distance_to(surface.project_global_point(point)))
+        }
+        GlobalPath::Circle(circle) => {
+            // A point on the surface is the point on the circle, plus a
+            // multiple of `v`. Moving along `v` until we reach the plane of
+            // the circle gives us both.
+            let normal = circle.a().cross(&circle.b());
+            let v_along_normal = surface.v.dot(&normal);
+            if v_along_normal == Scalar::ZERO {
+                return None;
+            }
+            let v = (point - circle.center()).dot(&normal) / v_along_normal;
+
+            let on_circle = point - surface.v * v - circle.center();
+            let u = Scalar::atan2(
+                on_circle.dot(&circle.b()),
+                on_circle.dot(&circle.a()),
+            );
+
+            // The path might refer to the point by an angle from a different
+            // revolution of the circle.
+            [-Scalar::TAU, Scalar::ZERO, Scalar::TAU]
+                .into_iter()
+                .map(|offset| distance_to(Point::from([u + offset, v])))
+                .reduce(Scalar::min)
+        }
+    }
+}
+
+/// The fractions of a half-edge's boundary at which [`distances`] samples it
+///
+/// Three samples (start, middle, end), are enough to detect weather lines and
+/// circles match. If we were to add more complicated curves, this might need
+/// to change.
+const SAMPLES: [f64; 3] = [0., 0.5, 1.];
+
 /// Sample two edges at various (currently 3) points in 3D along them.
 ///
 /// Returns an [`Iterator`] of the distance at each sample.
@@ -365,46 +837,49 @@ fn distances(
     edge_b: Handle<HalfEdge>,
     surface_b: Handle<Surface>,
 ) -> impl Iterator<Item = Scalar> {
-    fn sample(
-        percent: f64,
-        (edge, surface): (&Handle<HalfEdge>, SurfaceGeometry),
-    ) -> Point<3> {
-        let [start, end] = edge.boundary().inner;
-        let path_coords = start + (end - start) * percent;
-        let surface_coords = edge.path().point_from_path_coords(path_coords);
-        surface.point_from_surface_coords(surface_coords)
-    }
+    SAMPLES
+        .map(|percent| {
+            let sample1 = sample(percent, &edge_a, &surface_a);
+            let sample2 = sample(1.0 - percent, &edge_b, &surface_b);
+            sample1.distance_to(&sample2)
+        })
+        .into_iter()
+}
 
-    // Three samples (start, middle, end), are enough to detect weather lines
-    // and circles match. If we were to add more complicated curves, this might
-    // need to change.
-    let sample_count = 3;
-    let step = 1.0 / (sample_count as f64 - 1.0);
-
-    let mut distances = Vec::new();
-    for i in 0..sample_count {
-        let percent = i as f64 * step;
-        let sample1 = sample(percent, (&edge_a, surface_a.geometry()));
-        let sample2 = sample(1.0 - percent, (&edge_b, surface_b.geometry()));
-        distances.push(sample1.distance_to(&sample2))
-    }
-    distances.into_iter()
+/// Sample the half-edge in 3D, at the provided fraction of its boundary
+fn sample(
+    percent: f64,
+    edge: &Handle<HalfEdge>,
+    surface: &Handle<Surface>,
+) -> Point<3> {
+    let [start, end] = edge.boundary().inner;
+    let path_coords = start + (end - start) * percent;
+    let surface_coords = edge.path().point_from_path_coords(path_coords);
+    surface.geometry().point_from_surface_coords(surface_coords)
 }
 
 #[cfg(test)]
 mod tests {
+    use fj_math::{Line, Scalar, Vector};
+
     use crate::{
         assert_contains_err,
-        objects::{Curve, Shell},
+        geometry::{GlobalPath, SurfacePath},
+        objects::{Curve, Region, Shell, Sketch},
         operations::{
-            build::BuildShell,
+            build::{BuildRegion, BuildShell, BuildSketch},
             insert::Insert,
+            reverse::Reverse,
+            sweep::SweepSketch,
             update::{
                 UpdateCycle, UpdateFace, UpdateHalfEdge, UpdateRegion,
-                UpdateShell,
+                UpdateShell, UpdateSketch,
             },
         },
-        validate::{shell::ShellValidationError, Validate, ValidationError},
+        validate::{
+            shell::ShellValidationError, Severity, Validate, ValidationCheck,
+            ValidationConfig, ValidationError,
+        },
         Instance,
     };
 
@@ -451,6 +926,164 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn curve_paths_not_coincident() -> anyhow::Result<()> {
+        // Moving a half-edge disconnects it from its neighbors, which is not
+        // what this test is about.
+        let mut core = Instance::with_validation_config(
+            ValidationConfig::default().with_severity(
+                ValidationCheck::CycleHalfEdgeConnections,
+                Severity::Disabled,
+            ),
+        );
+
+        let valid = Shell::tetrahedron(
+            [[0., 0., 0.], [0., 1., 0.], [1., 0., 0.], [0., 0., 1.]],
+            &mut core,
+        );
+
+        // Move a path away from its sibling, while keeping the curve.
+        let move_away = |path| match path {
+            SurfacePath::Line(line) => {
+                SurfacePath::Line(Line::from_origin_and_direction(
+                    line.origin() + Vector::from([0., -0.1]),
+                    line.direction(),
+                ))
+            }
+            SurfacePath::Circle(_) => {
+                unreachable!("Tetrahedron only has straight edges")
+            }
+        };
+        let invalid = valid.shell.update_face(&valid.abc.face, |face| {
+            [face
+                .update_region(|region| {
+                    region
+                        .update_exterior(|cycle| {
+                            cycle
+                                .update_half_edge(
+                                    cycle.half_edges().nth_circular(0),
+                                    |edge| {
+                                        [edge
+                                            .update_path(move_away)
+                                            .insert(&mut core.services)]
+                                    },
+                                )
+                                .insert(&mut core.services)
+                        })
+                        .insert(&mut core.services)
+                })
+                .insert(&mut core.services)]
+        });
+
+        valid.shell.validate_and_return_first_error()?;
+        assert_contains_err!(
+            invalid,
+            ValidationError::Shell(
+                ShellValidationError::CurvePathsNotCoincident { .. }
+            )
+        );
+
+        // The coordinate systems of the curve don't match either, but that's
+        // implied by the paths not coinciding. It must be reported only once.
+        let mut errors = Vec::new();
+        invalid.validate(&mut errors);
+        let curve_errors = errors
+            .iter()
+            .filter(|err| {
+                matches!(
+                    err.check(),
+                    ValidationCheck::ShellCurveCoordinates
+                        | ValidationCheck::ShellCurveGeometry
+                )
+            })
+            .count();
+        assert_eq!(curve_errors, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn curve_paths_not_coincident_on_curved_surface() -> anyhow::Result<()> {
+        // Moving a half-edge disconnects it from its neighbors, which is not
+        // what this test is about.
+        let mut core = Instance::with_validation_config(
+            ValidationConfig::default().with_severity(
+                ValidationCheck::CycleHalfEdgeConnections,
+                Severity::Disabled,
+            ),
+        );
+
+        let surface = core.services.objects.surfaces.xy_plane();
+        let cylinder = Sketch::empty()
+            .add_region(
+                Region::circle([0., 0.], 1., &mut core)
+                    .insert(&mut core.services),
+            )
+            .sweep_sketch(surface, [0., 0., 1.], &mut core);
+        let valid = cylinder.shells().only().clone_object();
+
+        // Both half-edges on the seam of the cylinder are on its curved
+        // surface. Move one of them around the cylinder, while keeping the
+        // curve.
+        let side = valid
+            .faces()
+            .iter()
+            .find(|face| {
+                matches!(face.surface().geometry().u, GlobalPath::Circle(_))
+            })
+            .expect("Cylinder must have a curved face")
+            .clone();
+        let seam = side
+            .region()
+            .exterior()
+            .half_edges()
+            .iter()
+            .find(|half_edge| {
+                let SurfacePath::Line(line) = half_edge.path() else {
+                    return false;
+                };
+                line.direction().u == Scalar::ZERO
+            })
+            .expect("Curved face must have a seam")
+            .clone();
+        let move_around = |path| match path {
+            SurfacePath::Line(line) => {
+                SurfacePath::Line(Line::from_origin_and_direction(
+                    line.origin() + Vector::from([0.5, 0.]),
+                    line.direction(),
+                ))
+            }
+            SurfacePath::Circle(_) => unreachable!("Seam is a line"),
+        };
+        let invalid = valid.update_face(&side, |face| {
+            [face
+                .update_region(|region| {
+                    region
+                        .update_exterior(|cycle| {
+                            cycle
+                                .update_half_edge(&seam, |edge| {
+                                    [edge
+                                        .update_path(move_around)
+                                        .insert(&mut core.services)]
+                                })
+                                .insert(&mut core.services)
+                        })
+                        .insert(&mut core.services)
+                })
+                .insert(&mut core.services)]
+        });
+
+        valid.validate_and_return_first_error()?;
+        assert_contains_err!(
+            invalid,
+            ValidationError::Shell(
+                ShellValidationError::CurvePathsNotCoincident { .. }
+            )
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn half_edge_has_no_sibling() -> anyhow::Result<()> {
         let mut core = Instance::new();
@@ -514,4 +1147,59 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn intersecting_faces() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let valid = Shell::tetrahedron(
+            [[0., 0., 0.], [0., 1., 0.], [1., 0., 0.], [0., 0., 1.]],
+            &mut core,
+        );
+        let invalid = {
+            let other = Shell::tetrahedron(
+                [
+                    [0.2, 0.2, -0.5],
+                    [0.2, 0.5, -0.5],
+                    [0.5, 0.2, -0.5],
+                    [0.2, 0.2, 0.5],
+                ],
+                &mut core,
+            );
+            valid.shell.add_faces(other.shell.faces().iter().cloned())
+        };
+
+        valid.shell.validate_and_return_first_error()?;
+        assert_contains_err!(
+            invalid,
+            ValidationError::Shell(
+                ShellValidationError::IntersectingFaces { .. }
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn inconsistent_orientation() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let valid = Shell::tetrahedron(
+            [[0., 0., 0.], [0., 1., 0.], [1., 0., 0.], [0., 0., 1.]],
+            &mut core,
+        );
+        let invalid = valid.shell.update_face(&valid.abc.face, |face| {
+            [face.reverse(&mut core).insert(&mut core.services)]
+        });
+
+        valid.shell.validate_and_return_first_error()?;
+        assert_contains_err!(
+            invalid,
+            ValidationError::Shell(
+                ShellValidationError::InconsistentOrientation { .. }
+            )
+        );
+
+        Ok(())
+    }
 }
@@ -2,7 +2,8 @@ use crate::{objects::Sketch, validate_references};
 
 use super::{
     references::{ReferenceCountError, ReferenceCounter},
-    Validate, ValidationConfig, ValidationError,
+    report::Details,
+    Validate, ValidationCheck, ValidationConfig, ValidationError,
 };
 
 impl Validate for Sketch {
@@ -11,7 +12,11 @@ impl Validate for Sketch {
         config: &ValidationConfig,
         errors: &mut Vec<ValidationError>,
     ) {
-        SketchValidationError::check_object_references(self, config, errors);
+        if config.is_enabled(ValidationCheck::SketchObjectReferences) {
+            SketchValidationError::check_object_references(
+                self, config, errors,
+            );
+        }
     }
 }
 
@@ -24,6 +29,26 @@ pub enum SketchValidationError {
 }
 
 impl SketchValidationError {
+    /// The validation check that produced this error
+    pub fn check(&self) -> ValidationCheck {
+        match self {
+            Self::MultipleReferences(_) => {
+                ValidationCheck::SketchObjectReferences
+            }
+        }
+    }
+
+    /// The details of this error, for use in a [`ValidationReport`]
+    ///
+    /// [`ValidationReport`]: super::ValidationReport
+    pub(super) fn details(&self) -> Details {
+        match self {
+            Self::MultipleReferences(err) => {
+                err.add_objects(Details::new("MultipleReferences"))
+            }
+        }
+    }
+
     fn check_object_references(
         sketch: &Sketch,
         _config: &ValidationConfig,
@@ -1,15 +1,26 @@
-use std::iter::repeat;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    iter::repeat,
+};
 
 use crate::{
-    objects::{Solid, Vertex},
+    algorithms::{
+        approx::{Approx, Tolerance},
+        bounding_volume::BoundingVolume,
+        triangulate::{Triangulate, TriangulationError},
+    },
+    objects::{Face, HalfEdge, Shell, Solid, Vertex},
+    queries::SiblingOfHalfEdge,
     storage::Handle,
     validate_references,
 };
-use fj_math::Point;
+use fj_math::{Aabb, Bvh, Point, Scalar, Triangle};
 
 use super::{
+    polyline::{GlobalPolyline, PierceableFace},
     references::{ReferenceCountError, ReferenceCounter},
-    Validate, ValidationConfig, ValidationError,
+    report::Details,
+    Validate, ValidationCheck, ValidationConfig, ValidationError,
 };
 
 impl Validate for Solid {
@@ -18,8 +29,20 @@ impl Validate for Solid {
         config: &ValidationConfig,
         errors: &mut Vec<ValidationError>,
     ) {
-        SolidValidationError::check_vertices(self, config, errors);
-        SolidValidationError::check_object_references(self, config, errors);
+        if config.is_enabled(ValidationCheck::SolidVertices) {
+            SolidValidationError::check_vertices(self, config, errors);
+        }
+        if config.is_enabled(ValidationCheck::SolidObjectReferences) {
+            SolidValidationError::check_object_references(self, config, errors);
+        }
+        if config.is_enabled(ValidationCheck::SolidShellIntersections) {
+            SolidValidationError::check_shell_intersections(
+                self, config, errors,
+            );
+        }
+        if config.is_enabled(ValidationCheck::SolidShellOrientation) {
+            SolidValidationError::check_shell_orientation(self, config, errors);
+        }
     }
 }
 
@@ -69,9 +92,136 @@ pub enum SolidValidationError {
     /// Object within solid referenced by more than one other object
     #[error("Object within solid referenced by more than one other Object")]
     MultipleReferences(#[from] ReferenceCountError),
+
+    /// [`Solid`] contains shells that intersect each other
+    ///
+    /// The shells of a solid must not overlap. A shell may be located within
+    /// another one, to bound a cavity.
+    #[error(
+        "`Solid` contains `Shell`s that intersect each other\n\
+        - Intersection: {point:?}\n\
+        - Piercing `HalfEdge`: {half_edge:#?}"
+    )]
+    IntersectingShells {
+        /// A point where the shells intersect
+        point: Point<3>,
+
+        /// The half-edge of the first shell that pierces the second shell
+        half_edge: Handle<HalfEdge>,
+
+        /// The intersecting faces, one from each shell
+        faces: [Handle<Face>; 2],
+
+        /// The intersecting shells
+        shells: [Handle<Shell>; 2],
+    },
+
+    /// [`Solid`] contains a shell that is oriented the wrong way
+    ///
+    /// The faces of a shell must point outward, unless the shell bounds a
+    /// cavity, meaning it is located within another shell of the solid. Then
+    /// its faces must point inward.
+    #[error(
+        "`Solid` contains `Shell` that is inside out\n\
+        - Bounds a cavity: {is_cavity}\n\
+        - Signed volume: {volume:?}\n\
+        - `Shell`: {shell:#?}"
+    )]
+    ShellIsInsideOut {
+        /// The shell that is oriented the wrong way
+        shell: Handle<Shell>,
+
+        /// Whether the shell bounds a cavity
+        is_cavity: bool,
+
+        /// The volume of the shell, which is negative if its faces point inward
+        volume: Scalar,
+    },
+
+    /// The orientation of a shell in a [`Solid`] can't be determined
+    ///
+    /// Determining the orientation requires the shell to be triangulated.
+    #[error(
+        "Orientation of `Shell` can't be determined, as it can't be \
+        triangulated\n\
+        - Error: {error}\n\
+        - `Shell`: {shell:#?}"
+    )]
+    ShellNotTriangulated {
+        /// The shell that can't be triangulated
+        shell: Handle<Shell>,
+
+        /// The error that occurred while triangulating the shell
+        error: TriangulationError,
+    },
 }
 
 impl SolidValidationError {
+    /// The validation check that produced this error
+    pub fn check(&self) -> ValidationCheck {
+        match self {
+            Self::DistinctVerticesCoincide { .. }
+            | Self::IdenticalVerticesNotCoincident { .. } => {
+                ValidationCheck::SolidVertices
+            }
+            Self::MultipleReferences(_) => {
+                ValidationCheck::SolidObjectReferences
+            }
+            Self::IntersectingShells { .. } => {
+                ValidationCheck::SolidShellIntersections
+            }
+            Self::ShellIsInsideOut { .. }
+            | Self::ShellNotTriangulated { .. } => {
+                ValidationCheck::SolidShellOrientation
+            }
+        }
+    }
+
+    /// The details of this error, for use in a [`ValidationReport`]
+    ///
+    /// [`ValidationReport`]: super::ValidationReport
+    pub(super) fn details(&self) -> Details {
+        match self {
+            Self::DistinctVerticesCoincide {
+                vertex_a,
+                vertex_b,
+                position_a,
+                position_b,
+            } => Details::new("DistinctVerticesCoincide")
+                .objects([vertex_a, vertex_b])
+                .position(*position_a)
+                .position(*position_b),
+            Self::IdenticalVerticesNotCoincident {
+                vertex_a,
+                vertex_b,
+                position_a,
+                position_b,
+            } => Details::new("IdenticalVerticesNotCoincident")
+                .objects([vertex_a, vertex_b])
+                .position(*position_a)
+                .position(*position_b),
+            Self::MultipleReferences(err) => {
+                err.add_objects(Details::new("MultipleReferences"))
+            }
+            Self::IntersectingShells {
+                point,
+                half_edge,
+                faces,
+                shells,
+            } => Details::new("IntersectingShells")
+                .object(half_edge)
+                .objects(faces)
+                .objects(shells)
+                .position(*point),
+            Self::ShellIsInsideOut { shell, .. } => {
+                Details::new("ShellIsInsideOut").object(shell)
+            }
+            Self::ShellNotTriangulated { shell, .. } => {
+                Details::new("ShellNotTriangulated").object(shell)
+            }
+        }
+    }
+
     fn check_vertices(
         solid: &Solid,
         config: &ValidationConfig,
@@ -95,44 +245,74 @@ impl SolidValidationError {
             })
             .collect();
 
-        // This is O(N^2) which isn't great, but we can't use a HashMap since we
-        // need to deal with float inaccuracies. Maybe we could use some smarter
-        // data-structure like an octree.
-        for (position_a, vertex_a) in &vertices {
-            for (position_b, vertex_b) in &vertices {
-                let vertices_are_identical = vertex_a.id() == vertex_b.id();
-                let vertices_are_not_identical = !vertices_are_identical;
-
-                let too_far_to_be_identical = position_a
-                    .distance_to(position_b)
-                    > config.identical_max_distance;
-                let too_close_to_be_distinct = position_a
-                    .distance_to(position_b)
-                    < config.distinct_min_distance;
-
-                if vertices_are_identical && too_far_to_be_identical {
-                    errors.push(
-                        Self::IdenticalVerticesNotCoincident {
-                            vertex_a: vertex_a.clone(),
-                            vertex_b: vertex_b.clone(),
-                            position_a: *position_a,
-                            position_b: *position_b,
-                        }
-                        .into(),
-                    )
-                }
+        // Comparing all vertices with each other would be O(N^2). Only two
+        // kinds of pairs can result in an error though: Those that are close to
+        // each other, which we find using a spatial index, and those that refer
+        // to the same vertex.
+        //
+        // The pairs are compared in the same order as all pairs would be, which
+        // makes the order of the errors independent of this optimization.
+        let mut pairs = BTreeSet::new();
+
+        let point_aabb = |&position: &Point<3>| Aabb {
+            min: position,
+            max: position,
+        };
+        let bvh =
+            Bvh::new(vertices.iter().map(|(position, _)| point_aabb(position)));
+        for (a, (position, _)) in vertices.iter().enumerate() {
+            let aabb =
+                point_aabb(position).loosened(config.distinct_min_distance);
+            pairs.extend(bvh.intersecting(&aabb).into_iter().map(|b| (a, b)));
+        }
 
-                if vertices_are_not_identical && too_close_to_be_distinct {
-                    errors.push(
-                        Self::DistinctVerticesCoincide {
-                            vertex_a: vertex_a.clone(),
-                            vertex_b: vertex_b.clone(),
-                            position_a: *position_a,
-                            position_b: *position_b,
-                        }
-                        .into(),
-                    )
-                }
+        let mut occurrences = BTreeMap::new();
+        for (i, (_, vertex)) in vertices.iter().enumerate() {
+            occurrences
+                .entry(vertex.id())
+                .or_insert_with(Vec::new)
+                .push(i);
+        }
+        for occurrences in occurrences.values() {
+            for &a in occurrences {
+                pairs.extend(occurrences.iter().map(|&b| (a, b)));
+            }
+        }
+
+        for (a, b) in pairs {
+            let (position_a, vertex_a) = &vertices[a];
+            let (position_b, vertex_b) = &vertices[b];
+
+            let vertices_are_identical = vertex_a.id() == vertex_b.id();
+            let vertices_are_not_identical = !vertices_are_identical;
+
+            let too_far_to_be_identical = position_a.distance_to(position_b)
+                > config.identical_max_distance;
+            let too_close_to_be_distinct = position_a.distance_to(position_b)
+                < config.distinct_min_distance;
+
+            if vertices_are_identical && too_far_to_be_identical {
+                errors.push(
+                    Self::IdenticalVerticesNotCoincident {
+                        vertex_a: vertex_a.clone(),
+                        vertex_b: vertex_b.clone(),
+                        position_a: *position_a,
+                        position_b: *position_b,
+                    }
+                    .into(),
+                )
+            }
+
+            if vertices_are_not_identical && too_close_to_be_distinct {
+                errors.push(
+                    Self::DistinctVerticesCoincide {
+                        vertex_a: vertex_a.clone(),
+                        vertex_b: vertex_b.clone(),
+                        position_a: *position_a,
+                        position_b: *position_b,
+                    }
+                    .into(),
+                )
             }
         }
     }
@@ -169,6 +349,247 @@ impl SolidValidationError {
             referenced_cycles, Cycle;
         );
     }
+
+    /// Check that shells don't pierce each other
+    ///
+    /// Curved faces are approximated, which can cause intersections close to
+    /// their boundary to be missed. See [`CurvedFace`].
+    ///
+    /// [`CurvedFace`]: super::polyline::CurvedFace
+    fn check_shell_intersections(
+        solid: &Solid,
+        config: &ValidationConfig,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let shells = solid
+            .shells()
+            .iter()
+            .map(|shell| {
+                let faces = shell
+                    .faces()
+                    .iter()
+                    .map(|face| {
+                        (
+                            face,
+                            PierceableFace::new(face),
+                            GlobalPolyline::from_face(face),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                (shell, faces)
+            })
+            .collect::<Vec<_>>();
+
+        // Only faces whose AABBs intersect can pierce each other. We use a
+        // spatial index over the faces of all shells to find those.
+        let faces = shells
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (_, faces))| faces.iter().map(move |face| (i, face)))
+            .collect::<Vec<_>>();
+        let bvh = Bvh::new(faces.iter().map(|(_, (_, _, half_edges))| {
+            GlobalPolyline::aabb_of(half_edges)
+        }));
+
+        for (shell_a, faces_a) in &shells {
+            // For each face of this shell, the faces that it might pierce
+            let candidates = faces_a
+                .iter()
+                .map(|(face_a, _, half_edges)| {
+                    let aabb = GlobalPolyline::aabb_of(half_edges)
+                        .loosened(config.distinct_min_distance);
+                    let faces_b = bvh
+                        .intersecting(&aabb)
+                        .into_iter()
+                        .map(|i| faces[i])
+                        .collect::<Vec<_>>();
+
+                    (face_a, half_edges, faces_b)
+                })
+                .collect::<Vec<_>>();
+
+            for (b, (shell_b, _)) in shells.iter().enumerate() {
+                if shell_a.id() == shell_b.id() {
+                    continue;
+                }
+
+                let intersection =
+                    candidates.iter().find_map(|(face_a, a, faces_b)| {
+                        faces_b.iter().filter(|&&(i, _)| i == b).find_map(
+                            |(_, (face_b, pierceable_face, _))| {
+                                let pierceable_face =
+                                    pierceable_face.as_ref()?;
+
+                                a.iter().find_map(|half_edge| {
+                                    let point = pierceable_face
+                                        .pierced_by(half_edge, config)?;
+                                    Some((
+                                        point,
+                                        half_edge.half_edge,
+                                        *face_a,
+                                        face_b,
+                                    ))
+                                })
+                            },
+                        )
+                    });
+
+                if let Some((point, half_edge, face_a, face_b)) = intersection {
+                    errors.push(
+                        Self::IntersectingShells {
+                            point,
+                            half_edge: half_edge.clone(),
+                            faces: [(*face_a).clone(), (*face_b).clone()],
+                            shells: [(*shell_a).clone(), (*shell_b).clone()],
+                        }
+                        .into(),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Check that shells point outward, or inward if they bound a cavity
+    ///
+    /// Shells that aren't closed are skipped. There's a [`Shell`] validation
+    /// check that takes care of those. Shells that can't be triangulated are
+    /// reported, as their orientation can't be checked.
+    fn check_shell_orientation(
+        solid: &Solid,
+        _: &ValidationConfig,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let mut shells = Vec::new();
+        for shell in solid.shells() {
+            match closed_shell_triangles(shell) {
+                Ok(Some(triangles)) => shells.push((shell, triangles)),
+                Ok(None) => {}
+                Err(error) => errors.push(
+                    Self::ShellNotTriangulated {
+                        shell: shell.clone(),
+                        error,
+                    }
+                    .into(),
+                ),
+            }
+        }
+
+        for (shell, triangles) in &shells {
+            let Some(point) = triangles.first().map(|triangle| {
+                let [a, b, c] = triangle.points();
+                a + ((b - a) + (c - a)) / 3.
+            }) else {
+                continue;
+            };
+
+            // The shells don't intersect, as that is validated separately.
+            // Whether a shell is inside of another, can be determined by
+            // checking any of its points.
+            let num_enclosing_shells = shells
+                .iter()
+                .filter(|(other, other_triangles)| {
+                    other.id() != shell.id()
+                        && winding_number(point, other_triangles).abs()
+                            > Scalar::from_f64(0.5)
+                })
+                .count();
+            let is_cavity = num_enclosing_shells % 2 == 1;
+
+            let volume = triangles
+                .iter()
+                .map(|triangle| {
+                    let [a, b, c] = triangle.points().map(|point| point.coords);
+                    a.dot(&b.cross(&c)) / 6.
+                })
+                .fold(Scalar::ZERO, |sum, volume| sum + volume);
+
+            let is_inside_out = if is_cavity {
+                volume > Scalar::ZERO
+            } else {
+                volume < Scalar::ZERO
+            };
+
+            if is_inside_out {
+                errors.push(
+                    Self::ShellIsInsideOut {
+                        shell: (*shell).clone(),
+                        is_cavity,
+                        volume,
+                    }
+                    .into(),
+                );
+            }
+        }
+    }
+}
+
+/// Triangulate a shell, if it is closed
+///
+/// Returns `None`, if the shell isn't closed or has no extent.
+fn closed_shell_triangles(
+    shell: &Handle<Shell>,
+) -> Result<Option<Vec<Triangle<3>>>, TriangulationError> {
+    let is_closed = shell.faces().iter().all(|face| {
+        face.region().all_cycles().all(|cycle| {
+            cycle
+                .half_edges()
+                .iter()
+                .all(|half_edge| shell.get_sibling_of(half_edge).is_some())
+        })
+    });
+    if !is_closed {
+        return Ok(None);
+    }
+
+    // Only the orientation of the triangles matters here, so a coarse
+    // approximation is good enough.
+    let Some(size) = shell
+        .aabb()
+        .and_then(|aabb| aabb.size().components.into_iter().max())
+    else {
+        return Ok(None);
+    };
+    let Ok(tolerance) = Tolerance::from_scalar(size * Scalar::from_f64(1e-2))
+    else {
+        return Ok(None);
+    };
+
+    // The faces are triangulated separately. Where neighboring faces meet,
+    // their approximations don't need to match for the orientation to be
+    // right. Triangulating the whole shell would require that, and fail on
+    // shells whose vertices are slightly off, like imported ones.
+    let mut triangles = Vec::new();
+    for face in shell.faces() {
+        let mesh = face.approx(tolerance)?.triangulate()?;
+        triangles.extend(mesh.triangles().map(|triangle| triangle.inner));
+    }
+
+    Ok(Some(triangles))
+}
+
+/// Compute the winding number of a closed triangle mesh around a point
+///
+/// The result is close to `1` (or `-1`, depending on the orientation of the
+/// mesh) for points inside of the mesh, and close to `0` for points outside.
+fn winding_number(point: Point<3>, triangles: &[Triangle<3>]) -> Scalar {
+    // Sum the solid angles of the triangles, as seen from the point. See Van
+    // Oosterom and Strackee, The Solid Angle of a Plane Triangle.
+    let solid_angle = triangles
+        .iter()
+        .map(|triangle| {
+            let [a, b, c] = triangle.points().map(|vertex| vertex - point);
+            let [la, lb, lc] = [a, b, c].map(|vector| vector.magnitude());
+
+            let numerator = a.dot(&b.cross(&c));
+            let denominator =
+                la * lb * lc + a.dot(&b) * lc + a.dot(&c) * lb + b.dot(&c) * la;
+
+            numerator.atan2(denominator) * 2.
+        })
+        .fold(Scalar::ZERO, |sum, angle| sum + angle);
+
+    solid_angle / (Scalar::PI * 4.)
 }
 
 #[cfg(test)]
@@ -176,10 +597,19 @@ mod tests {
     use crate::{
         assert_contains_err,
         geometry::{GlobalPath, SurfaceGeometry},
-        objects::{Cycle, Face, HalfEdge, Region, Shell, Solid, Surface},
+        objects::{
+            Cycle, Face, HalfEdge, Region, Shell, Sketch, Solid, Surface,
+        },
         operations::{
-            build::{BuildFace, BuildHalfEdge},
+            build::{
+                BuildFace, BuildHalfEdge, BuildRegion, BuildShell, BuildSketch,
+            },
             insert::Insert,
+            merge::Merge,
+            reverse::Reverse,
+            sweep::SweepSketch,
+            transform::TransformObject,
+            update::UpdateSketch,
         },
         validate::{
             references::ReferenceCountError, SolidValidationError, Validate,
@@ -379,4 +809,167 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn intersecting_shells() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let [a, b, c] = [
+            [[0., 0., 0.], [0., 1., 0.], [1., 0., 0.], [0., 0., 1.]],
+            [[2., 0., 0.], [2., 1., 0.], [3., 0., 0.], [2., 0., 1.]],
+            [
+                [0.2, 0.2, -0.5],
+                [0.2, 0.5, -0.5],
+                [0.5, 0.2, -0.5],
+                [0.2, 0.2, 0.5],
+            ],
+        ]
+        .map(|points| {
+            Shell::tetrahedron(points, &mut core)
+                .shell
+                .insert(&mut core.services)
+        });
+
+        let valid = Solid::new([a.clone(), b]);
+        let invalid = Solid::new([a, c]);
+
+        valid.validate_and_return_first_error()?;
+        assert_contains_err!(
+            invalid,
+            ValidationError::Solid(
+                SolidValidationError::IntersectingShells { .. }
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn merged_solids_intersect() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        // The `all` model merges solids, which can overlap. Here, a bar runs
+        // straight through a cylinder. None of the cylinder's edges touch the
+        // bar, so only the bar's edges piercing the cylinder's curved face
+        // reveal the overlap.
+        let cylinder =
+            sweep(Region::circle([0., 0.], 1., &mut core), 2., &mut core);
+        let bar = sweep(
+            Region::polygon(
+                [[-0.1, -2.], [0.1, -2.], [0.1, 2.], [-0.1, 2.]],
+                &mut core,
+            ),
+            0.2,
+            &mut core,
+        )
+        .translate([0., 0., 0.9], &mut core);
+        let beside = bar.translate([3., 0., 0.], &mut core);
+
+        let valid = cylinder.merge(&beside);
+        let invalid = cylinder.merge(&bar);
+
+        valid.validate_and_return_first_error()?;
+        assert_contains_err!(
+            invalid,
+            ValidationError::Solid(
+                SolidValidationError::IntersectingShells { .. }
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn shell_not_triangulated() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        // A closed shell, made up of two discs that share their edge. One of
+        // them is on a curved surface, which circles can't be approximated on.
+        let disc = Face::new(
+            core.services.objects.surfaces.xy_plane(),
+            Region::circle([0., 0.], 1., &mut core).insert(&mut core.services),
+        )
+        .insert(&mut core.services);
+        let curved_surface = Surface::new(SurfaceGeometry {
+            u: GlobalPath::circle_from_radius(1.),
+            v: [0., 0., 1.].into(),
+        })
+        .insert(&mut core.services);
+        let curved_disc = Face::new(
+            curved_surface,
+            disc.region().reverse(&mut core).insert(&mut core.services),
+        )
+        .insert(&mut core.services);
+
+        let shell = Shell::new([disc, curved_disc]).insert(&mut core.services);
+        let solid = Solid::new([shell]);
+
+        assert_contains_err!(
+            solid,
+            ValidationError::Solid(
+                SolidValidationError::ShellNotTriangulated { .. }
+            )
+        );
+
+        core.services.validation.errors.clear();
+
+        Ok(())
+    }
+
+    fn sweep(region: Region, height: f64, core: &mut Instance) -> Solid {
+        let surface = core.services.objects.surfaces.xy_plane();
+
+        Sketch::empty()
+            .add_region(region.insert(&mut core.services))
+            .sweep_sketch(surface, [0., 0., height], core)
+    }
+
+    #[test]
+    fn shell_is_inside_out() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let [outer, inner] = [
+            [[0., 0., 0.], [0., 4., 0.], [4., 0., 0.], [0., 0., 4.]],
+            [
+                [0.5, 0.5, 0.5],
+                [0.5, 1., 0.5],
+                [1., 0.5, 0.5],
+                [0.5, 0.5, 1.],
+            ],
+        ]
+        .map(|points| {
+            Shell::tetrahedron(points, &mut core)
+                .shell
+                .insert(&mut core.services)
+        });
+        let [outer_reversed, inner_reversed] =
+            [&outer, &inner].map(|shell| {
+                Shell::new(shell.faces().iter().map(|face| {
+                    face.reverse(&mut core).insert(&mut core.services)
+                }))
+                .insert(&mut core.services)
+            });
+
+        let valid = Solid::new([outer.clone(), inner_reversed.clone()]);
+        let invalid_exterior = Solid::new([outer_reversed, inner_reversed]);
+        let invalid_cavity = Solid::new([outer, inner]);
+
+        valid.validate_and_return_first_error()?;
+        assert_contains_err!(
+            invalid_exterior,
+            ValidationError::Solid(SolidValidationError::ShellIsInsideOut {
+                is_cavity: false,
+                ..
+            })
+        );
+        assert_contains_err!(
+            invalid_cavity,
+            ValidationError::Solid(SolidValidationError::ShellIsInsideOut {
+                is_cavity: true,
+                ..
+            })
+        );
+
+        Ok(())
+    }
 }
@@ -1,12 +1,166 @@
-use crate::objects::Surface;
+use fj_math::{Circle, Line, Scalar, Vector};
 
-use super::{Validate, ValidationConfig, ValidationError};
+use crate::{geometry::GlobalPath, objects::Surface};
+
+use super::{
+    report::Details, Validate, ValidationCheck, ValidationConfig,
+    ValidationError,
+};
 
 impl Validate for Surface {
     fn validate_with_config(
         &self,
-        _: &ValidationConfig,
-        _: &mut Vec<ValidationError>,
+        config: &ValidationConfig,
+        errors: &mut Vec<ValidationError>,
     ) {
+        if config.is_enabled(ValidationCheck::SurfaceGeometry) {
+            SurfaceValidationError::check_geometry(self, config, errors);
+        }
+    }
+}
+
+/// [`Surface`] validation error
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum SurfaceValidationError {
+    /// The `v` vector of [`Surface`] has a length of zero
+    #[error(
+        "The `v` vector of `Surface` has a length of zero\n\
+        - `v`: {v:?}"
+    )]
+    ZeroLengthV {
+        /// The `v` vector of the surface
+        v: Vector<3>,
+    },
+
+    /// The `v` vector of [`Surface`] is parallel to its `u` axis
+    #[error(
+        "The `v` vector of `Surface` is parallel to its `u` axis\n\
+        - `u`: {u:?}\n\
+        - `v`: {v:?}\n\
+        - Distance of `v` from `u`: {distance}"
+    )]
+    ParallelUAndV {
+        /// The line that defines the `u` axis of the surface
+        u: Line<3>,
+
+        /// The `v` vector of the surface
+        v: Vector<3>,
+
+        /// The length of the component of `v` that is orthogonal to `u`
+        distance: Scalar,
+    },
+
+    /// The circle that defines the `u` axis of [`Surface`] has a zero radius
+    #[error(
+        "The circle that defines the `u` axis of `Surface` has a zero radius\n\
+        - `u`: {u:?}"
+    )]
+    ZeroRadiusU {
+        /// The circle that defines the `u` axis of the surface
+        u: Circle<3>,
+    },
+}
+
+impl SurfaceValidationError {
+    /// The validation check that produced this error
+    pub fn check(&self) -> ValidationCheck {
+        match self {
+            Self::ZeroLengthV { .. }
+            | Self::ParallelUAndV { .. }
+            | Self::ZeroRadiusU { .. } => ValidationCheck::SurfaceGeometry,
+        }
+    }
+
+    /// The details of this error, for use in a [`ValidationReport`]
+    ///
+    /// [`ValidationReport`]: super::ValidationReport
+    pub(super) fn details(&self) -> Details {
+        match self {
+            Self::ZeroLengthV { .. } => Details::new("ZeroLengthV"),
+            Self::ParallelUAndV { .. } => Details::new("ParallelUAndV"),
+            Self::ZeroRadiusU { .. } => Details::new("ZeroRadiusU"),
+        }
+    }
+
+    fn check_geometry(
+        surface: &Surface,
+        config: &ValidationConfig,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let geometry = surface.geometry();
+        let v = geometry.v;
+
+        if v.magnitude() < config.distinct_min_distance {
+            errors.push(Self::ZeroLengthV { v }.into());
+        } else if let GlobalPath::Line(u) = geometry.u {
+            // The direction of `u` can't be zero, or the line couldn't have
+            // been constructed. This makes dividing by its length safe.
+            let distance =
+                u.direction().cross(&v).magnitude() / u.direction().magnitude();
+
+            if distance < config.distinct_min_distance {
+                errors.push(Self::ParallelUAndV { u, v, distance }.into());
+            }
+        }
+
+        if let GlobalPath::Circle(u) = geometry.u {
+            if u.radius() < config.distinct_min_distance {
+                errors.push(Self::ZeroRadiusU { u }.into());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_math::Vector;
+
+    use crate::{
+        assert_contains_err,
+        geometry::{GlobalPath, SurfaceGeometry},
+        objects::Surface,
+        validate::{SurfaceValidationError, Validate, ValidationError},
+    };
+
+    #[test]
+    fn degenerate_geometry() -> anyhow::Result<()> {
+        let valid = Surface::new(SurfaceGeometry {
+            u: GlobalPath::x_axis(),
+            v: Vector::unit_y(),
+        });
+        let zero_length_v = Surface::new(SurfaceGeometry {
+            u: GlobalPath::x_axis(),
+            v: Vector::from([0., 0., 0.]),
+        });
+        let parallel_u_and_v = Surface::new(SurfaceGeometry {
+            u: GlobalPath::x_axis(),
+            v: Vector::from([-2., 0., 0.]),
+        });
+        let zero_radius_u = Surface::new(SurfaceGeometry {
+            u: GlobalPath::circle_from_radius(1e-9),
+            v: Vector::unit_z(),
+        });
+
+        valid.validate_and_return_first_error()?;
+        assert_contains_err!(
+            zero_length_v,
+            ValidationError::Surface(
+                SurfaceValidationError::ZeroLengthV { .. }
+            )
+        );
+        assert_contains_err!(
+            parallel_u_and_v,
+            ValidationError::Surface(
+                SurfaceValidationError::ParallelUAndV { .. }
+            )
+        );
+        assert_contains_err!(
+            zero_radius_u,
+            ValidationError::Surface(
+                SurfaceValidationError::ZeroRadiusU { .. }
+            )
+        );
+
+        Ok(())
     }
 }
@@ -0,0 +1,302 @@
+//! DXF export
+//!
+//! See [`export_dxf`].
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use fj_math::Scalar;
+
+use crate::{
+    profile::{angle, Profile, Segment},
+    Error, ExportOptions, Unit,
+};
+
+/// Export the provided profile to a DXF file at the given path
+///
+/// This function will create a file if it does not exist, and will truncate it
+/// if it does.
+///
+/// Straight edges are written as `LINE` entities. Circular edges are written as
+/// `ARC` entities, or as `CIRCLE` entities, if they form a full circle. Only
+/// entities that are part of the original DXF format (R12) are used, so any
+/// application that reads DXF files should be able to read them.
+///
+/// Coordinates are interpreted as millimeters, which is declared in the header.
+pub fn export_dxf<'r>(
+    profile: impl Into<Profile<'r>>,
+    path: &Path,
+) -> Result<(), Error> {
+    export_dxf_with_options(profile, path, &ExportOptions::default())
+}
+
+/// Export the provided profile to a DXF file at the given path, using the
+/// options
+///
+/// This works like [`export_dxf`], except that the options control the unit
+/// that is declared in the header, and the scale. DXF has no place for the
+/// name and author of the drawing, so those are written as comments.
+pub fn export_dxf_with_options<'r>(
+    profile: impl Into<Profile<'r>>,
+    path: &Path,
+    options: &ExportOptions,
+) -> Result<(), Error> {
+    let file = File::create(path)?;
+    export_dxf_to_writer(profile, options, file)
+}
+
+/// Export the provided profile to the given writer, as DXF
+///
+/// See [`export_dxf_with_options`].
+pub fn export_dxf_to_writer<'r>(
+    profile: impl Into<Profile<'r>>,
+    options: &ExportOptions,
+    writer: impl Write,
+) -> Result<(), Error> {
+    let regions = profile.into().regions(options.factor()?)?;
+
+    let mut file = BufWriter::new(writer);
+
+    let metadata = [("Name", &options.name), ("Author", &options.author)];
+    for (key, value) in metadata {
+        if let Some(value) = value {
+            // Comments end at the end of the line.
+            let value = value.replace(['\r', '\n'], " ");
+            group(&mut file, 999, format!("{key}: {value}"))?;
+        }
+    }
+
+    group(&mut file, 0, "SECTION")?;
+    group(&mut file, 2, "HEADER")?;
+    group(&mut file, 9, "$INSUNITS")?;
+    group(&mut file, 70, units(options.unit))?;
+    group(&mut file, 0, "ENDSEC")?;
+
+    group(&mut file, 0, "SECTION")?;
+    group(&mut file, 2, "ENTITIES")?;
+
+    for segment in regions.iter().flatten().flatten() {
+        match *segment {
+            Segment::Line { start, end } => {
+                group(&mut file, 0, "LINE")?;
+                group(&mut file, 8, LAYER)?;
+                group(&mut file, 10, start.u)?;
+                group(&mut file, 20, start.v)?;
+                group(&mut file, 30, 0.)?;
+                group(&mut file, 11, end.u)?;
+                group(&mut file, 21, end.v)?;
+                group(&mut file, 31, 0.)?;
+            }
+            Segment::Arc {
+                center,
+                radius,
+                start,
+                end,
+                sweep,
+            } => {
+                let kind = if segment.is_full_circle() {
+                    "CIRCLE"
+                } else {
+                    "ARC"
+                };
+
+                group(&mut file, 0, kind)?;
+                group(&mut file, 8, LAYER)?;
+                group(&mut file, 10, center.u)?;
+                group(&mut file, 20, center.v)?;
+                group(&mut file, 30, 0.)?;
+                group(&mut file, 40, radius)?;
+
+                if !segment.is_full_circle() {
+                    // Arcs always run counter-clockwise from their start angle
+                    // to their end angle.
+                    let [from, to] = if sweep > Scalar::ZERO {
+                        [start, end]
+                    } else {
+                        [end, start]
+                    };
+
+                    let [from, to] = [from, to].map(|point| {
+                        angle(point - center).to_degrees().rem_euclid(360.)
+                    });
+
+                    group(&mut file, 50, from)?;
+                    group(&mut file, 51, to)?;
+                }
+            }
+        }
+    }
+
+    group(&mut file, 0, "ENDSEC")?;
+    group(&mut file, 0, "EOF")?;
+
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Write a group, the basic building block of a DXF file
+///
+/// A group consists of a code that defines the meaning of the value, followed
+/// by the value itself.
+fn group(
+    writer: &mut impl Write,
+    code: u16,
+    value: impl std::fmt::Display,
+) -> io::Result<()> {
+    writeln!(writer, "{code:>3}")?;
+    writeln!(writer, "{value}")
+}
+
+const LAYER: &str = "0";
+
+/// The value of `$INSUNITS` that specifies the unit
+fn units(unit: Unit) -> u8 {
+    match unit {
+        Unit::Micron => 13,
+        Unit::Millimeter => 4,
+        Unit::Centimeter => 5,
+        Unit::Meter => 6,
+        Unit::Inch => 1,
+        Unit::Foot => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use fj_core::{
+        objects::{Cycle, Face, HalfEdge, Region},
+        operations::{
+            build::{BuildCycle, BuildHalfEdge},
+            insert::Insert,
+            reverse::Reverse,
+        },
+        Instance,
+    };
+
+    use crate::{ExportOptions, Unit};
+
+    use super::export_dxf_to_writer;
+
+    #[test]
+    fn arcs() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let face = quarter_disc_with_hole(&mut core);
+
+        let mut dxf = Vec::new();
+        export_dxf_to_writer(&face, &ExportOptions::default(), &mut dxf)?;
+        let dxf = String::from_utf8(dxf)?;
+
+        let entities = entities(&dxf);
+        let kinds = entities
+            .iter()
+            .map(|(kind, _)| kind.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, ["LINE", "ARC", "LINE", "CIRCLE"]);
+
+        // Arcs run counter-clockwise from the start to the end angle.
+        let (_, arc) = &entities[1];
+        assert_eq!(
+            arc,
+            &[(10, 0.), (20, 0.), (30, 0.), (40, 1.), (50, 0.), (51, 90.)]
+        );
+
+        // The hole runs clockwise, but circles don't have a direction.
+        let (_, circle) = &entities[3];
+        assert_eq!(circle, &[(10, 0.4), (20, 0.4), (30, 0.), (40, 0.2)]);
+
+        core.services.drop_and_validate()?;
+        Ok(())
+    }
+
+    #[test]
+    fn options() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let face = quarter_disc_with_hole(&mut core);
+
+        let options = ExportOptions {
+            unit: Unit::Centimeter,
+            scale: 2.,
+            name: Some("quarter disc".into()),
+            author: Some("Fornjot\ncontributors".into()),
+            ..ExportOptions::default()
+        };
+        let mut dxf = Vec::new();
+        export_dxf_to_writer(&face, &options, &mut dxf)?;
+        let dxf = String::from_utf8(dxf)?;
+
+        let lines = dxf.lines().map(str::trim).collect::<Vec<_>>();
+        assert_eq!(
+            lines[..4],
+            [
+                "999",
+                "Name: quarter disc",
+                "999",
+                "Author: Fornjot contributors"
+            ]
+        );
+        let units = lines
+            .iter()
+            .position(|&line| line == "$INSUNITS")
+            .map(|i| lines[i + 2]);
+        assert_eq!(units, Some("5"));
+
+        // Scaled by 2, and converted to centimeters.
+        let (_, circle) = &entities(&dxf)[3];
+        assert_eq!(circle, &[(10, 0.08), (20, 0.08), (30, 0.), (40, 0.04)]);
+
+        core.services.drop_and_validate()?;
+        Ok(())
+    }
+
+    fn quarter_disc_with_hole(core: &mut Instance) -> Face {
+        let half_edges = [
+            HalfEdge::line_segment([[0., 0.], [1., 0.]], None, core),
+            HalfEdge::arc([1., 0.], [0., 1.], FRAC_PI_2, core),
+            HalfEdge::line_segment([[0., 1.], [0., 0.]], None, core),
+        ]
+        .map(|half_edge| half_edge.insert(&mut core.services));
+        let exterior = Cycle::new(half_edges).insert(&mut core.services);
+        let hole = Cycle::circle([0.4, 0.4], 0.2, core)
+            .reverse(core)
+            .insert(&mut core.services);
+        let region =
+            Region::new(exterior, [hole], None).insert(&mut core.services);
+
+        Face::new(core.services.objects.surfaces.xy_plane(), region)
+    }
+
+    /// Read the entities, with their numeric groups rounded
+    ///
+    /// The layer is left out, as it's the same for all entities.
+    fn entities(dxf: &str) -> Vec<(String, Vec<(u16, f64)>)> {
+        let mut lines = dxf.lines();
+        let mut entities = Vec::new();
+
+        while let (Some(code), Some(value)) = (lines.next(), lines.next()) {
+            let code: u16 = code.trim().parse().unwrap();
+
+            match (code, value) {
+                (0, "LINE" | "ARC" | "CIRCLE") => {
+                    entities.push((value.to_string(), Vec::new()));
+                }
+                (0 | 8, _) => {}
+                _ => {
+                    if let Some((_, groups)) = entities.last_mut() {
+                        let value: f64 = value.parse().unwrap();
+                        groups.push((code, (value * 1e9).round() / 1e9 + 0.));
+                    }
+                }
+            }
+        }
+
+        entities
+    }
+}
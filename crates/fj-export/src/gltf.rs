@@ -0,0 +1,558 @@
+//! glTF export
+//!
+//! Writes a mesh as a glTF 2.0 asset, either as JSON with an embedded buffer
+//! (`.gltf`), or in the binary container format (`.glb`). Triangles are
+//! grouped into one primitive per color, each with its own material.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write as _,
+    io::Write,
+};
+
+use fj_interop::{Color, Mesh};
+use fj_math::{Point, Scalar};
+
+use crate::{normals::vertex_normals, Error, ExportOptions};
+
+pub fn write_gltf(
+    mesh: &Mesh<Point<3>>,
+    options: &ExportOptions,
+    writer: &mut impl Write,
+) -> Result<(), Error> {
+    // The buffer is embedded into the JSON document as a data URI, so the
+    // resulting file is self-contained.
+    let (json, _) = document(mesh, options, |buffer| {
+        format!(
+            r#""uri":"data:application/octet-stream;base64,{}""#,
+            base64(buffer)
+        )
+    });
+
+    writer.write_all(json.as_bytes())?;
+
+    Ok(())
+}
+
+pub fn write_glb(
+    mesh: &Mesh<Point<3>>,
+    options: &ExportOptions,
+    writer: &mut impl Write,
+) -> Result<(), Error> {
+    let (json, mut buffer) = document(mesh, options, |_| String::new());
+
+    // Both chunks must be aligned to 4 bytes. The JSON chunk is padded with
+    // spaces, the binary chunk with zeros.
+    let mut json = json.into_bytes();
+    while json.len() % 4 != 0 {
+        json.push(b' ');
+    }
+    while buffer.len() % 4 != 0 {
+        buffer.push(0);
+    }
+
+    let mut length = 12 + 8 + json.len();
+    if !buffer.is_empty() {
+        length += 8 + buffer.len();
+    }
+    let length = u32::try_from(length).map_err(|_| Error::GlbTooLarge)?;
+
+    writer.write_all(b"glTF")?;
+    writer.write_all(&2u32.to_le_bytes())?;
+    writer.write_all(&length.to_le_bytes())?;
+
+    for (chunk, chunk_type) in [(&json, b"JSON"), (&buffer, b"BIN\0")] {
+        if chunk.is_empty() {
+            continue;
+        }
+
+        // Lengths were checked above, as part of the total length.
+        writer.write_all(&(chunk.len() as u32).to_le_bytes())?;
+        writer.write_all(chunk_type)?;
+        writer.write_all(chunk)?;
+    }
+
+    Ok(())
+}
+
+/// Build the JSON document and the binary buffer it refers to
+///
+/// `buffer_uri` provides the properties that locate the buffer, which depend
+/// on the container format.
+fn document(
+    mesh: &Mesh<Point<3>>,
+    options: &ExportOptions,
+    buffer_uri: impl FnOnce(&[u8]) -> String,
+) -> (String, Vec<u8>) {
+    let primitives = primitives(mesh);
+
+    let mut buffer = Vec::new();
+    let mut buffer_views = Vec::new();
+    let mut accessors = Vec::new();
+    let mut materials = Vec::new();
+    let mut mesh_primitives = Vec::new();
+
+    for (color, primitive) in &primitives {
+        let [min, max] = primitive.bounds();
+
+        let positions = buffer_view(
+            &mut buffer,
+            &mut buffer_views,
+            primitive
+                .vertices
+                .iter()
+                .flat_map(|(position, _)| *position),
+            TARGET_ARRAY_BUFFER,
+        );
+        let normals = buffer_view(
+            &mut buffer,
+            &mut buffer_views,
+            primitive.vertices.iter().flat_map(|(_, normal)| *normal),
+            TARGET_ARRAY_BUFFER,
+        );
+        let indices = buffer_view(
+            &mut buffer,
+            &mut buffer_views,
+            primitive.indices.iter().copied(),
+            TARGET_ELEMENT_ARRAY_BUFFER,
+        );
+
+        let num_vertices = primitive.vertices.len();
+        let position_accessor = accessors.len();
+        accessors.push(format!(
+            r#"{{"bufferView":{positions},"componentType":{FLOAT},"count":{num_vertices},"type":"VEC3","min":[{}],"max":[{}]}}"#,
+            join(min),
+            join(max),
+        ));
+        let normal_accessor = accessors.len();
+        accessors.push(format!(
+            r#"{{"bufferView":{normals},"componentType":{FLOAT},"count":{num_vertices},"type":"VEC3"}}"#,
+        ));
+        let index_accessor = accessors.len();
+        accessors.push(format!(
+            r#"{{"bufferView":{indices},"componentType":{UNSIGNED_INT},"count":{},"type":"SCALAR"}}"#,
+            primitive.indices.len(),
+        ));
+
+        let material = materials.len();
+        materials.push(material_for(*color));
+
+        mesh_primitives.push(format!(
+            r#"{{"attributes":{{"POSITION":{position_accessor},"NORMAL":{normal_accessor}}},"indices":{index_accessor},"material":{material},"mode":{MODE_TRIANGLES}}}"#,
+        ));
+    }
+
+    let mut json = String::new();
+    json.push_str(r#"{"asset":{"version":"2.0","generator":"Fornjot""#);
+    if let Some(author) = &options.author {
+        let _ = write!(json, r#","copyright":{}"#, string(author));
+    }
+    json.push('}');
+
+    let name = options
+        .name
+        .as_ref()
+        .map(|name| format!(r#""name":{},"#, string(name)))
+        .unwrap_or_default();
+
+    if mesh_primitives.is_empty() {
+        // A mesh without primitives is not allowed, so an empty model results
+        // in an empty scene.
+        let _ = write!(
+            json,
+            r#","scene":0,"scenes":[{{{}}}]"#,
+            name.trim_end_matches(',')
+        );
+    } else {
+        // Fornjot models are Z-up, and their coordinates have been converted
+        // into the exported unit. glTF is Y-up and uses meters. Instead of
+        // modifying the geometry, the root node converts between the two.
+        let scale = options.unit.millimeters() / 1000.;
+        let _ = write!(
+            json,
+            r#","scene":0,"scenes":[{{{name}"nodes":[0]}}],"nodes":[{{{name}"mesh":0,"rotation":[{},0,0,{}],"scale":[{scale},{scale},{scale}]}}]"#,
+            -std::f64::consts::FRAC_1_SQRT_2,
+            std::f64::consts::FRAC_1_SQRT_2,
+        );
+        let _ = write!(
+            json,
+            r#","meshes":[{{{name}"primitives":[{}]}}]"#,
+            mesh_primitives.join(","),
+        );
+        let _ = write!(json, r#","materials":[{}]"#, materials.join(","));
+        let _ = write!(json, r#","accessors":[{}]"#, accessors.join(","));
+        let _ = write!(json, r#","bufferViews":[{}]"#, buffer_views.join(","));
+
+        let uri = buffer_uri(&buffer);
+        let separator = if uri.is_empty() { "" } else { "," };
+        let _ = write!(
+            json,
+            r#","buffers":[{{"byteLength":{}{separator}{uri}}}]"#,
+            buffer.len(),
+        );
+    }
+
+    json.push('}');
+
+    (json, buffer)
+}
+
+/// The triangles of a single color, with indexed vertices
+#[derive(Default)]
+struct Primitive {
+    vertices: Vec<([f32; 3], [f32; 3])>,
+    indices: Vec<u32>,
+}
+
+impl Primitive {
+    fn bounds(&self) -> [[f32; 3]; 2] {
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+
+        for (position, _) in &self.vertices {
+            for i in 0..3 {
+                min[i] = min[i].min(position[i]);
+                max[i] = max[i].max(position[i]);
+            }
+        }
+
+        [min, max]
+    }
+}
+
+/// Group the triangles of the mesh by color, with their vertex normals
+fn primitives(mesh: &Mesh<Point<3>>) -> BTreeMap<Color, Primitive> {
+    let mut primitives: BTreeMap<Color, Primitive> = BTreeMap::new();
+    let mut indices_by_vertex: HashMap<(Color, [u32; 3], [u32; 3]), u32> =
+        HashMap::new();
+
+    for (triangle, normals) in mesh.triangles().zip(vertex_normals(mesh)) {
+        let primitive = primitives.entry(triangle.color).or_default();
+
+        for (point, normal) in triangle.inner.points().into_iter().zip(normals)
+        {
+            let position = point.coords.components.map(Scalar::into_f32);
+            let normal = normal.components.map(Scalar::into_f32);
+
+            let key = (
+                triangle.color,
+                position.map(f32::to_bits),
+                normal.map(f32::to_bits),
+            );
+            let index = *indices_by_vertex.entry(key).or_insert_with(|| {
+                let index = primitive.vertices.len() as u32;
+                primitive.vertices.push((position, normal));
+                index
+            });
+
+            primitive.indices.push(index);
+        }
+    }
+
+    primitives
+}
+
+/// Append data to the buffer, and add a buffer view that refers to it
+///
+/// Returns the index of the buffer view.
+fn buffer_view<T: ToLeBytes>(
+    buffer: &mut Vec<u8>,
+    buffer_views: &mut Vec<String>,
+    data: impl IntoIterator<Item = T>,
+    target: u32,
+) -> usize {
+    let offset = buffer.len();
+    for value in data {
+        buffer.extend_from_slice(&value.to_le_bytes());
+    }
+    let length = buffer.len() - offset;
+
+    let index = buffer_views.len();
+    buffer_views.push(format!(
+        r#"{{"buffer":0,"byteOffset":{offset},"byteLength":{length},"target":{target}}}"#
+    ));
+
+    index
+}
+
+fn material_for(color: Color) -> String {
+    let [r, g, b, a] = color.0.map(|channel| f64::from(channel) / 255.);
+
+    // glTF expects linear colors, while ours are in sRGB.
+    let [r, g, b] = [r, g, b].map(|channel| {
+        if channel <= 0.04045 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    });
+
+    let alpha_mode = if color.0[3] < 255 { "BLEND" } else { "OPAQUE" };
+
+    format!(
+        r#"{{"pbrMetallicRoughness":{{"baseColorFactor":[{r},{g},{b},{a}],"metallicFactor":0,"roughnessFactor":1}},"alphaMode":"{alpha_mode}"}}"#
+    )
+}
+
+/// Encode the text as a JSON string
+fn string(text: &str) -> String {
+    let mut encoded = String::from('"');
+
+    for c in text.chars() {
+        match c {
+            '"' => encoded.push_str("\\\""),
+            '\\' => encoded.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(encoded, "\\u{:04x}", u32::from(c));
+            }
+            c => encoded.push(c),
+        }
+    }
+
+    encoded.push('"');
+    encoded
+}
+
+fn join(values: [f32; 3]) -> String {
+    values.map(|value| value.to_string()).join(",")
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (bits >> (18 - 6 * i)) & 0b11_1111;
+                encoded.push(char::from(ALPHABET[index as usize]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+trait ToLeBytes {
+    fn to_le_bytes(self) -> [u8; 4];
+}
+
+impl ToLeBytes for f32 {
+    fn to_le_bytes(self) -> [u8; 4] {
+        f32::to_le_bytes(self)
+    }
+}
+
+impl ToLeBytes for u32 {
+    fn to_le_bytes(self) -> [u8; 4] {
+        u32::to_le_bytes(self)
+    }
+}
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const MODE_TRIANGLES: u32 = 4;
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine;
+    use fj_interop::{Color, Mesh};
+    use fj_math::Point;
+    use serde_json::Value;
+
+    use crate::ExportOptions;
+
+    use super::{write_glb, write_gltf};
+
+    #[test]
+    fn gltf_json() -> anyhow::Result<()> {
+        let mesh = two_colored_mesh();
+
+        let mut gltf = Vec::new();
+        write_gltf(&mesh, &ExportOptions::default(), &mut gltf)?;
+        let json: Value = serde_json::from_slice(&gltf)?;
+
+        assert_eq!(json["asset"]["version"], "2.0");
+        assert_eq!(json["materials"].as_array().map(Vec::len), Some(2));
+
+        let uri = json["buffers"][0]["uri"].as_str().unwrap();
+        let data = uri
+            .strip_prefix("data:application/octet-stream;base64,")
+            .unwrap();
+        let buffer = base64::engine::general_purpose::STANDARD.decode(data)?;
+        assert_eq!(
+            json["buffers"][0]["byteLength"].as_u64(),
+            Some(buffer.len() as u64),
+        );
+
+        // Primitives are ordered by color, so blue comes before red.
+        let mut triangles = triangles(&json, &buffer);
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(
+            triangles.remove(0),
+            vec![[[1., 0., 0.], [1., 1., 0.], [0., 1., 0.]]]
+        );
+        assert_eq!(
+            triangles.remove(0),
+            vec![[[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]]
+        );
+
+        for primitive in json["meshes"][0]["primitives"].as_array().unwrap() {
+            let normals =
+                accessor(&json, &buffer, &primitive["attributes"]["NORMAL"]);
+            for normal in normals.chunks(3) {
+                assert_eq!(normal, [0., 0., 1.]);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn glb_layout() -> anyhow::Result<()> {
+        let mesh = two_colored_mesh();
+
+        let mut glb = Vec::new();
+        write_glb(&mesh, &ExportOptions::default(), &mut glb)?;
+
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(u32_at(&glb, 4), 2);
+        assert_eq!(u32_at(&glb, 8) as usize, glb.len());
+
+        let json_length = u32_at(&glb, 12) as usize;
+        assert_eq!(&glb[16..20], b"JSON");
+        assert_eq!(json_length % 4, 0);
+        let json: Value = serde_json::from_slice(&glb[20..20 + json_length])?;
+
+        let bin_start = 20 + json_length;
+        let bin_length = u32_at(&glb, bin_start) as usize;
+        assert_eq!(&glb[bin_start + 4..bin_start + 8], b"BIN\0");
+        assert_eq!(bin_length % 4, 0);
+        assert_eq!(bin_start + 8 + bin_length, glb.len());
+        let buffer = &glb[bin_start + 8..];
+
+        // The buffer is stored in the binary chunk, not referred to by URI.
+        assert!(json["buffers"][0].get("uri").is_none());
+        let byte_length = json["buffers"][0]["byteLength"].as_u64().unwrap();
+        assert!(byte_length as usize <= bin_length);
+
+        assert_eq!(triangles(&json, buffer).len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sliver_triangle() -> anyhow::Result<()> {
+        // This triangle has an area, but not once its points are converted to
+        // `f32`. Its normals must still be valid.
+        let mut mesh = Mesh::new();
+        mesh.push_triangle(
+            [[0., 0., 0.], [1., 0., 0.], [2., 1e-50, 0.]].map(Point::from),
+            Color::default(),
+        );
+
+        let mut gltf = Vec::new();
+        write_gltf(&mesh, &ExportOptions::default(), &mut gltf)?;
+        let json: Value = serde_json::from_slice(&gltf)?;
+
+        let uri = json["buffers"][0]["uri"].as_str().unwrap();
+        let buffer = base64::engine::general_purpose::STANDARD
+            .decode(uri.split_once(',').unwrap().1)?;
+
+        let primitive = &json["meshes"][0]["primitives"][0];
+        let normals =
+            accessor(&json, &buffer, &primitive["attributes"]["NORMAL"]);
+        for normal in normals.chunks(3) {
+            let magnitude = normal.iter().map(|n| n * n).sum::<f32>().sqrt();
+            assert!((magnitude - 1.).abs() < 1e-6, "{normal:?}");
+        }
+
+        Ok(())
+    }
+
+    fn two_colored_mesh() -> Mesh<Point<3>> {
+        let mut mesh = Mesh::new();
+        mesh.push_triangle(
+            [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]].map(Point::from),
+            Color([255, 0, 0, 255]),
+        );
+        mesh.push_triangle(
+            [[1., 0., 0.], [1., 1., 0.], [0., 1., 0.]].map(Point::from),
+            Color([0, 0, 255, 255]),
+        );
+        mesh
+    }
+
+    /// Read back the triangles of each primitive
+    fn triangles(json: &Value, buffer: &[u8]) -> Vec<Vec<[[f32; 3]; 3]>> {
+        json["meshes"][0]["primitives"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|primitive| {
+                let positions = accessor(
+                    json,
+                    buffer,
+                    &primitive["attributes"]["POSITION"],
+                );
+                let indices = accessor(json, buffer, &primitive["indices"]);
+
+                indices
+                    .chunks(3)
+                    .map(|triangle| {
+                        [0, 1, 2].map(|i| {
+                            let index = triangle[i] as usize * 3;
+                            [0, 1, 2].map(|j| positions[index + j])
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Read the data of an accessor, converting all values to `f32`
+    fn accessor(json: &Value, buffer: &[u8], index: &Value) -> Vec<f32> {
+        let accessor = &json["accessors"][index.as_u64().unwrap() as usize];
+        let view = &json["bufferViews"]
+            [accessor["bufferView"].as_u64().unwrap() as usize];
+
+        let offset = view["byteOffset"].as_u64().unwrap() as usize;
+        let length = view["byteLength"].as_u64().unwrap() as usize;
+        let components = match accessor["type"].as_str().unwrap() {
+            "SCALAR" => 1,
+            "VEC3" => 3,
+            other => panic!("Unexpected accessor type: {other}"),
+        };
+        let count = accessor["count"].as_u64().unwrap() as usize;
+        assert_eq!(count * components * 4, length);
+
+        buffer[offset..offset + length]
+            .chunks(4)
+            .map(|bytes| {
+                let bytes = bytes.try_into().unwrap();
+                match accessor["componentType"].as_u64().unwrap() {
+                    5126 => f32::from_le_bytes(bytes),
+                    5125 => u32::from_le_bytes(bytes) as f32,
+                    other => panic!("Unexpected component type: {other}"),
+                }
+            })
+            .collect()
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+}
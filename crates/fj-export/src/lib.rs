@@ -8,70 +8,192 @@
 //!
 //! [Fornjot]: https://www.fornjot.app/
 
-use std::{fs::File, path::Path};
+mod dxf;
+mod gltf;
+mod normals;
+mod obj;
+mod options;
+mod profile;
+mod step;
+mod svg;
+mod three_mf;
+
+pub use self::{
+    dxf::{export_dxf, export_dxf_to_writer, export_dxf_with_options},
+    obj::export_mtl_to_writer,
+    options::{ExportOptions, StlEncoding, Unit},
+    profile::Profile,
+    step::{export_step, export_step_to_writer, export_step_with_options},
+    svg::{export_svg, export_svg_to_writer, export_svg_with_options},
+    three_mf::{export_3mf, export_3mf_to_writer},
+};
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
 
 use thiserror::Error;
 
-use fj_interop::Mesh;
+use fj_core::{
+    objects::{Face, HalfEdge},
+    storage::Handle,
+};
+use fj_interop::{Mesh, MeshAnalysis};
 use fj_math::{Point, Triangle};
 
 /// Export the provided mesh to the file at the given path.
 ///
 /// This function will create a file if it does not exist, and will truncate it if it does.
 ///
-/// Currently 3MF & STL file types are supported. The case insensitive file extension of
-/// the provided path is used to switch between supported types.
+/// Currently 3MF, glTF (`.gltf` or `.glb`), OBJ & STL file types are supported.
+/// The case insensitive file extension of the provided path is used to switch
+/// between supported types.
+///
+/// OBJ files are accompanied by an MTL file with the same name, which contains
+/// the materials for the mesh's colors.
+///
+/// STEP files can't be exported from a mesh. Use [`export_step`] instead. For
+/// 2D drawings, use [`export_svg`] or [`export_dxf`].
 pub fn export(mesh: &Mesh<Point<3>>, path: &Path) -> Result<(), Error> {
-    match path.extension() {
-        Some(extension) if extension.to_ascii_uppercase() == "3MF" => {
-            export_3mf(mesh, path)
-        }
-        Some(extension) if extension.to_ascii_uppercase() == "STL" => {
-            export_stl(mesh, path)
+    export_with_options(mesh, path, &ExportOptions::default())
+}
+
+/// Export the provided mesh to the file at the given path, using the options
+///
+/// This works like [`export`], except that the options control units, scale,
+/// precision and metadata of the exported file.
+pub fn export_with_options(
+    mesh: &Mesh<Point<3>>,
+    path: &Path,
+    options: &ExportOptions,
+) -> Result<(), Error> {
+    match Format::from_path(path)? {
+        Format::Obj => {
+            let mesh = options.transform(mesh)?;
+            obj::export_obj(&mesh, options, path)
         }
-        Some(extension) if extension.to_ascii_uppercase() == "OBJ" => {
-            export_obj(mesh, path)
+        format => {
+            let file = File::create(path)?;
+            export_to_writer(mesh, format, options, file)
         }
-        Some(extension) => Err(Error::InvalidExtension(
-            extension.to_string_lossy().into_owned(),
-        )),
-        None => Err(Error::NoExtension),
     }
 }
 
-fn export_3mf(mesh: &Mesh<Point<3>>, path: &Path) -> Result<(), Error> {
-    let vertices = mesh
-        .vertices()
-        .map(|point| threemf::model::Vertex {
-            x: point.x.into_f64(),
-            y: point.y.into_f64(),
-            z: point.z.into_f64(),
-        })
-        .collect();
-
-    let indices: Vec<_> = mesh.indices().collect();
-    let triangles = indices
-        .chunks(3)
-        .map(|triangle| threemf::model::Triangle {
-            v1: triangle[0] as usize,
-            v2: triangle[1] as usize,
-            v3: triangle[2] as usize,
-        })
-        .collect();
+/// Export the provided mesh to the given writer, in the given format
+///
+/// This works like [`export_with_options`], except for OBJ. Only the OBJ file
+/// itself is written, and it doesn't refer to a material library. Write the
+/// materials separately using [`export_mtl_to_writer`], if required.
+pub fn export_to_writer(
+    mesh: &Mesh<Point<3>>,
+    format: Format,
+    options: &ExportOptions,
+    writer: impl Write,
+) -> Result<(), Error> {
+    let mesh = options.transform(mesh)?;
+    let mut writer = BufWriter::new(writer);
 
-    let mesh = threemf::Mesh {
-        vertices: threemf::model::Vertices { vertex: vertices },
-        triangles: threemf::model::Triangles {
-            triangle: triangles,
+    match format {
+        Format::ThreeMF => {
+            three_mf::write_3mf_to_writer([&*mesh], options, &mut writer)?;
+        }
+        Format::Gltf => gltf::write_gltf(&mesh, options, &mut writer)?,
+        Format::Glb => gltf::write_glb(&mesh, options, &mut writer)?,
+        Format::Obj => obj::write_obj(&mesh, None, options, &mut writer)?,
+        Format::Stl => match options.stl_encoding {
+            StlEncoding::Binary => write_stl(&mesh, options, &mut writer)?,
+            StlEncoding::Ascii => write_ascii_stl(&mesh, options, &mut writer)?,
         },
-    };
+    }
 
-    threemf::write(path, mesh)?;
+    writer.flush()?;
 
     Ok(())
 }
 
-fn export_stl(mesh: &Mesh<Point<3>>, path: &Path) -> Result<(), Error> {
+/// Export the provided mesh to the file at the given path, if it is valid
+///
+/// Analyzes the mesh using [`Mesh::analyze`] first, and returns an error
+/// without writing anything, if any problems are found. Otherwise, this works
+/// like [`export`].
+pub fn export_validated(
+    mesh: &Mesh<Point<3>>,
+    path: &Path,
+) -> Result<(), Error> {
+    export_validated_with_options(mesh, path, &ExportOptions::default())
+}
+
+/// Export the provided mesh to the file at the given path, if it is valid
+///
+/// This works like [`export_validated`], except that the options control
+/// units, scale, precision and metadata of the exported file.
+pub fn export_validated_with_options(
+    mesh: &Mesh<Point<3>>,
+    path: &Path,
+    options: &ExportOptions,
+) -> Result<(), Error> {
+    let analysis = mesh.analyze();
+    if !analysis.is_valid() {
+        return Err(Error::InvalidMesh(Box::new(analysis)));
+    }
+
+    export_with_options(mesh, path, options)
+}
+
+/// A file format that meshes can be exported to
+///
+/// See [`export_to_writer`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// 3D Manufacturing Format (`.3mf`)
+    ThreeMF,
+
+    /// glTF, as JSON with an embedded buffer (`.gltf`)
+    Gltf,
+
+    /// glTF, in the binary container format (`.glb`)
+    Glb,
+
+    /// Wavefront OBJ (`.obj`)
+    Obj,
+
+    /// STL (`.stl`), binary or ASCII, depending on [`ExportOptions`]
+    Stl,
+}
+
+impl Format {
+    /// Determine the format from the extension of the provided path
+    ///
+    /// The extension is case insensitive.
+    pub fn from_path(path: &Path) -> Result<Self, Error> {
+        let Some(extension) = path.extension() else {
+            return Err(Error::NoExtension);
+        };
+
+        let format = match extension.to_ascii_uppercase().to_str() {
+            Some("3MF") => Self::ThreeMF,
+            Some("GLTF") => Self::Gltf,
+            Some("GLB") => Self::Glb,
+            Some("OBJ") => Self::Obj,
+            Some("STL") => Self::Stl,
+            _ => {
+                return Err(Error::InvalidExtension(
+                    extension.to_string_lossy().into_owned(),
+                ))
+            }
+        };
+
+        Ok(format)
+    }
+}
+
+fn write_stl(
+    mesh: &Mesh<Point<3>>,
+    options: &ExportOptions,
+    writer: &mut impl Write,
+) -> Result<(), Error> {
     let points = mesh
         .triangles()
         .map(|triangle| triangle.inner.points())
@@ -98,11 +220,16 @@ fn export_stl(mesh: &Mesh<Point<3>>, path: &Path) -> Result<(), Error> {
         })
         .collect::<Vec<_>>();
 
-    let mut file = File::create(path)?;
+    let mut header = [0u8; 80];
+    let text = options.stl_header.as_bytes();
+    if text.len() > header.len() {
+        return Err(Error::StlHeaderTooLong);
+    }
+    header[..text.len()].copy_from_slice(text);
 
     let binary_stl_file = stl::BinaryStlFile {
         header: stl::BinaryStlHeader {
-            header: [0u8; 80],
+            header,
             num_triangles: triangles
                 .len()
                 .try_into()
@@ -111,57 +238,37 @@ fn export_stl(mesh: &Mesh<Point<3>>, path: &Path) -> Result<(), Error> {
         triangles,
     };
 
-    stl::write_stl(&mut file, &binary_stl_file)?;
+    stl::write_stl(writer, &binary_stl_file)?;
 
     Ok(())
 }
 
-fn export_obj(mesh: &Mesh<Point<3>>, path: &Path) -> Result<(), Error> {
-    let mut f = File::create(path)?;
-
-    for (cnt, t) in mesh.triangles().enumerate() {
-        // write each point of the triangle
-        for v in t.inner.points() {
-            wavefront_rs::obj::writer::Writer { auto_newline: true }
-                .write(
-                    &mut f,
-                    &wavefront_rs::obj::entity::Entity::Vertex {
-                        x: v.x.into_f64(),
-                        y: v.y.into_f64(),
-                        z: v.z.into_f64(),
-                        w: None,
-                    },
-                )
-                .or(Err(Error::OBJ))?;
+fn write_ascii_stl(
+    mesh: &Mesh<Point<3>>,
+    options: &ExportOptions,
+    writer: &mut impl Write,
+) -> Result<(), Error> {
+    let name = options.name.as_deref().unwrap_or_default();
+
+    writeln!(writer, "solid {name}")?;
+
+    for triangle in mesh.triangles() {
+        let [nx, ny, nz] = triangle.inner.normal().components;
+        writeln!(writer, "  facet normal {nx} {ny} {nz}")?;
+        writeln!(writer, "    outer loop")?;
+
+        for point in triangle.inner.points() {
+            let [x, y, z] =
+                point.coords.components.map(|s| options.round(s.into_f64()));
+            writeln!(writer, "      vertex {x} {y} {z}")?;
         }
 
-        // write the triangle
-        wavefront_rs::obj::writer::Writer { auto_newline: true }
-            .write(
-                &mut f,
-                &wavefront_rs::obj::entity::Entity::Face {
-                    vertices: vec![
-                        wavefront_rs::obj::entity::FaceVertex {
-                            vertex: (cnt * 3 + 1) as i64,
-                            texture: None,
-                            normal: None,
-                        },
-                        wavefront_rs::obj::entity::FaceVertex {
-                            vertex: (cnt * 3 + 2) as i64,
-                            texture: None,
-                            normal: None,
-                        },
-                        wavefront_rs::obj::entity::FaceVertex {
-                            vertex: (cnt * 3 + 3) as i64,
-                            texture: None,
-                            normal: None,
-                        },
-                    ],
-                },
-            )
-            .or(Err(Error::OBJ))?;
+        writeln!(writer, "    endloop")?;
+        writeln!(writer, "  endfacet")?;
     }
 
+    writeln!(writer, "endsolid {name}")?;
+
     Ok(())
 }
 
@@ -184,11 +291,191 @@ pub enum Error {
     #[error("maximum triangle count exceeded")]
     InvalidTriangleCount,
 
-    /// Threemf error whilst exporting to 3MF file
-    #[error("threemf error whilst exporting to 3MF file")]
-    ThreeMF(#[from] threemf::Error),
+    /// Binary glTF file would be larger than its format allows (4 GiB)
+    #[error("binary glTF file would be larger than 4 GiB")]
+    GlbTooLarge,
+
+    /// Scale factor is not finite and positive
+    #[error("invalid scale factor `{0}`; must be finite and positive")]
+    InvalidScale(f64),
+
+    /// Header text of binary STL file is longer than 80 bytes
+    #[error("header text of binary STL file is longer than 80 bytes")]
+    StlHeaderTooLong,
+
+    /// Zip error whilst exporting to 3MF file
+    #[error("zip error whilst exporting to 3MF file")]
+    Zip(#[from] zip::result::ZipError),
+
+    /// Mesh analysis found problems in the mesh
+    #[error("{0}")]
+    InvalidMesh(Box<MeshAnalysis>),
 
     /// OBJ exporter error whilst exporting to OBJ file
     #[error("obj error whilst exporting to OBJ file")]
     OBJ,
+
+    /// Face can't be represented in STEP file
+    #[error("can't export face `{face:?}` to STEP file: {reason}")]
+    StepUnsupportedFace {
+        /// The face that can't be exported
+        face: Handle<Face>,
+
+        /// Why the face can't be exported
+        reason: &'static str,
+    },
+
+    /// Curve of half-edge can't be represented in STEP file
+    #[error(
+        "can't export curve of half-edge `{half_edge:?}` to STEP file: {reason}"
+    )]
+    StepUnsupportedCurve {
+        /// The half-edge whose curve can't be exported
+        half_edge: Handle<HalfEdge>,
+
+        /// Why the curve can't be exported
+        reason: &'static str,
+    },
+
+    /// Profile can't be represented in a 2D drawing
+    #[error("can't export profile to 2D drawing: {reason}")]
+    DrawingUnsupported {
+        /// Why the profile can't be exported
+        reason: &'static str,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use fj_interop::{Color, Mesh};
+    use fj_math::Point;
+
+    use crate::{
+        export_to_writer, export_with_options, Error, ExportOptions, Format,
+        StlEncoding,
+    };
+
+    #[test]
+    fn format_from_path() {
+        let formats = [
+            ("model.3mf", Format::ThreeMF),
+            ("model.gltf", Format::Gltf),
+            ("model.GLB", Format::Glb),
+            ("model.Obj", Format::Obj),
+            ("model.stl", Format::Stl),
+        ];
+        for (path, format) in formats {
+            assert_eq!(Format::from_path(Path::new(path)).ok(), Some(format));
+        }
+
+        assert!(matches!(
+            Format::from_path(Path::new("model")),
+            Err(Error::NoExtension)
+        ));
+        assert!(matches!(
+            Format::from_path(Path::new("model.step")),
+            Err(Error::InvalidExtension(extension)) if extension == "step"
+        ));
+    }
+
+    #[test]
+    fn writer_output() -> anyhow::Result<()> {
+        let mesh = tetrahedron();
+
+        let export = |format, options: &ExportOptions| {
+            let mut bytes = Vec::new();
+            export_to_writer(&mesh, format, options, &mut bytes).map(|()| bytes)
+        };
+
+        let three_mf = export(Format::ThreeMF, &ExportOptions::default())?;
+        assert!(three_mf.starts_with(b"PK\x03\x04"));
+
+        let gltf = export(Format::Gltf, &ExportOptions::default())?;
+        serde_json::from_slice::<serde_json::Value>(&gltf)?;
+
+        let glb = export(Format::Glb, &ExportOptions::default())?;
+        assert!(glb.starts_with(b"glTF"));
+
+        let stl = export(Format::Stl, &ExportOptions::default())?;
+        assert_eq!(stl.len(), 80 + 4 + 4 * 50);
+        assert_eq!(&stl[80..84], &4u32.to_le_bytes());
+
+        let ascii_stl = export(
+            Format::Stl,
+            &ExportOptions {
+                stl_encoding: StlEncoding::Ascii,
+                ..ExportOptions::default()
+            },
+        )?;
+        let ascii_stl = String::from_utf8(ascii_stl)?;
+        assert!(ascii_stl.starts_with("solid "));
+        assert_eq!(ascii_stl.matches("facet normal").count(), 4);
+
+        // When writing OBJ to a writer, there's no material library to refer
+        // to.
+        let obj =
+            String::from_utf8(export(Format::Obj, &ExportOptions::default())?)?;
+        assert!(!obj.contains("mtllib"));
+        assert_eq!(
+            obj.lines().filter(|line| line.starts_with("f ")).count(),
+            4
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn writer_matches_file() -> anyhow::Result<()> {
+        let mesh = tetrahedron();
+        let dir = tempfile::tempdir()?;
+        let options = ExportOptions {
+            name: Some("tetrahedron".into()),
+            ..ExportOptions::default()
+        };
+
+        for (file, format) in [
+            ("model.3mf", Format::ThreeMF),
+            ("model.gltf", Format::Gltf),
+            ("model.glb", Format::Glb),
+            ("model.stl", Format::Stl),
+        ] {
+            let path = dir.path().join(file);
+            export_with_options(&mesh, &path, &options)?;
+
+            let mut bytes = Vec::new();
+            export_to_writer(&mesh, format, &options, &mut bytes)?;
+
+            assert_eq!(fs::read(&path)?, bytes, "{file}");
+        }
+
+        // Only the OBJ file refers to its material library.
+        let path = dir.path().join("model.obj");
+        export_with_options(&mesh, &path, &options)?;
+        let file = fs::read_to_string(&path)?;
+        let mut bytes = Vec::new();
+        export_to_writer(&mesh, Format::Obj, &options, &mut bytes)?;
+        assert_eq!(
+            file.strip_prefix("mtllib model.mtl\n"),
+            Some(String::from_utf8(bytes)?.as_str()),
+        );
+        assert!(dir.path().join("model.mtl").exists());
+
+        Ok(())
+    }
+
+    fn tetrahedron() -> Mesh<Point<3>> {
+        let [a, b, c, d] =
+            [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]]
+                .map(Point::from);
+
+        let mut mesh = Mesh::new();
+        for triangle in [[a, c, b], [a, b, d], [b, c, d], [a, d, c]] {
+            mesh.start_face();
+            mesh.push_triangle(triangle, Color::default());
+        }
+
+        mesh
+    }
 }
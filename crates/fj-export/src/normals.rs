@@ -0,0 +1,84 @@
+//! Vertex normals for formats that support smooth shading
+
+use std::collections::BTreeMap;
+
+use fj_interop::Mesh;
+use fj_math::{Point, Scalar, Vector};
+
+/// Compute the vertex normals of each triangle in the mesh
+///
+/// Returns the normals of the 3 vertices of each triangle, in the same order as
+/// [`Mesh::triangles`].
+///
+/// Vertex normals are averaged over the adjacent triangles that face in a
+/// similar direction. This results in smooth shading of curved surfaces, while
+/// keeping sharp edges sharp.
+///
+/// All returned normals have unit length, including those of triangles that
+/// have no area.
+pub fn vertex_normals(mesh: &Mesh<Point<3>>) -> Vec<[Vector<3>; 3]> {
+    let triangles = mesh
+        .triangles()
+        .map(|triangle| {
+            let points = triangle.inner.points();
+            let [a, b, c] = points;
+
+            // The magnitude of the cross product is twice the triangle's area,
+            // which makes it suitable for weighting the normals.
+            let normal = (b - a).cross(&(c - a));
+            (points, normal)
+        })
+        .collect::<Vec<_>>();
+
+    let mut triangles_by_point: BTreeMap<Point<3>, Vec<usize>> =
+        BTreeMap::new();
+    for (i, (points, _)) in triangles.iter().enumerate() {
+        for point in points {
+            triangles_by_point.entry(*point).or_default().push(i);
+        }
+    }
+
+    let min_cos = Scalar::from(SMOOTHING_ANGLE_DEGREES.to_radians().cos());
+    let is_similar = |a: &Vector<3>, b: &Vector<3>| {
+        let magnitudes = a.magnitude() * b.magnitude();
+        magnitudes > Scalar::ZERO && a.dot(b) >= magnitudes * min_cos
+    };
+
+    triangles
+        .iter()
+        .map(|(points, normal)| {
+            points.map(|point| {
+                let adjacent = triangles_by_point[&point]
+                    .iter()
+                    .map(|&i| triangles[i].1)
+                    .collect::<Vec<_>>();
+
+                let mut vertex_normal = adjacent
+                    .iter()
+                    .filter(|other| is_similar(normal, other))
+                    .fold(Vector::from([0., 0., 0.]), |sum, other| {
+                        sum + *other
+                    });
+
+                // A triangle without area doesn't have a normal. Use the
+                // normals of all adjacent triangles instead, or an arbitrary
+                // one, if none of those have a normal either.
+                if vertex_normal.magnitude() == Scalar::ZERO {
+                    vertex_normal = adjacent
+                        .iter()
+                        .fold(Vector::from([0., 0., 0.]), |sum, other| {
+                            sum + *other
+                        });
+                }
+                if vertex_normal.magnitude() == Scalar::ZERO {
+                    return Vector::unit_z();
+                }
+
+                vertex_normal.normalize()
+            })
+        })
+        .collect()
+}
+
+/// Triangles whose normals differ by less than this are shaded smoothly
+const SMOOTHING_ANGLE_DEGREES: f64 = 30.;
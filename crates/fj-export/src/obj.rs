@@ -0,0 +1,334 @@
+//! OBJ export
+//!
+//! Writes an indexed mesh with vertex normals. Colors are written as materials
+//! into a companion MTL file, next to the OBJ file.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use fj_interop::{Color, Mesh};
+use fj_math::{Point, Scalar};
+use wavefront_rs::obj;
+
+use crate::{normals::vertex_normals, Error, ExportOptions};
+
+/// Export the mesh to an OBJ file, and its materials to an MTL file
+///
+/// The MTL file is written to the same path as the OBJ file, with the
+/// extension replaced by `mtl`. Each face of the mesh becomes a group.
+pub fn export_obj(
+    mesh: &Mesh<Point<3>>,
+    options: &ExportOptions,
+    path: &Path,
+) -> Result<(), Error> {
+    let mtl_path = path.with_extension("mtl");
+    let mtl_name = mtl_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned());
+
+    let mut file = BufWriter::new(File::create(path)?);
+    write_obj(mesh, mtl_name.as_deref(), options, &mut file)?;
+    file.flush()?;
+
+    let mut file = BufWriter::new(File::create(&mtl_path)?);
+    export_mtl_to_writer(mesh, &mut file)?;
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Export the materials of the provided mesh to the given writer, as MTL
+///
+/// Each color of the mesh becomes a material. This is the material library that
+/// OBJ files written by [`export`] refer to.
+///
+/// [`export`]: crate::export
+pub fn export_mtl_to_writer(
+    mesh: &Mesh<Point<3>>,
+    mut writer: impl Write,
+) -> Result<(), Error> {
+    // The MTL writer of `wavefront_rs` writes the color statements in lower
+    // case, which many readers don't recognize. The format is simple enough to
+    // write it directly.
+    for (color, name) in materials(mesh) {
+        let [r, g, b, a] = color.0.map(|channel| f64::from(channel) / 255.);
+
+        writeln!(writer, "newmtl {name}")?;
+        writeln!(writer, "Ka {r} {g} {b}")?;
+        writeln!(writer, "Kd {r} {g} {b}")?;
+        writeln!(writer, "d {a}")?;
+        writeln!(writer, "illum 1")?;
+    }
+
+    Ok(())
+}
+
+/// Write the mesh as OBJ
+///
+/// `mtl_lib` is the name of the material library that the file refers to, if
+/// any. The mesh is expected to be transformed according to the options
+/// already.
+pub fn write_obj(
+    mesh: &Mesh<Point<3>>,
+    mtl_lib: Option<&str>,
+    options: &ExportOptions,
+    writer: &mut impl Write,
+) -> Result<(), Error> {
+    let materials = materials(mesh);
+
+    let mut write = |entity: obj::entity::Entity| {
+        obj::writer::Writer { auto_newline: true }
+            .write(writer, &entity)
+            .or(Err(Error::OBJ))
+    };
+
+    if let Some(name) = mtl_lib {
+        write(obj::entity::Entity::MtlLib {
+            name: name.to_string(),
+        })?;
+    }
+
+    if let Some(name) = &options.name {
+        write(obj::entity::Entity::Object { name: name.clone() })?;
+    }
+
+    let mut vertex_indices = BTreeMap::new();
+    for vertex in mesh.vertices() {
+        let index = vertex_indices.len() + 1;
+        vertex_indices.insert(vertex, index);
+
+        write(obj::entity::Entity::Vertex {
+            x: options.round(vertex.x.into_f64()),
+            y: options.round(vertex.y.into_f64()),
+            z: options.round(vertex.z.into_f64()),
+            w: None,
+        })?;
+    }
+
+    // Many vertices share the same normal, most notably on planar faces.
+    let mut normal_indices = HashMap::new();
+    let normals = vertex_normals(mesh)
+        .into_iter()
+        .map(|normals| {
+            normals.map(|normal| {
+                let components = normal.components.map(Scalar::into_f64);
+                let next_index = normal_indices.len() + 1;
+                let index = *normal_indices
+                    .entry(components.map(f64::to_bits))
+                    .or_insert(next_index);
+
+                (index, components)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut written_normals = 0;
+    for &(index, [x, y, z]) in normals.iter().flatten() {
+        if index > written_normals {
+            write(obj::entity::Entity::VertexNormal { x, y, z })?;
+            written_normals = index;
+        }
+    }
+
+    let mut normals = normals.into_iter();
+    let mut current_material = None;
+
+    for (i, face) in mesh.faces().enumerate() {
+        write(obj::entity::Entity::Group {
+            name: format!("face{i}"),
+        })?;
+
+        for (triangle, normals) in face.iter().zip(&mut normals) {
+            if current_material != Some(triangle.color) {
+                current_material = Some(triangle.color);
+                write(obj::entity::Entity::UseMtl {
+                    name: materials[&triangle.color].clone(),
+                })?;
+            }
+
+            let vertices = triangle
+                .inner
+                .points()
+                .into_iter()
+                .zip(normals)
+                .map(|(point, (normal, _))| {
+                    obj::entity::FaceVertex::new_vtn(
+                        vertex_indices[&point] as i64,
+                        None,
+                        Some(normal as i64),
+                    )
+                })
+                .collect();
+
+            write(obj::entity::Entity::Face { vertices })?;
+        }
+    }
+
+    Ok(())
+}
+
+fn materials(mesh: &Mesh<Point<3>>) -> BTreeMap<Color, String> {
+    mesh.triangles()
+        .map(|triangle| (triangle.color, material_name(triangle.color)))
+        .collect()
+}
+
+fn material_name(Color([r, g, b, a]): Color) -> String {
+    format!("color_{r:02X}{g:02X}{b:02X}{a:02X}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use fj_interop::{Color, Mesh};
+    use fj_math::Point;
+
+    use crate::ExportOptions;
+
+    use super::{export_obj, write_obj};
+
+    #[test]
+    fn indices_and_normals() -> anyhow::Result<()> {
+        let mesh = mesh();
+
+        let mut obj = Vec::new();
+        write_obj(&mesh, None, &ExportOptions::default(), &mut obj)?;
+        let obj = Obj::parse(&String::from_utf8(obj)?);
+
+        assert_eq!(obj.mtl_lib, None);
+        assert_eq!(obj.vertices.len(), 5);
+
+        // Indices are 1-based. Reading back the triangles through them results
+        // in the original triangles.
+        let triangles = obj
+            .faces
+            .iter()
+            .map(|face| face.map(|(vertex, _)| obj.vertices[vertex - 1]))
+            .collect::<Vec<_>>();
+        let expected =
+            mesh.triangles()
+                .map(|triangle| {
+                    triangle.inner.points().map(|point| {
+                        point.coords.components.map(|s| s.into_f64())
+                    })
+                })
+                .collect::<Vec<_>>();
+        assert_eq!(triangles, expected);
+
+        // The first two triangles are coplanar and share their normals. The
+        // third one is at a right angle to them, so the edge between them is
+        // sharp.
+        assert_eq!(obj.normals, [[0., 0., 1.], [0., 1., 0.]]);
+        let normals = obj
+            .faces
+            .iter()
+            .map(|face| face.map(|(_, normal)| obj.normals[normal - 1]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            normals,
+            [[[0., 0., 1.]; 3], [[0., 0., 1.]; 3], [[0., 1., 0.]; 3]],
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn material_library() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("model.obj");
+
+        export_obj(&mesh(), &ExportOptions::default(), &path)?;
+
+        let obj = Obj::parse(&fs::read_to_string(&path)?);
+        let mtl = fs::read_to_string(dir.path().join("model.mtl"))?;
+
+        assert_eq!(obj.mtl_lib.as_deref(), Some("model.mtl"));
+        assert_eq!(obj.materials, ["color_FF0000FF", "color_0000FFFF"]);
+
+        let materials = mtl
+            .lines()
+            .filter_map(|line| line.strip_prefix("newmtl "))
+            .collect::<Vec<_>>();
+        assert_eq!(materials, ["color_0000FFFF", "color_FF0000FF"]);
+        assert!(mtl.contains("Kd 0 0 1\n"));
+
+        Ok(())
+    }
+
+    /// Two red triangles that form a square, and a blue one at a right angle
+    fn mesh() -> Mesh<Point<3>> {
+        let red = Color([255, 0, 0, 255]);
+        let blue = Color([0, 0, 255, 255]);
+
+        let mut mesh = Mesh::new();
+        mesh.start_face();
+        mesh.push_triangle(
+            [[0., 0., 0.], [1., 0., 0.], [1., 1., 0.]].map(Point::from),
+            red,
+        );
+        mesh.push_triangle(
+            [[0., 0., 0.], [1., 1., 0.], [0., 1., 0.]].map(Point::from),
+            red,
+        );
+        mesh.start_face();
+        mesh.push_triangle(
+            [[0., 0., 0.], [0., 0., 1.], [1., 0., 0.]].map(Point::from),
+            blue,
+        );
+        mesh
+    }
+
+    /// The parts of an OBJ file that the exporter writes
+    #[derive(Default)]
+    struct Obj {
+        mtl_lib: Option<String>,
+        vertices: Vec<[f64; 3]>,
+        normals: Vec<[f64; 3]>,
+        faces: Vec<[(usize, usize); 3]>,
+        materials: Vec<String>,
+    }
+
+    impl Obj {
+        fn parse(source: &str) -> Self {
+            let mut obj = Self::default();
+
+            for line in source.lines() {
+                let (keyword, rest) = line.split_once(' ').unwrap();
+                let mut values = rest.split(' ');
+
+                match keyword {
+                    "mtllib" => obj.mtl_lib = Some(rest.to_string()),
+                    "usemtl" => obj.materials.push(rest.to_string()),
+                    "v" | "vn" => {
+                        let coords = [0; 3]
+                            .map(|_| values.next().unwrap().parse().unwrap());
+                        if keyword == "v" {
+                            obj.vertices.push(coords);
+                        } else {
+                            obj.normals.push(coords);
+                        }
+                    }
+                    "f" => {
+                        let face = [0; 3].map(|_| {
+                            let (vertex, normal) = values
+                                .next()
+                                .unwrap()
+                                .split_once("//")
+                                .unwrap();
+                            (vertex.parse().unwrap(), normal.parse().unwrap())
+                        });
+                        obj.faces.push(face);
+                    }
+                    _ => {}
+                }
+            }
+
+            obj
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! Options that control how models are exported
+//!
+//! See [`ExportOptions`].
+
+use std::borrow::Cow;
+
+use fj_interop::Mesh;
+use fj_math::Point;
+
+use crate::Error;
+
+/// Options that control how a mesh is exported
+///
+/// See [`export_with_options`] and [`export_to_writer`]. The default options
+/// result in the same files as [`export`].
+///
+/// Fornjot models don't have units. Their coordinates are interpreted as
+/// millimeters, and converted to [`ExportOptions::unit`] on export.
+///
+/// ```
+/// use fj_export::{ExportOptions, StlEncoding, Unit};
+///
+/// // An ASCII STL file, with coordinates in inches.
+/// let options = ExportOptions {
+///     unit: Unit::Inch,
+///     stl_encoding: StlEncoding::Ascii,
+///     ..ExportOptions::default()
+/// };
+/// ```
+///
+/// [`export`]: crate::export
+/// [`export_with_options`]: crate::export_with_options
+/// [`export_to_writer`]: crate::export_to_writer
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportOptions {
+    /// The unit that the exported coordinates are in
+    ///
+    /// Formats that can declare their unit (3MF, glTF, STEP, DXF, SVG) do so.
+    /// For all others, the consumer of the file must know which unit to
+    /// expect.
+    pub unit: Unit,
+
+    /// The factor that the model is scaled by, in addition to unit conversion
+    ///
+    /// Must be finite and positive.
+    pub scale: f64,
+
+    /// The number of decimal places that coordinates are rounded to
+    ///
+    /// Only applies to text-based formats (3MF, OBJ, ASCII STL). If `None`,
+    /// coordinates are written with as many places as required to represent
+    /// them exactly.
+    pub precision: Option<u8>,
+
+    /// The encoding of STL files
+    pub stl_encoding: StlEncoding,
+
+    /// The text in the header of binary STL files
+    ///
+    /// Must not be longer than 80 bytes. Should not start with `solid`, as
+    /// some applications would mistake the file for an ASCII STL file.
+    pub stl_header: String,
+
+    /// The name of the model
+    ///
+    /// Written to 3MF (as the title), glTF, OBJ, ASCII STL, STEP, SVG (as the
+    /// title), and DXF (as a comment) files.
+    pub name: Option<String>,
+
+    /// The author of the model
+    ///
+    /// Written to 3MF (as the designer), glTF (as the copyright holder),
+    /// STEP, SVG (as the creator), and DXF (as a comment) files.
+    pub author: Option<String>,
+}
+
+impl ExportOptions {
+    /// Convert the mesh into the exported unit, and scale it
+    pub(crate) fn transform<'r>(
+        &self,
+        mesh: &'r Mesh<Point<3>>,
+    ) -> Result<Cow<'r, Mesh<Point<3>>>, Error> {
+        let factor = self.factor()?;
+        if factor == 1. {
+            return Ok(Cow::Borrowed(mesh));
+        }
+
+        let mut transformed = Mesh::new();
+        for face in mesh.faces() {
+            transformed.start_face();
+
+            for triangle in face {
+                transformed.push_triangle(
+                    triangle.inner.points().map(|point| point * factor),
+                    triangle.color,
+                );
+            }
+        }
+
+        Ok(Cow::Owned(transformed))
+    }
+
+    /// The factor that converts model coordinates into exported coordinates
+    ///
+    /// Combines the scale with the conversion into the exported unit.
+    pub(crate) fn factor(&self) -> Result<f64, Error> {
+        if !(self.scale.is_finite() && self.scale > 0.) {
+            return Err(Error::InvalidScale(self.scale));
+        }
+
+        Ok(self.scale / self.unit.millimeters())
+    }
+
+    /// Round a coordinate to the configured precision
+    pub(crate) fn round(&self, value: f64) -> f64 {
+        let Some(precision) = self.precision else {
+            return value;
+        };
+
+        let factor = 10f64.powi(i32::from(precision));
+
+        // Adding zero turns negative zero, which rounding can result in, into
+        // positive zero. Otherwise, it would be written as `-0`.
+        (value * factor).round() / factor + 0.
+    }
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            unit: Unit::default(),
+            scale: 1.,
+            precision: None,
+            stl_encoding: StlEncoding::default(),
+            stl_header: String::new(),
+            name: None,
+            author: None,
+        }
+    }
+}
+
+/// The unit that the coordinates of an exported model are interpreted in
+///
+/// Fornjot models don't have units. Formats that can declare their unit use
+/// millimeters by default.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Unit {
+    /// Micrometers
+    Micron,
+
+    /// Millimeters
+    #[default]
+    Millimeter,
+
+    /// Centimeters
+    Centimeter,
+
+    /// Meters
+    Meter,
+
+    /// Inches
+    Inch,
+
+    /// Feet
+    Foot,
+}
+
+impl Unit {
+    /// The length of the unit, in millimeters
+    pub fn millimeters(&self) -> f64 {
+        match self {
+            Self::Micron => 0.001,
+            Self::Millimeter => 1.,
+            Self::Centimeter => 10.,
+            Self::Meter => 1000.,
+            Self::Inch => 25.4,
+            Self::Foot => 304.8,
+        }
+    }
+}
+
+/// The encoding of an STL file
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum StlEncoding {
+    /// Binary STL, which is more compact and widely supported
+    #[default]
+    Binary,
+
+    /// ASCII STL, which is human-readable
+    Ascii,
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_interop::{Color, Mesh};
+    use fj_math::Point;
+
+    use crate::{export_to_writer, Error, Format};
+
+    use super::{ExportOptions, Unit};
+
+    #[test]
+    fn unit_and_scale() -> anyhow::Result<()> {
+        let mesh = triangle();
+
+        let options = ExportOptions {
+            unit: Unit::Centimeter,
+            scale: 2.,
+            ..ExportOptions::default()
+        };
+        let transformed = options.transform(&mesh)?;
+        let points = transformed
+            .triangles()
+            .map(|triangle| triangle.inner.points())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            points,
+            [[[0., 0., 0.], [2., 0., 0.], [0., 4., 0.]].map(Point::from)],
+        );
+
+        // Without conversion, the original mesh is used.
+        let transformed = ExportOptions::default().transform(&mesh)?;
+        assert!(std::ptr::eq(&*transformed, &mesh));
+
+        for scale in [0., -1., f64::NAN, f64::INFINITY] {
+            let options = ExportOptions {
+                scale,
+                ..ExportOptions::default()
+            };
+            assert!(matches!(
+                options.transform(&mesh),
+                Err(Error::InvalidScale(_))
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn units() {
+        for unit in [
+            Unit::Micron,
+            Unit::Millimeter,
+            Unit::Centimeter,
+            Unit::Meter,
+            Unit::Inch,
+            Unit::Foot,
+        ] {
+            let options = ExportOptions {
+                unit,
+                ..ExportOptions::default()
+            };
+            let x = options
+                .transform(&triangle())
+                .ok()
+                .and_then(|mesh| mesh.vertices().nth(1))
+                .map(|point| point.x.into_f64())
+                .unwrap_or_default();
+
+            // The vertex is 10 mm from the origin.
+            let expected = 10. / unit.millimeters();
+            assert!((x - expected).abs() <= expected * 1e-12, "{unit:?}");
+        }
+    }
+
+    #[test]
+    fn precision() {
+        let options = ExportOptions {
+            precision: Some(2),
+            ..ExportOptions::default()
+        };
+        assert_eq!(options.round(1.23456), 1.23);
+        assert_eq!(options.round(1.005_1), 1.01);
+
+        // Negative zero would be written as `-0`.
+        let rounded = options.round(-0.001);
+        assert_eq!(rounded, 0.);
+        assert!(rounded.is_sign_positive());
+
+        let options = ExportOptions {
+            precision: Some(0),
+            ..ExportOptions::default()
+        };
+        assert_eq!(options.round(2.5), 3.);
+
+        assert_eq!(ExportOptions::default().round(1.23456), 1.23456);
+    }
+
+    #[test]
+    fn precision_in_output() -> anyhow::Result<()> {
+        let mut mesh = Mesh::new();
+        mesh.push_triangle(
+            [[0., 0., 0.], [1. / 3., 0., 0.], [0., 2. / 3., 0.]]
+                .map(Point::from),
+            Color::default(),
+        );
+
+        let options = ExportOptions {
+            precision: Some(3),
+            ..ExportOptions::default()
+        };
+        let mut obj = Vec::new();
+        export_to_writer(&mesh, Format::Obj, &options, &mut obj)?;
+        let obj = String::from_utf8(obj)?;
+
+        assert!(obj.contains("v 0.333 0 0\n"), "{obj}");
+        assert!(obj.contains("v 0 0.667 0\n"), "{obj}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn stl_header() -> anyhow::Result<()> {
+        let mesh = triangle();
+
+        let options = ExportOptions {
+            stl_header: "Fornjot".into(),
+            ..ExportOptions::default()
+        };
+        let mut stl = Vec::new();
+        export_to_writer(&mesh, Format::Stl, &options, &mut stl)?;
+        assert_eq!(&stl[..7], b"Fornjot");
+        assert!(stl[7..80].iter().all(|&byte| byte == 0));
+
+        let options = ExportOptions {
+            stl_header: "a".repeat(80),
+            ..ExportOptions::default()
+        };
+        export_to_writer(&mesh, Format::Stl, &options, &mut Vec::new())?;
+
+        let options = ExportOptions {
+            stl_header: "a".repeat(81),
+            ..ExportOptions::default()
+        };
+        assert!(matches!(
+            export_to_writer(&mesh, Format::Stl, &options, &mut Vec::new()),
+            Err(Error::StlHeaderTooLong)
+        ));
+
+        Ok(())
+    }
+
+    fn triangle() -> Mesh<Point<3>> {
+        let mut mesh = Mesh::new();
+        mesh.push_triangle(
+            [[0., 0., 0.], [10., 0., 0.], [0., 20., 0.]].map(Point::from),
+            Color::default(),
+        );
+        mesh
+    }
+}
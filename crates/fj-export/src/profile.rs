@@ -0,0 +1,449 @@
+//! 2D profiles, as exported to drawing formats
+//!
+//! See [`Profile`].
+
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+use fj_core::{
+    geometry::{GlobalPath, SurfaceGeometry, SurfacePath},
+    objects::{Cycle, Face, Handedness, Region, Sketch},
+};
+use fj_math::{Point, Scalar, Vector};
+
+use crate::Error;
+
+/// A 2D shape that can be exported to a drawing format
+///
+/// Drawings preserve the exact geometry of the shape. Straight edges become
+/// lines, circular edges become arcs or circles.
+#[derive(Clone, Copy, Debug)]
+pub enum Profile<'r> {
+    /// A sketch, in its own coordinate system
+    Sketch(&'r Sketch),
+
+    /// A planar face
+    ///
+    /// The face is drawn as seen from the front, in a coordinate system within
+    /// its plane. The origin of that coordinate system is the origin of the
+    /// surface, and its x-axis points along the surface's u-axis. Since the
+    /// front of a face is the side from which its exterior appears
+    /// counter-clockwise, a reversed face is drawn mirrored.
+    Face(&'r Face),
+}
+
+impl<'r> From<&'r Sketch> for Profile<'r> {
+    fn from(sketch: &'r Sketch) -> Self {
+        Self::Sketch(sketch)
+    }
+}
+
+impl<'r> From<&'r Face> for Profile<'r> {
+    fn from(face: &'r Face) -> Self {
+        Self::Face(face)
+    }
+}
+
+impl Profile<'_> {
+    /// Convert the profile into its segments, scaled by the provided factor
+    ///
+    /// Returns the cycles of each region, each of which is a closed chain of
+    /// segments.
+    pub(crate) fn regions(
+        &self,
+        factor: f64,
+    ) -> Result<Vec<Vec<Vec<Segment>>>, Error> {
+        let regions = match self {
+            Self::Sketch(sketch) => sketch
+                .regions()
+                .iter()
+                .map(|region| region_segments(region, &Frame::Identity))
+                .collect::<Result<Vec<_>, _>>()?,
+            Self::Face(face) => {
+                let frame = Frame::from_surface(
+                    face.surface().geometry(),
+                    face.coord_handedness(),
+                )?;
+                vec![region_segments(face.region(), &frame)?]
+            }
+        };
+
+        let regions = regions
+            .into_iter()
+            .map(|cycles| {
+                cycles
+                    .into_iter()
+                    .map(|segments| {
+                        segments
+                            .into_iter()
+                            .map(|segment| segment.scale(factor))
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(regions)
+    }
+}
+
+/// A segment of a cycle in a drawing
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Segment {
+    Line {
+        start: Point<2>,
+        end: Point<2>,
+    },
+    Arc {
+        center: Point<2>,
+        radius: Scalar,
+        start: Point<2>,
+        end: Point<2>,
+
+        /// The angle that the arc spans, in radians
+        ///
+        /// Positive values indicate a counter-clockwise arc. If the absolute
+        /// value is a full turn, the arc is a full circle.
+        sweep: Scalar,
+    },
+}
+
+impl Segment {
+    pub fn start(&self) -> Point<2> {
+        match self {
+            Self::Line { start, .. } | Self::Arc { start, .. } => *start,
+        }
+    }
+
+    pub fn end(&self) -> Point<2> {
+        match self {
+            Self::Line { end, .. } | Self::Arc { end, .. } => *end,
+        }
+    }
+
+    /// Scale the segment around the origin
+    pub fn scale(self, factor: f64) -> Self {
+        match self {
+            Self::Line { start, end } => Self::Line {
+                start: start * factor,
+                end: end * factor,
+            },
+            Self::Arc {
+                center,
+                radius,
+                start,
+                end,
+                sweep,
+            } => Self::Arc {
+                center: center * factor,
+                radius: radius * factor,
+                start: start * factor,
+                end: end * factor,
+                sweep,
+            },
+        }
+    }
+
+    /// Determine whether this is an arc that is a full circle
+    pub fn is_full_circle(&self) -> bool {
+        match self {
+            Self::Line { .. } => false,
+            Self::Arc { sweep, .. } => {
+                (sweep.abs().into_f64() - TAU).abs() < ANGLE_EPSILON
+            }
+        }
+    }
+
+    /// The points that the bounding box of the segment must contain
+    pub fn extreme_points(&self) -> Vec<Point<2>> {
+        let mut points = vec![self.start(), self.end()];
+
+        if let Self::Arc {
+            center,
+            radius,
+            start,
+            sweep,
+            ..
+        } = *self
+        {
+            let start_angle = angle(start - center);
+            let [from, to] = if sweep > Scalar::ZERO {
+                [start_angle, start_angle + sweep.into_f64()]
+            } else {
+                [start_angle + sweep.into_f64(), start_angle]
+            };
+
+            // Add the points where the arc crosses the axes.
+            let mut quarter = (from / FRAC_PI_2).ceil();
+            while quarter * FRAC_PI_2 <= to {
+                points.push(point_at_angle(
+                    center,
+                    radius,
+                    quarter * FRAC_PI_2,
+                ));
+                quarter += 1.;
+            }
+        }
+
+        points
+    }
+}
+
+/// The angle of a vector, in radians, as used by drawing formats
+pub(crate) fn angle(vector: Vector<2>) -> f64 {
+    vector.v.into_f64().atan2(vector.u.into_f64())
+}
+
+/// The point on a circle at the given angle, in radians
+pub(crate) fn point_at_angle(
+    center: Point<2>,
+    radius: Scalar,
+    angle: f64,
+) -> Point<2> {
+    center + Vector::from([angle.cos(), angle.sin()]) * radius
+}
+
+fn region_segments(
+    region: &Region,
+    frame: &Frame,
+) -> Result<Vec<Vec<Segment>>, Error> {
+    region
+        .all_cycles()
+        .map(|cycle| cycle_segments(cycle, frame))
+        .collect()
+}
+
+fn cycle_segments(cycle: &Cycle, frame: &Frame) -> Result<Vec<Segment>, Error> {
+    cycle
+        .half_edges()
+        .iter()
+        .map(|half_edge| {
+            let [t_start, t_end] = half_edge.boundary().inner;
+
+            let segment = match half_edge.path() {
+                SurfacePath::Line(line) => Segment::Line {
+                    start: frame.point(line.point_from_line_coords(t_start)),
+                    end: frame.point(line.point_from_line_coords(t_end)),
+                },
+                SurfacePath::Circle(circle) => {
+                    let center = frame.point(circle.center());
+                    let [a, b] = [circle.a(), circle.b()]
+                        .map(|vector| frame.vector(vector));
+
+                    // A circle can be distorted into an ellipse, if the
+                    // coordinate system of the surface is.
+                    let is_circle = (a.magnitude() - b.magnitude()).abs()
+                        <= a.magnitude() * RELATIVE_EPSILON
+                        && a.dot(&b).abs()
+                            <= a.magnitude() * b.magnitude() * RELATIVE_EPSILON;
+                    if !is_circle {
+                        return Err(Error::DrawingUnsupported {
+                            reason: "elliptical arcs are not supported",
+                        });
+                    }
+
+                    // The circle's coordinates run counter-clockwise, unless
+                    // the circle is mirrored.
+                    let direction = if a.cross2d(&b) > Scalar::ZERO {
+                        1.
+                    } else {
+                        -1.
+                    };
+
+                    Segment::Arc {
+                        center,
+                        radius: a.magnitude(),
+                        start: frame
+                            .point(circle.point_from_circle_coords(t_start)),
+                        end: frame
+                            .point(circle.point_from_circle_coords(t_end)),
+                        sweep: (t_end.t - t_start.t) * direction,
+                    }
+                }
+            };
+
+            Ok(segment)
+        })
+        .collect()
+}
+
+/// The coordinate system that a profile is drawn in
+enum Frame {
+    /// The coordinates of the profile are used as they are
+    Identity,
+
+    /// An orthonormal coordinate system within a plane
+    Plane {
+        surface: SurfaceGeometry,
+        origin: Point<3>,
+        axes: [Vector<3>; 2],
+    },
+}
+
+impl Frame {
+    fn from_surface(
+        surface: SurfaceGeometry,
+        handedness: Handedness,
+    ) -> Result<Self, Error> {
+        let GlobalPath::Line(u) = surface.u else {
+            return Err(Error::DrawingUnsupported {
+                reason: "face is not planar",
+            });
+        };
+
+        let normal = u.direction().cross(&surface.v);
+        if normal.magnitude() == Scalar::ZERO {
+            return Err(Error::DrawingUnsupported {
+                reason: "surface of face is degenerate",
+            });
+        }
+
+        // The front of a face with a left-handed coordinate system is on the
+        // back of its surface. Looking at it from there flips the y-axis.
+        let normal = match handedness {
+            Handedness::RightHanded => normal,
+            Handedness::LeftHanded => -normal,
+        };
+
+        let x = u.direction().normalize();
+        let y = normal.normalize().cross(&x);
+
+        Ok(Self::Plane {
+            surface,
+            origin: surface.point_from_surface_coords([0., 0.]),
+            axes: [x, y],
+        })
+    }
+
+    fn point(&self, point: Point<2>) -> Point<2> {
+        match self {
+            Self::Identity => point,
+            Self::Plane {
+                surface,
+                origin,
+                axes,
+            } => {
+                let point = surface.point_from_surface_coords(point);
+                Point::from(axes.map(|axis| (point - *origin).dot(&axis)))
+            }
+        }
+    }
+
+    fn vector(&self, vector: Vector<2>) -> Vector<2> {
+        match self {
+            Self::Identity => vector,
+            Self::Plane { surface, axes, .. } => {
+                let vector = surface.vector_from_surface_coords(vector);
+                Vector::from(axes.map(|axis| vector.dot(&axis)))
+            }
+        }
+    }
+}
+
+/// The relative tolerance for deciding whether a transformed circle is one
+const RELATIVE_EPSILON: f64 = 1e-9;
+
+/// The tolerance for comparing angles, in radians
+const ANGLE_EPSILON: f64 = 1e-9;
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use fj_core::{
+        geometry::{GlobalPath, SurfaceGeometry},
+        objects::{Cycle, Face, HalfEdge, Region, Surface},
+        operations::{build::BuildHalfEdge, insert::Insert, reverse::Reverse},
+        storage::Handle,
+        validate::ValidationConfig,
+        Instance,
+    };
+    use fj_math::{Point, Scalar};
+
+    use crate::Error;
+
+    use super::{Profile, Segment};
+
+    #[test]
+    fn reversed_face() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let surface = core.services.objects.surfaces.xy_plane();
+        let face = quarter_disc(surface, &mut core);
+        let reversed = face.reverse(&mut core);
+
+        let [front, back] = [&face, &reversed]
+            .map(|face| Profile::Face(face).regions(1.))
+            .map(|regions| {
+                regions.map(|mut regions| regions.remove(0).remove(0))
+            });
+        let [front, back] = [front?, back?];
+        core.services.drop_and_validate()?;
+
+        // The reversed face is seen from the other side, which mirrors it.
+        // Either way, the arc is counter-clockwise, like the exterior it is part
+        // of.
+        let mirrored = |point: Point<2>| Point::from([point.u, -point.v]);
+        let mut front_points = front
+            .iter()
+            .map(|segment| mirrored(segment.start()))
+            .collect::<Vec<_>>();
+        let mut back_points =
+            back.iter().map(Segment::start).collect::<Vec<_>>();
+        front_points.sort();
+        back_points.sort();
+        for (a, b) in front_points.into_iter().zip(back_points) {
+            assert!(a.distance_to(&b) < Scalar::from(1e-12), "{a:?} != {b:?}");
+        }
+
+        for segments in [front, back] {
+            let sweeps = segments
+                .iter()
+                .filter_map(|segment| match segment {
+                    Segment::Arc { sweep, .. } => Some(*sweep),
+                    Segment::Line { .. } => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(sweeps, [Scalar::from(FRAC_PI_2)]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn degenerate_surface() {
+        let mut core = Instance::new();
+
+        let (u, _) = GlobalPath::line_from_points([[0., 0., 0.], [1., 0., 0.]]);
+        let surface = Surface::new(SurfaceGeometry {
+            u,
+            v: [2., 0., 0.].into(),
+        })
+        .insert(&mut core.services);
+        let face = quarter_disc(surface, &mut core);
+
+        assert!(matches!(
+            Profile::Face(&face).regions(1.),
+            Err(Error::DrawingUnsupported { .. })
+        ));
+
+        // Validation rejects the surface too. Creating it anyway makes sure
+        // that export doesn't rely on that.
+        let report =
+            core.services.drop_and_report(&ValidationConfig::default());
+        assert!(!report.is_valid());
+    }
+
+    /// A quarter of a unit disc, in the first quadrant of the surface
+    fn quarter_disc(surface: Handle<Surface>, core: &mut Instance) -> Face {
+        let half_edges = [
+            HalfEdge::line_segment([[0., 0.], [1., 0.]], None, core),
+            HalfEdge::arc([1., 0.], [0., 1.], FRAC_PI_2, core),
+            HalfEdge::line_segment([[0., 1.], [0., 0.]], None, core),
+        ]
+        .map(|half_edge| half_edge.insert(&mut core.services));
+        let exterior = Cycle::new(half_edges).insert(&mut core.services);
+        let region = Region::new(exterior, [], None).insert(&mut core.services);
+
+        Face::new(surface, region)
+    }
+}
@@ -0,0 +1,994 @@
+//! STEP export
+//!
+//! Writes the boundary representation of a [`Solid`] to a STEP file, using the
+//! AP214 application protocol. Unlike the mesh-based formats, this preserves
+//! the exact geometry of the model.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use fj_core::{
+    algorithms::{
+        approx::Tolerance, bounding_volume::BoundingVolume,
+        triangulate::Triangulate,
+    },
+    geometry::{CurveBoundary, GlobalPath, SurfacePath},
+    objects::{Curve, Cycle, Face, HalfEdge, Handedness, Shell, Solid, Vertex},
+    storage::{Handle, HandleWrapper},
+};
+use fj_math::{Point, Scalar, Vector};
+
+use crate::{Error, ExportOptions, Unit};
+
+/// Export the provided solid to a STEP file at the given path
+///
+/// This function will create a file if it does not exist, and will truncate it
+/// if it does.
+///
+/// Each outer shell of the solid is written as a separate manifold solid. Shells
+/// that bound a cavity are written as voids of the smallest outer shell that
+/// contains them. Planar and cylindrical surfaces are supported, as well as
+/// straight and circular edges. Other geometry (like an edge that is a helix on
+/// a cylindrical surface) results in an error.
+///
+/// Fornjot models don't have units. Coordinates are interpreted as millimeters.
+pub fn export_step(solid: &Solid, path: &Path) -> Result<(), Error> {
+    export_step_with_options(solid, path, &ExportOptions::default())
+}
+
+/// Export the provided solid to a STEP file at the given path, using the
+/// options
+///
+/// This works like [`export_step`], except that the options control the length
+/// unit of the file, the scale, and the name and author that are written to
+/// it. If no name is provided, it is derived from the file name.
+pub fn export_step_with_options(
+    solid: &Solid,
+    path: &Path,
+    options: &ExportOptions,
+) -> Result<(), Error> {
+    let name = match &options.name {
+        Some(name) => name.clone(),
+        None => path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+
+    let mut file = BufWriter::new(File::create(path)?);
+    write_step(solid, &name, options, &mut file)?;
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Export the provided solid to the given writer, as STEP
+///
+/// See [`export_step_with_options`]. The name of the product that the file
+/// describes is left empty, if the options don't provide one.
+pub fn export_step_to_writer(
+    solid: &Solid,
+    options: &ExportOptions,
+    writer: impl Write,
+) -> Result<(), Error> {
+    let name = options.name.as_deref().unwrap_or_default();
+
+    let mut writer = BufWriter::new(writer);
+    write_step(solid, name, options, &mut writer)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn write_step(
+    solid: &Solid,
+    name: &str,
+    options: &ExportOptions,
+    writer: &mut impl Write,
+) -> Result<(), Error> {
+    let mut step = StepExporter::new(options.factor()?);
+    let context = step.write_context(name, options.unit);
+
+    let mut items = Vec::new();
+    for (shell, voids) in group_shells(solid) {
+        items.push(step.write_solid(shell, &voids)?);
+    }
+
+    let representation = step.entities.add(format!(
+        "ADVANCED_BREP_SHAPE_REPRESENTATION({},({}),{})",
+        string(name),
+        list(&items),
+        context.geometric_context,
+    ));
+    step.entities.add(format!(
+        "SHAPE_DEFINITION_REPRESENTATION({},{representation})",
+        context.product_definition_shape,
+    ));
+
+    let author = options.author.as_deref().unwrap_or_default();
+    write_header(writer, name, author)?;
+    step.entities.write(writer)?;
+    write_footer(writer)?;
+
+    Ok(())
+}
+
+fn write_header(
+    writer: &mut impl Write,
+    name: &str,
+    author: &str,
+) -> io::Result<()> {
+    writeln!(writer, "ISO-10303-21;")?;
+    writeln!(writer, "HEADER;")?;
+    writeln!(writer, "FILE_DESCRIPTION(('Fornjot model'),'2;1');")?;
+
+    // The organization is left empty. The two fields that follow it name the
+    // software that wrote the file, not the organization of the author.
+    writeln!(
+        writer,
+        "FILE_NAME({},'',({}),(''),'Fornjot','Fornjot','');",
+        string(name),
+        string(author),
+    )?;
+    writeln!(
+        writer,
+        "FILE_SCHEMA(('AUTOMOTIVE_DESIGN {{ 1 0 10303 214 1 1 1 1 }}'));"
+    )?;
+    writeln!(writer, "ENDSEC;")?;
+    writeln!(writer, "DATA;")?;
+
+    Ok(())
+}
+
+fn write_footer(writer: &mut impl Write) -> io::Result<()> {
+    writeln!(writer, "ENDSEC;")?;
+    writeln!(writer, "END-ISO-10303-21;")?;
+
+    Ok(())
+}
+
+struct StepExporter {
+    entities: Entities,
+    vertices: BTreeMap<HandleWrapper<Vertex>, Id>,
+    edges: BTreeMap<EdgeKey, (Id, CurveBoundary<Point<1>>)>,
+}
+
+/// Identifies the edge that a half-edge and its sibling share
+type EdgeKey = (HandleWrapper<Curve>, CurveBoundary<Point<1>>);
+
+impl StepExporter {
+    /// Create an exporter that multiplies all coordinates and lengths by
+    /// `factor`
+    fn new(factor: f64) -> Self {
+        Self {
+            entities: Entities {
+                inner: Vec::new(),
+                factor,
+            },
+            vertices: BTreeMap::new(),
+            edges: BTreeMap::new(),
+        }
+    }
+
+    /// Write the entities that define the product and its units
+    fn write_context(&mut self, name: &str, unit: Unit) -> Context {
+        let e = &mut self.entities;
+
+        let application_context = e.add(
+            "APPLICATION_CONTEXT('core data for automotive mechanical design \
+            processes')",
+        );
+        e.add(format!(
+            "APPLICATION_PROTOCOL_DEFINITION('international standard',\
+            'automotive_design',2000,{application_context})"
+        ));
+        let product_context = e.add(format!(
+            "PRODUCT_CONTEXT('',{application_context},'mechanical')"
+        ));
+        let product = e.add(format!(
+            "PRODUCT({name},{name},'',({product_context}))",
+            name = string(name),
+        ));
+        e.add(format!(
+            "PRODUCT_RELATED_PRODUCT_CATEGORY('part',$,({product}))"
+        ));
+        let formation =
+            e.add(format!("PRODUCT_DEFINITION_FORMATION('','',{product})"));
+        let definition_context = e.add(format!(
+            "PRODUCT_DEFINITION_CONTEXT('part definition',\
+            {application_context},'design')"
+        ));
+        let definition = e.add(format!(
+            "PRODUCT_DEFINITION('design','',{formation},{definition_context})"
+        ));
+        let product_definition_shape =
+            e.add(format!("PRODUCT_DEFINITION_SHAPE('','',{definition})"));
+
+        let si_length_unit = |prefix| {
+            format!("(LENGTH_UNIT()NAMED_UNIT(*)SI_UNIT({prefix},.METRE.))")
+        };
+        let conversion_based_length_unit =
+            |e: &mut Entities, name: &str, millimeters: f64| {
+                let millimeter = e.add(si_length_unit(".MILLI."));
+                let measure = e.add(format!(
+                    "LENGTH_MEASURE_WITH_UNIT(LENGTH_MEASURE({}),{millimeter})",
+                    real(millimeters),
+                ));
+                let dimensions =
+                    e.add("DIMENSIONAL_EXPONENTS(1.,0.,0.,0.,0.,0.,0.)");
+                e.add(format!(
+                    "(CONVERSION_BASED_UNIT({},{measure})LENGTH_UNIT()\
+                    NAMED_UNIT({dimensions}))",
+                    string(name),
+                ))
+            };
+        let length_unit = match unit {
+            Unit::Micron => e.add(si_length_unit(".MICRO.")),
+            Unit::Millimeter => e.add(si_length_unit(".MILLI.")),
+            Unit::Centimeter => e.add(si_length_unit(".CENTI.")),
+            Unit::Meter => e.add(si_length_unit("$")),
+            Unit::Inch => conversion_based_length_unit(e, "INCH", 25.4),
+            Unit::Foot => conversion_based_length_unit(e, "FOOT", 304.8),
+        };
+        let angle_unit =
+            e.add("(NAMED_UNIT(*)PLANE_ANGLE_UNIT()SI_UNIT($,.RADIAN.))");
+        let solid_angle_unit =
+            e.add("(NAMED_UNIT(*)SI_UNIT($,.STERADIAN.)SOLID_ANGLE_UNIT())");
+        let uncertainty = e.add(format!(
+            "UNCERTAINTY_MEASURE_WITH_UNIT(LENGTH_MEASURE(1.E-07),\
+            {length_unit},'distance_accuracy_value','confusion accuracy')"
+        ));
+        let geometric_context = e.add(format!(
+            "(GEOMETRIC_REPRESENTATION_CONTEXT(3)\
+            GLOBAL_UNCERTAINTY_ASSIGNED_CONTEXT(({uncertainty}))\
+            GLOBAL_UNIT_ASSIGNED_CONTEXT(\
+            ({length_unit},{angle_unit},{solid_angle_unit}))\
+            REPRESENTATION_CONTEXT('',''))"
+        ));
+
+        Context {
+            product_definition_shape,
+            geometric_context,
+        }
+    }
+
+    fn write_solid(
+        &mut self,
+        shell: &Shell,
+        voids: &[&Handle<Shell>],
+    ) -> Result<Id, Error> {
+        let shell = self.write_shell(shell, false)?;
+
+        if voids.is_empty() {
+            return Ok(self
+                .entities
+                .add(format!("MANIFOLD_SOLID_BREP('',{shell})")));
+        }
+
+        // The shell of a void is written facing into the void, like it would
+        // be, if it were an outer shell. The reference to it then reverses it
+        // again, so it faces away from the material.
+        let mut oriented_voids = Vec::new();
+        for void in voids {
+            let void = self.write_shell(void, true)?;
+            oriented_voids.push(
+                self.entities
+                    .add(format!("ORIENTED_CLOSED_SHELL('',*,{void},.F.)")),
+            );
+        }
+
+        Ok(self.entities.add(format!(
+            "BREP_WITH_VOIDS('',{shell},({}))",
+            list(&oriented_voids),
+        )))
+    }
+
+    /// Write a shell, optionally reversing all of its faces
+    fn write_shell(
+        &mut self,
+        shell: &Shell,
+        reversed: bool,
+    ) -> Result<Id, Error> {
+        let mut faces = Vec::new();
+        for face in shell.faces() {
+            faces.push(self.write_face(face, reversed)?);
+        }
+
+        Ok(self
+            .entities
+            .add(format!("CLOSED_SHELL('',({}))", list(&faces))))
+    }
+
+    fn write_face(
+        &mut self,
+        face: &Handle<Face>,
+        reversed: bool,
+    ) -> Result<Id, Error> {
+        if face.region().exterior().try_winding().is_none() {
+            return Err(Error::StepUnsupportedFace {
+                face: face.clone(),
+                reason: "winding of exterior cycle is undefined",
+            });
+        }
+
+        let (surface, surface_sense) = self.write_surface(face)?;
+
+        let mut bounds = Vec::new();
+        bounds.push(self.write_cycle(
+            face,
+            face.region().exterior(),
+            "FACE_OUTER_BOUND",
+            reversed,
+        )?);
+        for interior in face.region().interiors() {
+            bounds.push(self.write_cycle(
+                face,
+                interior,
+                "FACE_BOUND",
+                reversed,
+            )?);
+        }
+
+        // The front side of a face is the side from which its exterior cycle
+        // appears counter-clockwise. From which side of the surface that is,
+        // depends on the face's coordinate system.
+        let front_is_surface_normal =
+            face.coord_handedness() == Handedness::RightHanded;
+
+        Ok(self.entities.add(format!(
+            "ADVANCED_FACE('',({}),{surface},{})",
+            list(&bounds),
+            boolean((front_is_surface_normal == surface_sense) != reversed),
+        )))
+    }
+
+    /// Write the surface of a face
+    ///
+    /// Also returns whether the normal of the written surface points to the
+    /// same side as the normal of the face's surface coordinate system.
+    fn write_surface(
+        &mut self,
+        face: &Handle<Face>,
+    ) -> Result<(Id, bool), Error> {
+        let geometry = face.surface().geometry();
+        let unsupported = |reason| Error::StepUnsupportedFace {
+            face: face.clone(),
+            reason,
+        };
+
+        match geometry.u {
+            GlobalPath::Line(line) => {
+                let normal = line.direction().cross(&geometry.v);
+                let reference =
+                    line.direction().magnitude() * geometry.v.magnitude();
+                if is_zero(normal.magnitude(), reference) {
+                    return Err(unsupported("surface axes are parallel"));
+                }
+
+                let placement = self.entities.placement(
+                    line.origin(),
+                    normal,
+                    line.direction(),
+                );
+                let plane = self.entities.add(format!("PLANE('',{placement})"));
+
+                Ok((plane, true))
+            }
+            GlobalPath::Circle(circle) => {
+                let axis = circle.a().cross(&circle.b());
+                let reference = geometry.v.magnitude() * axis.magnitude();
+                if !is_zero(geometry.v.cross(&axis).magnitude(), reference) {
+                    return Err(unsupported(
+                        "v-axis is not perpendicular to circle",
+                    ));
+                }
+
+                let placement =
+                    self.entities.placement(circle.center(), axis, circle.a());
+                let radius = self.entities.length(circle.radius());
+                let cylinder = self.entities.add(format!(
+                    "CYLINDRICAL_SURFACE('',{placement},{radius})"
+                ));
+
+                // The normal of a cylindrical surface points outward. The
+                // normal of the face's surface coordinate system points
+                // outward too, if going along the u-axis is a
+                // counter-clockwise rotation around the v-axis.
+                let surface_sense = geometry.v.dot(&axis) > Scalar::ZERO;
+
+                Ok((cylinder, surface_sense))
+            }
+        }
+    }
+
+    fn write_cycle(
+        &mut self,
+        face: &Handle<Face>,
+        cycle: &Handle<Cycle>,
+        bound: &str,
+        reversed: bool,
+    ) -> Result<Id, Error> {
+        let mut oriented_edges = Vec::new();
+
+        for (half_edge, next) in cycle.half_edges().pairs() {
+            let (edge, boundary) =
+                self.write_edge(face, half_edge, next.start_vertex())?;
+            let orientation = half_edge.boundary() == boundary;
+
+            oriented_edges.push(self.entities.add(format!(
+                "ORIENTED_EDGE('',*,*,{edge},{})",
+                boolean(orientation),
+            )));
+        }
+
+        let edge_loop = self
+            .entities
+            .add(format!("EDGE_LOOP('',({}))", list(&oriented_edges)));
+
+        Ok(self
+            .entities
+            .add(format!("{bound}('',{edge_loop},{})", boolean(!reversed),)))
+    }
+
+    /// Write the edge that a half-edge is a part of
+    ///
+    /// A half-edge and its sibling share the same edge, which is only written
+    /// once. Returns the edge, along with the boundary of the half-edge it was
+    /// written for. The direction of the edge is the direction of that
+    /// half-edge.
+    fn write_edge(
+        &mut self,
+        face: &Handle<Face>,
+        half_edge: &Handle<HalfEdge>,
+        end_vertex: &Handle<Vertex>,
+    ) -> Result<(Id, CurveBoundary<Point<1>>), Error> {
+        let key = (
+            HandleWrapper::from(half_edge.curve().clone()),
+            half_edge.boundary().normalize(),
+        );
+        if let Some(edge) = self.edges.get(&key) {
+            return Ok(*edge);
+        }
+
+        let (curve, curve_sense) = self.write_curve(face, half_edge)?;
+        let start =
+            self.write_vertex(face, half_edge, half_edge.start_vertex());
+        let end = self.write_vertex(face, half_edge, end_vertex);
+
+        let [a, b] = half_edge.boundary().inner;
+        let same_sense = (a < b) == curve_sense;
+
+        let edge = self.entities.add(format!(
+            "EDGE_CURVE('',{start},{end},{curve},{})",
+            boolean(same_sense),
+        ));
+
+        let edge = (edge, half_edge.boundary());
+        self.edges.insert(key, edge);
+
+        Ok(edge)
+    }
+
+    /// Write the curve that a half-edge is defined on
+    ///
+    /// Also returns whether increasing the curve coordinate of the half-edge
+    /// means going along the direction of the written curve.
+    fn write_curve(
+        &mut self,
+        face: &Handle<Face>,
+        half_edge: &Handle<HalfEdge>,
+    ) -> Result<(Id, bool), Error> {
+        let surface = face.surface().geometry();
+        let unsupported = |reason| Error::StepUnsupportedCurve {
+            half_edge: half_edge.clone(),
+            reason,
+        };
+
+        match (half_edge.path(), surface.u) {
+            (SurfacePath::Line(line), GlobalPath::Line(_)) => {
+                let origin = surface.point_from_surface_coords(line.origin());
+                let direction =
+                    surface.vector_from_surface_coords(line.direction());
+
+                Ok((self.entities.line(origin, direction), true))
+            }
+            (SurfacePath::Line(line), GlobalPath::Circle(circle)) => {
+                let direction = line.direction();
+
+                if direction.u == Scalar::ZERO {
+                    // The line goes along the v-axis of the cylinder.
+                    let origin =
+                        surface.point_from_surface_coords(line.origin());
+                    let direction = surface.v * direction.v;
+
+                    Ok((self.entities.line(origin, direction), true))
+                } else if direction.v == Scalar::ZERO {
+                    // The line goes around the cylinder, at a constant height.
+                    let center = circle.center() + surface.v * line.origin().v;
+                    let curve = self.entities.circle(
+                        center,
+                        circle.a(),
+                        circle.b(),
+                        circle.radius(),
+                    );
+
+                    Ok((curve, direction.u > Scalar::ZERO))
+                } else {
+                    Err(unsupported("helix on cylindrical surface"))
+                }
+            }
+            (SurfacePath::Circle(path), GlobalPath::Line(_)) => {
+                let center = surface.point_from_surface_coords(path.center());
+                let a = surface.vector_from_surface_coords(path.a());
+                let b = surface.vector_from_surface_coords(path.b());
+
+                let radius = a.magnitude();
+                let is_circle = is_zero(a.dot(&b), radius * radius)
+                    && is_zero(b.magnitude() - radius, radius);
+                if !is_circle {
+                    return Err(unsupported("circle is distorted by surface"));
+                }
+
+                Ok((self.entities.circle(center, a, b, radius), true))
+            }
+            (SurfacePath::Circle(_), GlobalPath::Circle(_)) => {
+                Err(unsupported("circle on cylindrical surface"))
+            }
+        }
+    }
+
+    fn write_vertex(
+        &mut self,
+        face: &Handle<Face>,
+        half_edge: &Handle<HalfEdge>,
+        vertex: &Handle<Vertex>,
+    ) -> Id {
+        let key = HandleWrapper::from(vertex.clone());
+        if let Some(id) = self.vertices.get(&key) {
+            return *id;
+        }
+
+        // If this is the start vertex of the half-edge, that's where it is
+        // located. Otherwise, it's the end vertex, located at the other end.
+        let [start, end] = half_edge
+            .boundary()
+            .inner
+            .map(|point| half_edge.path().point_from_path_coords(point));
+        let position = if vertex.id() == half_edge.start_vertex().id() {
+            start
+        } else {
+            end
+        };
+        let position = face
+            .surface()
+            .geometry()
+            .point_from_surface_coords(position);
+
+        let point = self.entities.cartesian_point(position);
+        let id = self.entities.add(format!("VERTEX_POINT('',{point})"));
+        self.vertices.insert(key, id);
+
+        id
+    }
+}
+
+/// Group the shells of a solid into outer shells and the voids within them
+///
+/// A shell that faces inward bounds a cavity. It becomes a void of the smallest
+/// outer shell whose bounding box contains it. Shells whose orientation can't
+/// be determined are treated as outer shells.
+fn group_shells(solid: &Solid) -> Vec<(&Handle<Shell>, Vec<&Handle<Shell>>)> {
+    let (voids, outer): (Vec<_>, Vec<_>) = solid
+        .shells()
+        .iter()
+        .map(|shell| (shell, shell.aabb()))
+        .partition(|(shell, _)| is_cavity(shell));
+
+    let mut groups = outer
+        .iter()
+        .map(|&(shell, _)| (shell, Vec::new()))
+        .collect::<Vec<_>>();
+
+    for (void, void_aabb) in voids {
+        let container = void_aabb.and_then(|void_aabb| {
+            outer
+                .iter()
+                .enumerate()
+                .filter_map(|(i, (_, aabb))| {
+                    let aabb = (*aabb)?;
+                    let contains = aabb.contains(void_aabb.min)
+                        && aabb.contains(void_aabb.max);
+                    contains.then_some((i, aabb.size().magnitude()))
+                })
+                .min_by_key(|&(_, size)| size)
+                .map(|(i, _)| i)
+        });
+
+        match container {
+            Some(i) => groups[i].1.push(void),
+            // A void that isn't within any outer shell doesn't make sense in
+            // STEP. Write it as it is, and leave it to the reader to complain.
+            None => groups.push((void, Vec::new())),
+        }
+    }
+
+    groups
+}
+
+/// Indicate whether a shell bounds a cavity, meaning it faces inward
+fn is_cavity(shell: &Shell) -> bool {
+    // Only the orientation of the triangles matters here, so a coarse
+    // approximation is good enough.
+    let Some(size) = shell
+        .aabb()
+        .and_then(|aabb| aabb.size().components.into_iter().max())
+    else {
+        return false;
+    };
+    let Ok(tolerance) = Tolerance::from_scalar(size * Scalar::from_f64(1e-2))
+    else {
+        return false;
+    };
+    let Ok(mesh) = (shell, tolerance).triangulate() else {
+        return false;
+    };
+
+    let volume = mesh
+        .triangles()
+        .map(|triangle| {
+            let [a, b, c] = triangle.inner.points().map(|point| point.coords);
+            a.dot(&b.cross(&c))
+        })
+        .fold(Scalar::ZERO, |sum, volume| sum + volume);
+
+    volume < Scalar::ZERO
+}
+
+struct Context {
+    product_definition_shape: Id,
+    geometric_context: Id,
+}
+
+/// The entities of a STEP file
+struct Entities {
+    inner: Vec<String>,
+
+    /// The factor that all coordinates and lengths are multiplied by
+    factor: f64,
+}
+
+impl Entities {
+    fn add(&mut self, entity: impl Into<String>) -> Id {
+        self.inner.push(entity.into());
+        Id(self.inner.len())
+    }
+
+    fn cartesian_point(&mut self, point: Point<3>) -> Id {
+        self.add(format!(
+            "CARTESIAN_POINT('',({}))",
+            point
+                .coords
+                .components
+                .map(|coord| self.length(coord))
+                .join(","),
+        ))
+    }
+
+    fn direction(&mut self, direction: Vector<3>) -> Id {
+        self.add(format!(
+            "DIRECTION('',({}))",
+            direction.normalize().components.map(real).join(","),
+        ))
+    }
+
+    fn placement(
+        &mut self,
+        location: Point<3>,
+        axis: Vector<3>,
+        ref_direction: Vector<3>,
+    ) -> Id {
+        let location = self.cartesian_point(location);
+        let axis = self.direction(axis);
+        let ref_direction = self.direction(ref_direction);
+
+        self.add(format!(
+            "AXIS2_PLACEMENT_3D('',{location},{axis},{ref_direction})"
+        ))
+    }
+
+    fn line(&mut self, origin: Point<3>, direction: Vector<3>) -> Id {
+        let origin = self.cartesian_point(origin);
+        let magnitude = direction.magnitude();
+        let direction = self.direction(direction);
+        let vector = self
+            .add(format!("VECTOR('',{direction},{})", self.length(magnitude)));
+
+        self.add(format!("LINE('',{origin},{vector})"))
+    }
+
+    /// Write a circle
+    ///
+    /// Going from `a` towards `b` is the direction of the circle.
+    fn circle(
+        &mut self,
+        center: Point<3>,
+        a: Vector<3>,
+        b: Vector<3>,
+        radius: Scalar,
+    ) -> Id {
+        let placement = self.placement(center, a.cross(&b), a);
+        self.add(format!("CIRCLE('',{placement},{})", self.length(radius)))
+    }
+
+    /// Format a coordinate or length, converting it into the exported unit
+    fn length(&self, value: Scalar) -> String {
+        real(value * self.factor)
+    }
+
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        for (i, entity) in self.inner.iter().enumerate() {
+            writeln!(writer, "{}={entity};", Id(i + 1))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The identifier of an entity in a STEP file
+#[derive(Clone, Copy, Debug)]
+struct Id(usize);
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+fn list(ids: &[Id]) -> String {
+    ids.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn boolean(value: bool) -> &'static str {
+    if value {
+        ".T."
+    } else {
+        ".F."
+    }
+}
+
+fn string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Format a real number, as STEP expects it
+///
+/// STEP requires a decimal point in every real number, and an upper-case
+/// exponent marker.
+fn real(value: impl Into<Scalar>) -> String {
+    let value = format!("{:?}", value.into().into_f64());
+
+    match value.split_once('e') {
+        Some((mantissa, exponent)) if mantissa.contains('.') => {
+            format!("{mantissa}E{exponent}")
+        }
+        Some((mantissa, exponent)) => format!("{mantissa}.E{exponent}"),
+        None => value,
+    }
+}
+
+/// Indicate whether a value is zero, relative to a reference value
+fn is_zero(value: Scalar, reference: Scalar) -> bool {
+    value.abs() <= reference.abs() * Scalar::from_f64(RELATIVE_EPSILON)
+}
+
+/// The tolerance used for checks on geometry, relative to its size
+const RELATIVE_EPSILON: f64 = 1e-9;
+
+#[cfg(test)]
+mod tests {
+    use fj_core::{
+        geometry::{GlobalPath, SurfaceGeometry},
+        objects::{Cycle, Face, Region, Shell, Solid, Surface},
+        operations::{
+            build::{BuildCycle, BuildShell},
+            insert::Insert,
+            reverse::Reverse,
+        },
+        storage::Handle,
+        validate::ValidationConfig,
+        Instance,
+    };
+
+    use crate::{Error, ExportOptions, Unit};
+
+    use super::export_step_to_writer;
+
+    #[test]
+    fn export_tetrahedron_with_void() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let solid = tetrahedron_with_void(&mut core);
+
+        let mut step = Vec::new();
+        let options = ExportOptions {
+            name: Some("tetrahedron".into()),
+            ..ExportOptions::default()
+        };
+        export_step_to_writer(&solid, &options, &mut step)?;
+
+        assert_eq!(
+            String::from_utf8(step)?,
+            include_str!("../fixtures/tetrahedron_with_void.step"),
+        );
+
+        core.services.drop_and_validate()?;
+        Ok(())
+    }
+
+    #[test]
+    fn options() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let solid = tetrahedron_with_void(&mut core);
+
+        let mut step = Vec::new();
+        let options = ExportOptions {
+            unit: Unit::Inch,
+            scale: 2.54,
+            author: Some("Fornjot contributors".into()),
+            ..ExportOptions::default()
+        };
+        export_step_to_writer(&solid, &options, &mut step)?;
+        let step = String::from_utf8(step)?;
+
+        assert!(step.contains(
+            "FILE_NAME('','',('Fornjot contributors'),(''),'Fornjot','Fornjot','');"
+        ));
+        assert!(step.contains("CONVERSION_BASED_UNIT('INCH',"));
+        assert!(step.contains("LENGTH_MEASURE(25.4)"));
+
+        // The outer tetrahedron has a size of 4 millimeters. Scaled by 2.54,
+        // that's 0.4 inches.
+        let coordinates = step
+            .lines()
+            .filter_map(|line| {
+                line.split_once("CARTESIAN_POINT('',(")?.1.split_once(')')
+            })
+            .flat_map(|(coordinates, _)| coordinates.split(','))
+            .map(|coordinate| coordinate.parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let max = coordinates.into_iter().fold(0., f64::max);
+        assert!((max - 0.4).abs() < 1e-12, "unexpected size: {max}");
+
+        core.services.drop_and_validate()?;
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_scale() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let solid = tetrahedron_with_void(&mut core);
+
+        let options = ExportOptions {
+            scale: 0.,
+            ..ExportOptions::default()
+        };
+        let result = export_step_to_writer(&solid, &options, Vec::new());
+        assert!(matches!(result, Err(Error::InvalidScale(_))));
+
+        core.services.drop_and_validate()?;
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_curve() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        // The last edge of this triangle goes around the cylinder and along
+        // its axis at the same time, making it a helix.
+        let surface = Surface::new(SurfaceGeometry {
+            u: GlobalPath::circle_from_radius(1.),
+            v: [0., 0., 1.].into(),
+        })
+        .insert(&mut core.services);
+        let exterior =
+            Cycle::polygon([[0., 0.], [1., 0.], [1., 1.]], &mut core)
+                .insert(&mut core.services);
+
+        let result = export(surface, exterior, core);
+        assert!(matches!(
+            result,
+            Err(Error::StepUnsupportedCurve {
+                reason: "helix on cylindrical surface",
+                ..
+            }),
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_face() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        // A cylinder whose axis is slanted.
+        let surface = Surface::new(SurfaceGeometry {
+            u: GlobalPath::circle_from_radius(1.),
+            v: [1., 0., 1.].into(),
+        })
+        .insert(&mut core.services);
+        let exterior =
+            Cycle::polygon([[0., 0.], [1., 0.], [0., 1.]], &mut core)
+                .insert(&mut core.services);
+
+        let result = export(surface, exterior, core);
+        assert!(matches!(
+            result,
+            Err(Error::StepUnsupportedFace {
+                reason: "v-axis is not perpendicular to circle",
+                ..
+            }),
+        ));
+
+        Ok(())
+    }
+
+    fn tetrahedron_with_void(core: &mut Instance) -> Handle<Solid> {
+        let [outer, inner] = [
+            [[0., 0., 0.], [0., 4., 0.], [4., 0., 0.], [0., 0., 4.]],
+            [
+                [0.5, 0.5, 0.5],
+                [0.5, 1., 0.5],
+                [1., 0.5, 0.5],
+                [0.5, 0.5, 1.],
+            ],
+        ]
+        .map(|points| {
+            Shell::tetrahedron(points, core)
+                .shell
+                .insert(&mut core.services)
+        });
+        let void = Shell::new(
+            inner
+                .faces()
+                .iter()
+                .map(|face| face.reverse(core).insert(&mut core.services)),
+        )
+        .insert(&mut core.services);
+
+        Solid::new([outer, void]).insert(&mut core.services)
+    }
+
+    /// Export a solid made of a single face
+    ///
+    /// The solid is not valid, but that doesn't matter for testing the handling
+    /// of unsupported geometry.
+    fn export(
+        surface: Handle<Surface>,
+        exterior: Handle<Cycle>,
+        mut core: Instance,
+    ) -> Result<(), Error> {
+        let region = Region::new(exterior, [], None).insert(&mut core.services);
+        let face = Face::new(surface, region).insert(&mut core.services);
+        let shell = Shell::new([face]).insert(&mut core.services);
+        let solid = Solid::new([shell]);
+
+        let result = export_step_to_writer(
+            &solid,
+            &ExportOptions::default(),
+            Vec::new(),
+        );
+        core.services.drop_and_report(&ValidationConfig::default());
+
+        result
+    }
+}
@@ -0,0 +1,325 @@
+//! SVG export
+//!
+//! See [`export_svg`].
+
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use fj_math::{Aabb, Point, Scalar};
+
+use crate::{
+    profile::{angle, point_at_angle, Profile, Segment},
+    Error, ExportOptions, Unit,
+};
+
+/// Export the provided profile to an SVG file at the given path
+///
+/// This function will create a file if it does not exist, and will truncate it
+/// if it does.
+///
+/// Each region becomes a path, with a closed subpath for each of its cycles.
+/// Circular edges are written as arcs. Coordinates are interpreted as
+/// millimeters, which is reflected in the size of the document.
+pub fn export_svg<'r>(
+    profile: impl Into<Profile<'r>>,
+    path: &Path,
+) -> Result<(), Error> {
+    export_svg_with_options(profile, path, &ExportOptions::default())
+}
+
+/// Export the provided profile to an SVG file at the given path, using the
+/// options
+///
+/// This works like [`export_svg`], except that the options control the unit of
+/// the coordinates, and the scale. The size of the document reflects both. The
+/// name is written as the title of the document, the author as its creator.
+pub fn export_svg_with_options<'r>(
+    profile: impl Into<Profile<'r>>,
+    path: &Path,
+    options: &ExportOptions,
+) -> Result<(), Error> {
+    let file = File::create(path)?;
+    export_svg_to_writer(profile, options, file)
+}
+
+/// Export the provided profile to the given writer, as SVG
+///
+/// See [`export_svg_with_options`].
+pub fn export_svg_to_writer<'r>(
+    profile: impl Into<Profile<'r>>,
+    options: &ExportOptions,
+    writer: impl Write,
+) -> Result<(), Error> {
+    let regions = profile.into().regions(options.factor()?)?;
+
+    let points = regions
+        .iter()
+        .flatten()
+        .flatten()
+        .flat_map(Segment::extreme_points)
+        .collect::<Vec<_>>();
+    let aabb = if points.is_empty() {
+        Aabb {
+            min: Point::origin(),
+            max: Point::origin(),
+        }
+    } else {
+        Aabb::<2>::from_points(points)
+    };
+    let [width, height] = (aabb.max - aabb.min).components;
+
+    // SVG only supports some units for the size of the document. Others are
+    // converted into one of those.
+    let (unit, unit_factor) = match options.unit {
+        Unit::Micron => ("mm", 0.001),
+        Unit::Millimeter => ("mm", 1.),
+        Unit::Centimeter => ("cm", 1.),
+        Unit::Meter => ("cm", 100.),
+        Unit::Inch => ("in", 1.),
+        Unit::Foot => ("in", 12.),
+    };
+    let [document_width, document_height] =
+        [width, height].map(|length| length * unit_factor);
+
+    // The stroke is 0.1 mm wide, regardless of unit and scale.
+    let stroke_width = 0.1 / options.unit.millimeters();
+
+    let mut file = BufWriter::new(writer);
+
+    writeln!(file, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+
+    // SVG's y-axis points down, so all y-coordinates are negated.
+    writeln!(
+        file,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{document_width}{unit}" height="{document_height}{unit}" viewBox="{} {} {width} {height}">"#,
+        aabb.min.u,
+        Scalar::ZERO - aabb.max.v,
+    )?;
+
+    if let Some(name) = &options.name {
+        writeln!(file, "  <title>{}</title>", escape(name))?;
+    }
+    if let Some(author) = &options.author {
+        writeln!(file, "  <metadata>")?;
+        writeln!(
+            file,
+            r#"    <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#" xmlns:dc="http://purl.org/dc/elements/1.1/">"#
+        )?;
+        writeln!(
+            file,
+            r#"      <rdf:Description dc:creator="{}" />"#,
+            escape(author)
+        )?;
+        writeln!(file, "    </rdf:RDF>")?;
+        writeln!(file, "  </metadata>")?;
+    }
+
+    for cycles in &regions {
+        let mut data = String::new();
+
+        for cycle in cycles {
+            let Some(first) = cycle.first() else {
+                continue;
+            };
+
+            let _ = write!(data, "M {} ", coords(first.start()));
+
+            for segment in cycle {
+                match *segment {
+                    Segment::Line { end, .. } => {
+                        let _ = write!(data, "L {} ", coords(end));
+                    }
+                    Segment::Arc {
+                        center,
+                        radius,
+                        start,
+                        end,
+                        sweep,
+                    } => {
+                        // An arc command can't describe a full circle, as its
+                        // start and end points would be the same. Split it in
+                        // half.
+                        let ends = if segment.is_full_circle() {
+                            let angle =
+                                angle(start - center) + sweep.into_f64() / 2.;
+                            let middle = point_at_angle(center, radius, angle);
+
+                            vec![(middle, sweep / 2.), (end, sweep / 2.)]
+                        } else {
+                            vec![(end, sweep)]
+                        };
+
+                        for (end, sweep) in ends {
+                            let large_arc = u8::from(sweep.abs() > Scalar::PI);
+
+                            // With the y-axis flipped, counter-clockwise arcs
+                            // run in the negative angle direction.
+                            let positive = u8::from(sweep < Scalar::ZERO);
+
+                            let _ = write!(
+                                data,
+                                "A {radius} {radius} 0 {large_arc} {positive} {} ",
+                                coords(end),
+                            );
+                        }
+                    }
+                }
+            }
+
+            data.push_str("Z ");
+        }
+
+        writeln!(
+            file,
+            r#"  <path d="{}" fill="none" stroke="black" stroke-width="{stroke_width}" />"#,
+            data.trim_end(),
+        )?;
+    }
+
+    writeln!(file, "</svg>")?;
+    file.flush()?;
+
+    Ok(())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn coords(point: Point<2>) -> String {
+    // Subtracting from zero, instead of negating, avoids writing `-0`.
+    format!("{} {}", point.u, Scalar::ZERO - point.v)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use fj_core::{
+        objects::{Cycle, Face, HalfEdge, Region},
+        operations::{
+            build::{BuildCycle, BuildHalfEdge},
+            insert::Insert,
+            reverse::Reverse,
+        },
+        Instance,
+    };
+
+    use crate::{ExportOptions, Unit};
+
+    use super::export_svg_to_writer;
+
+    #[test]
+    fn arcs() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let face = quarter_disc_with_hole(&mut core);
+
+        let mut svg = Vec::new();
+        export_svg_to_writer(&face, &ExportOptions::default(), &mut svg)?;
+        let svg = String::from_utf8(svg)?;
+
+        let data = svg
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(r#"<path d=""#))
+            .and_then(|line| line.split_once('"'))
+            .map(|(data, _)| rounded(data))
+            .unwrap();
+
+        // The exterior runs counter-clockwise, the hole clockwise. As the
+        // y-axis is flipped, that's the other way around in the sweep flags.
+        // The hole is a full circle, which is split into two arcs.
+        assert_eq!(
+            data,
+            "M 0 0 L 1 0 A 1 1 0 0 0 0 -1 L 0 0 Z \
+            M 0.6 -0.4 A 0.2 0.2 0 0 1 0.2 -0.4 A 0.2 0.2 0 0 1 0.6 -0.4 Z",
+        );
+
+        let view_box = svg
+            .split_once(r#"viewBox=""#)
+            .and_then(|(_, rest)| rest.split_once('"'))
+            .map(|(view_box, _)| rounded(view_box))
+            .unwrap();
+        assert_eq!(view_box, "0 -1 1 1");
+
+        core.services.drop_and_validate()?;
+        Ok(())
+    }
+
+    #[test]
+    fn options() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let face = quarter_disc_with_hole(&mut core);
+
+        let options = ExportOptions {
+            unit: Unit::Centimeter,
+            scale: 2.,
+            name: Some("Quarter <disc>".into()),
+            author: Some("A & B".into()),
+            ..ExportOptions::default()
+        };
+        let mut svg = Vec::new();
+        export_svg_to_writer(&face, &options, &mut svg)?;
+        let svg = String::from_utf8(svg)?;
+
+        let attribute = |name: &str| {
+            svg.split_once(&format!(r#" {name}=""#))
+                .and_then(|(_, rest)| rest.split_once('"'))
+                .map(|(value, _)| rounded(value))
+                .unwrap()
+        };
+
+        // Scaled by 2, and converted to centimeters.
+        for name in ["width", "height"] {
+            let value = attribute(name);
+            assert_eq!(
+                value.strip_suffix("cm").map(rounded),
+                Some("0.2".into())
+            );
+        }
+        assert_eq!(attribute("viewBox"), "0 -0.2 0.2 0.2");
+        assert_eq!(attribute("stroke-width"), "0.01");
+
+        assert!(svg.contains("<title>Quarter &lt;disc&gt;</title>"));
+        assert!(svg.contains(r#"dc:creator="A &amp; B""#));
+
+        core.services.drop_and_validate()?;
+        Ok(())
+    }
+
+    fn quarter_disc_with_hole(core: &mut Instance) -> Face {
+        let half_edges = [
+            HalfEdge::line_segment([[0., 0.], [1., 0.]], None, core),
+            HalfEdge::arc([1., 0.], [0., 1.], FRAC_PI_2, core),
+            HalfEdge::line_segment([[0., 1.], [0., 0.]], None, core),
+        ]
+        .map(|half_edge| half_edge.insert(&mut core.services));
+        let exterior = Cycle::new(half_edges).insert(&mut core.services);
+        let hole = Cycle::circle([0.4, 0.4], 0.2, core)
+            .reverse(core)
+            .insert(&mut core.services);
+        let region =
+            Region::new(exterior, [hole], None).insert(&mut core.services);
+
+        Face::new(core.services.objects.surfaces.xy_plane(), region)
+    }
+
+    /// Round the numbers in the path data, to make them comparable
+    fn rounded(data: &str) -> String {
+        data.split(' ')
+            .map(|token| match token.parse::<f64>() {
+                Ok(value) => ((value * 1e9).round() / 1e9 + 0.).to_string(),
+                Err(_) => token.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
@@ -0,0 +1,415 @@
+//! 3MF export
+//!
+//! Each mesh is written as a separate object. Triangle colors are written as
+//! base materials, which is the part of the 3MF core specification that
+//! multi-material printers and slicers use to assign colors.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufWriter, Seek, Write},
+    path::Path,
+};
+
+use fj_interop::{Color, Mesh};
+use fj_math::Point;
+use zip::{write::FileOptions, ZipWriter};
+
+use crate::{Error, ExportOptions, Unit};
+
+/// Export the provided meshes to a 3MF file at the given path
+///
+/// This function will create a file if it does not exist, and will truncate it
+/// if it does.
+///
+/// Each mesh becomes a separate object in the 3MF file. To get one object per
+/// [`Shell`] or [`Solid`], triangulate each of them separately:
+///
+/// ``` no_run
+/// # use fj_core::{
+/// #     algorithms::{approx::Tolerance, triangulate::Triangulate},
+/// #     objects::Solid,
+/// # };
+/// # use fj_export::{export_3mf, Unit};
+/// #
+/// # fn export(
+/// #     solid: &Solid,
+/// #     tolerance: Tolerance,
+/// # ) -> Result<(), Box<dyn std::error::Error>> {
+/// let meshes = solid
+///     .shells()
+///     .iter()
+///     .map(|shell| (&**shell, tolerance).triangulate())
+///     .collect::<Result<Vec<_>, _>>()?;
+///
+/// export_3mf(&meshes, Unit::Millimeter, "model.3mf".as_ref())?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The coordinates are written as they are. `unit` declares how they are
+/// interpreted.
+///
+/// [`Shell`]: fj_core::objects::Shell
+/// [`Solid`]: fj_core::objects::Solid
+pub fn export_3mf<'r>(
+    meshes: impl IntoIterator<Item = &'r Mesh<Point<3>>>,
+    unit: Unit,
+    path: &Path,
+) -> Result<(), Error> {
+    let file = File::create(path)?;
+    write_3mf(meshes, &unit_only(unit), file)?;
+
+    Ok(())
+}
+
+/// Export the provided meshes to the given writer, as 3MF
+///
+/// See [`export_3mf`]. A 3MF file is a ZIP archive, which can't be written
+/// sequentially. The archive is assembled in memory, then written in one go.
+pub fn export_3mf_to_writer<'r>(
+    meshes: impl IntoIterator<Item = &'r Mesh<Point<3>>>,
+    unit: Unit,
+    writer: impl Write,
+) -> Result<(), Error> {
+    write_3mf_to_writer(meshes, &unit_only(unit), writer)
+}
+
+pub(crate) fn write_3mf_to_writer<'r>(
+    meshes: impl IntoIterator<Item = &'r Mesh<Point<3>>>,
+    options: &ExportOptions,
+    mut writer: impl Write,
+) -> Result<(), Error> {
+    let mut buffer = io::Cursor::new(Vec::new());
+    write_3mf(meshes, options, &mut buffer)?;
+    writer.write_all(buffer.get_ref())?;
+
+    Ok(())
+}
+
+/// The options that correspond to the arguments of the public functions
+///
+/// Those write the coordinates as they are, so the options must not be used to
+/// transform the meshes.
+fn unit_only(unit: Unit) -> ExportOptions {
+    ExportOptions {
+        unit,
+        ..ExportOptions::default()
+    }
+}
+
+fn write_3mf<'r>(
+    meshes: impl IntoIterator<Item = &'r Mesh<Point<3>>>,
+    options: &ExportOptions,
+    writer: impl Write + Seek,
+) -> Result<(), Error> {
+    let mut archive = ZipWriter::new(writer);
+
+    archive.start_file("[Content_Types].xml", FileOptions::default())?;
+    archive.write_all(CONTENT_TYPES.as_bytes())?;
+
+    archive.start_file("_rels/.rels", FileOptions::default())?;
+    archive.write_all(RELATIONSHIPS.as_bytes())?;
+
+    archive.start_file("3D/3dmodel.model", FileOptions::default())?;
+    write_model(meshes, options, &mut BufWriter::new(&mut archive))?;
+
+    archive.finish()?;
+
+    Ok(())
+}
+
+fn write_model<'r>(
+    meshes: impl IntoIterator<Item = &'r Mesh<Point<3>>>,
+    options: &ExportOptions,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    // Meshes without triangles are not valid objects, so leave them out.
+    let meshes = meshes
+        .into_iter()
+        .filter(|mesh| mesh.triangles().next().is_some())
+        .collect::<Vec<_>>();
+
+    // All objects share the same materials. Collect the colors in a
+    // deterministic order, so the same model always results in the same file.
+    let mut materials = BTreeMap::new();
+    for mesh in &meshes {
+        for triangle in mesh.triangles() {
+            materials.insert(triangle.color, 0);
+        }
+    }
+    for (index, material) in materials.values_mut().enumerate() {
+        *material = index;
+    }
+
+    let unit = match options.unit {
+        Unit::Micron => "micron",
+        Unit::Millimeter => "millimeter",
+        Unit::Centimeter => "centimeter",
+        Unit::Meter => "meter",
+        Unit::Inch => "inch",
+        Unit::Foot => "foot",
+    };
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<model unit="{unit}" xml:lang="en-US" xmlns="{NAMESPACE}">"#
+    )?;
+
+    let metadata = [("Title", &options.name), ("Designer", &options.author)];
+    for (name, value) in metadata {
+        if let Some(value) = value {
+            writeln!(
+                writer,
+                r#"  <metadata name="{name}">{}</metadata>"#,
+                escape(value),
+            )?;
+        }
+    }
+
+    writeln!(writer, "  <resources>")?;
+
+    if !materials.is_empty() {
+        writeln!(writer, r#"    <basematerials id="{MATERIALS_ID}">"#)?;
+        for color in materials.keys() {
+            let color = hex(*color);
+            writeln!(
+                writer,
+                r#"      <base name="{color}" displaycolor="{color}" />"#
+            )?;
+        }
+        writeln!(writer, "    </basematerials>")?;
+    }
+
+    for (index, mesh) in meshes.iter().enumerate() {
+        let id = FIRST_OBJECT_ID + index;
+
+        // The color of the first triangle is the default for the whole
+        // object. Only triangles with a different color need to specify one.
+        let default_material = mesh
+            .triangles()
+            .next()
+            .map(|triangle| materials[&triangle.color])
+            .unwrap_or_default();
+
+        writeln!(
+            writer,
+            r#"    <object id="{id}" type="model" pid="{MATERIALS_ID}" pindex="{default_material}">"#
+        )?;
+        writeln!(writer, "      <mesh>")?;
+
+        writeln!(writer, "        <vertices>")?;
+        for vertex in mesh.vertices() {
+            writeln!(
+                writer,
+                r#"          <vertex x="{}" y="{}" z="{}" />"#,
+                options.round(vertex.x.into_f64()),
+                options.round(vertex.y.into_f64()),
+                options.round(vertex.z.into_f64()),
+            )?;
+        }
+        writeln!(writer, "        </vertices>")?;
+
+        writeln!(writer, "        <triangles>")?;
+        let indices = mesh.indices().collect::<Vec<_>>();
+        for (triangle, indices) in mesh.triangles().zip(indices.chunks(3)) {
+            write!(
+                writer,
+                r#"          <triangle v1="{}" v2="{}" v3="{}""#,
+                indices[0], indices[1], indices[2]
+            )?;
+
+            let material = materials[&triangle.color];
+            if material != default_material {
+                write!(writer, r#" p1="{material}""#)?;
+            }
+
+            writeln!(writer, " />")?;
+        }
+        writeln!(writer, "        </triangles>")?;
+
+        writeln!(writer, "      </mesh>")?;
+        writeln!(writer, "    </object>")?;
+    }
+
+    writeln!(writer, "  </resources>")?;
+    writeln!(writer, "  <build>")?;
+    for index in 0..meshes.len() {
+        let id = FIRST_OBJECT_ID + index;
+        writeln!(writer, r#"    <item objectid="{id}" />"#)?;
+    }
+    writeln!(writer, "  </build>")?;
+    writeln!(writer, "</model>")?;
+
+    writer.flush()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn hex(Color([r, g, b, a]): Color) -> String {
+    format!("#{r:02X}{g:02X}{b:02X}{a:02X}")
+}
+
+const NAMESPACE: &str =
+    "http://schemas.microsoft.com/3dmanufacturing/core/2015/02";
+
+/// The resource id of the base materials
+///
+/// Objects are numbered after it, as resource ids must be unique.
+const MATERIALS_ID: usize = 1;
+const FIRST_OBJECT_ID: usize = MATERIALS_ID + 1;
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml" />
+  <Default Extension="model" ContentType="application/vnd.ms-package.3dmanufacturing-3dmodel+xml" />
+</Types>
+"#;
+
+const RELATIONSHIPS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Target="/3D/3dmodel.model" Id="rel0" Type="http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel" />
+</Relationships>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, io};
+
+    use fj_interop::{Color, Mesh};
+    use fj_math::Point;
+    use quick_xml::events::Event;
+    use zip::ZipArchive;
+
+    use crate::Unit;
+
+    use super::export_3mf_to_writer;
+
+    #[test]
+    fn colors_and_objects() -> anyhow::Result<()> {
+        let red = Color([255, 0, 0, 255]);
+        let blue = Color([0, 0, 255, 128]);
+
+        let mut a = Mesh::new();
+        a.push_triangle(
+            [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]].map(Point::from),
+            red,
+        );
+        a.push_triangle(
+            [[1., 0., 0.], [1., 1., 0.], [0., 1., 0.]].map(Point::from),
+            blue,
+        );
+
+        let mut b = Mesh::new();
+        b.push_triangle(
+            [[0., 0., 1.], [1., 0., 1.], [0., 1., 1.]].map(Point::from),
+            blue,
+        );
+
+        let elements = export([&a, &b], Unit::Millimeter)?;
+
+        // The colors are shared between objects, in a deterministic order.
+        let colors = attributes(&elements, "base", "displaycolor");
+        assert_eq!(colors, ["#0000FF80", "#FF0000FF"]);
+
+        let objects = elements
+            .iter()
+            .filter(|(name, _)| name == "object")
+            .map(|(_, attributes)| {
+                (attributes["id"].as_str(), attributes["pindex"].as_str())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(objects, [("2", "1"), ("3", "0")]);
+        assert_eq!(attributes(&elements, "item", "objectid"), ["2", "3"]);
+
+        // Only the triangle that differs from its object's default color
+        // specifies a material.
+        let triangles = elements
+            .iter()
+            .filter(|(name, _)| name == "triangle")
+            .map(|(_, attributes)| attributes.get("p1").map(String::as_str))
+            .collect::<Vec<_>>();
+        assert_eq!(triangles, [None, Some("0"), None]);
+
+        assert_eq!(attributes(&elements, "vertex", "x").len(), 4 + 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unit_attribute() -> anyhow::Result<()> {
+        let units = [
+            (Unit::Micron, "micron"),
+            (Unit::Millimeter, "millimeter"),
+            (Unit::Centimeter, "centimeter"),
+            (Unit::Meter, "meter"),
+            (Unit::Inch, "inch"),
+            (Unit::Foot, "foot"),
+        ];
+
+        for (unit, expected) in units {
+            let elements = export([], unit)?;
+            assert_eq!(attributes(&elements, "model", "unit"), [expected]);
+        }
+
+        Ok(())
+    }
+
+    type Element = (String, BTreeMap<String, String>);
+
+    /// Export the meshes, then read back the elements of the model file
+    fn export<'r>(
+        meshes: impl IntoIterator<Item = &'r Mesh<Point<3>>>,
+        unit: Unit,
+    ) -> anyhow::Result<Vec<Element>> {
+        let mut buffer = Vec::new();
+        export_3mf_to_writer(meshes, unit, &mut buffer)?;
+
+        let mut archive = ZipArchive::new(io::Cursor::new(buffer))?;
+        for required in ["[Content_Types].xml", "_rels/.rels"] {
+            archive.by_name(required)?;
+        }
+        let model = io::read_to_string(archive.by_name("3D/3dmodel.model")?)?;
+
+        let mut reader = quick_xml::Reader::from_str(&model);
+        let mut elements = Vec::new();
+        loop {
+            match reader.read_event()? {
+                Event::Start(element) | Event::Empty(element) => {
+                    let name =
+                        String::from_utf8(element.name().as_ref().to_vec())?;
+                    let mut attributes = BTreeMap::new();
+                    for attribute in element.attributes() {
+                        let attribute = attribute?;
+                        attributes.insert(
+                            String::from_utf8(attribute.key.as_ref().to_vec())?,
+                            attribute.unescape_value()?.into_owned(),
+                        );
+                    }
+                    elements.push((name, attributes));
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok(elements)
+    }
+
+    fn attributes<'e>(
+        elements: &'e [Element],
+        element: &str,
+        attribute: &str,
+    ) -> Vec<&'e str> {
+        elements
+            .iter()
+            .filter(|(name, _)| name == element)
+            .map(|(_, attributes)| attributes[attribute].as_str())
+            .collect()
+    }
+}
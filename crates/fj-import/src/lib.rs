@@ -0,0 +1,102 @@
+//! # Fornjot Importer
+//!
+//! [Fornjot] is an early-stage b-rep CAD kernel written in Rust. The kernel is
+//! split into multiple libraries that can be used semi-independently, and this
+//! is one of those.
+//!
+//! This library imports models from external file formats into Fornjot objects.
+//!
+//! [Fornjot]: https://www.fornjot.app/
+
+mod mesh;
+mod step;
+
+pub use self::{
+    mesh::{import_3mf, import_mesh, import_obj, import_stl, MeshImportConfig},
+    step::import_step,
+};
+
+use fj_core::operations::build::BuildShellError;
+use thiserror::Error;
+
+/// An error that can occur while importing
+#[derive(Debug, Error)]
+pub enum Error {
+    /// I/O error whilst importing from file
+    #[error("I/O error whilst importing from file")]
+    Io(#[from] std::io::Error),
+
+    /// STEP file is not syntactically valid
+    #[error("syntax error in STEP file, line {line}: {message}")]
+    StepSyntax {
+        /// The line on which the error occurred
+        line: usize,
+
+        /// A description of the error
+        message: String,
+    },
+
+    /// STEP file uses an entity that can't be imported
+    #[error("can't import STEP entity `#{id}` (`{entity}`): {reason}")]
+    StepUnsupported {
+        /// The id of the entity
+        id: u64,
+
+        /// The type of the entity
+        entity: String,
+
+        /// Why the entity can't be imported
+        reason: &'static str,
+    },
+
+    /// STEP file contains an entity that is malformed
+    #[error("invalid STEP entity `#{id}`: {reason}")]
+    StepInvalid {
+        /// The id of the entity
+        id: u64,
+
+        /// Why the entity is invalid
+        reason: String,
+    },
+
+    /// STEP file doesn't contain any shells
+    #[error("STEP file doesn't contain any shells")]
+    StepNoShells,
+
+    /// Mesh file is not syntactically valid
+    #[error("syntax error in {format} file: {message}")]
+    MeshSyntax {
+        /// The format of the file
+        format: &'static str,
+
+        /// A description of the error
+        message: String,
+    },
+
+    /// Mesh file doesn't contain any triangles
+    ///
+    /// This includes meshes whose triangles all collapse into points or lines,
+    /// after their vertices have been welded.
+    #[error("mesh doesn't contain any non-degenerate triangles")]
+    MeshNoTriangles,
+
+    /// Mesh can't be converted into a shell
+    #[error("mesh can't be converted into a shell")]
+    MeshInvalid(#[from] BuildShellError),
+
+    /// Zip error whilst importing from 3MF file
+    #[error("zip error whilst importing from 3MF file")]
+    Zip(#[from] zip::result::ZipError),
+
+    /// XML error whilst importing from 3MF file
+    #[error("XML error whilst importing from 3MF file")]
+    Xml(#[from] quick_xml::Error),
+
+    /// No extension specified
+    #[error("no extension specified")]
+    NoExtension,
+
+    /// Unrecognized extension found
+    #[error("unrecognized extension found `{0:?}`")]
+    InvalidExtension(String),
+}
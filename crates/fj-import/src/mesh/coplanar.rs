@@ -0,0 +1,197 @@
+//! Merging of coplanar triangles
+//!
+//! See [`merge`].
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use fj_math::{Point, Scalar, Vector};
+
+/// Group adjacent coplanar triangles into polygonal faces
+///
+/// Returns the faces in the format expected by
+/// [`BuildShell::from_vertices_and_polygons`]. Where the boundary of a group of
+/// triangles can't be represented as a polygon with holes (because it touches
+/// itself in a vertex), the triangles of that group become separate faces.
+///
+/// [`BuildShell::from_vertices_and_polygons`]: fj_core::operations::build::BuildShell::from_vertices_and_polygons
+pub fn merge(
+    vertices: &[Point<3>],
+    triangles: &[[usize; 3]],
+) -> Vec<Vec<Vec<usize>>> {
+    let mut triangles_by_edge = HashMap::new();
+    for (i, triangle) in triangles.iter().enumerate() {
+        for edge in edges(triangle) {
+            triangles_by_edge.insert(edge, i);
+        }
+    }
+
+    let mut faces = Vec::new();
+    let mut merged = vec![false; triangles.len()];
+
+    for seed in 0..triangles.len() {
+        if merged[seed] {
+            continue;
+        }
+        merged[seed] = true;
+
+        let plane = Plane::from_triangle(vertices, &triangles[seed]);
+
+        let mut group = vec![seed];
+        let mut next = 0;
+        while let Some(&triangle) = group.get(next) {
+            next += 1;
+
+            for [a, b] in edges(&triangles[triangle]) {
+                let Some(&neighbor) = triangles_by_edge.get(&[b, a]) else {
+                    continue;
+                };
+                if merged[neighbor]
+                    || !plane.contains(vertices, &triangles[neighbor])
+                {
+                    continue;
+                }
+
+                merged[neighbor] = true;
+                group.push(neighbor);
+            }
+        }
+
+        match boundary(vertices, triangles, &group, &plane) {
+            Some(cycles) => faces.push(cycles),
+            None => faces.extend(
+                group
+                    .iter()
+                    .map(|&triangle| vec![triangles[triangle].to_vec()]),
+            ),
+        }
+    }
+
+    faces
+}
+
+/// Find the boundary of a group of triangles
+///
+/// Returns the exterior, followed by the holes, if the boundary consists of
+/// simple cycles.
+fn boundary(
+    vertices: &[Point<3>],
+    triangles: &[[usize; 3]],
+    group: &[usize],
+    plane: &Plane,
+) -> Option<Vec<Vec<usize>>> {
+    let group_edges = group
+        .iter()
+        .flat_map(|&triangle| edges(&triangles[triangle]))
+        .collect::<Vec<_>>();
+    let inner_edges = group_edges.iter().copied().collect::<HashSet<_>>();
+
+    // Edges between triangles of the group are inside of the polygon. All
+    // others are on its boundary.
+    let mut next_vertex = BTreeMap::new();
+    for &[a, b] in &group_edges {
+        if inner_edges.contains(&[b, a]) {
+            continue;
+        }
+
+        // If multiple boundary edges start at the same vertex, the boundary
+        // touches itself there.
+        if next_vertex.insert(a, b).is_some() {
+            return None;
+        }
+    }
+
+    let mut cycles = Vec::new();
+    while let Some((&start, _)) = next_vertex.first_key_value() {
+        let mut cycle = vec![start];
+        let mut current = next_vertex.remove(&start)?;
+
+        while current != start {
+            cycle.push(current);
+            current = next_vertex.remove(&current)?;
+        }
+
+        cycles.push(cycle);
+    }
+
+    // The exterior is the only cycle that winds counter-clockwise around the
+    // normal of the plane. Holes wind the other way.
+    let areas = cycles
+        .iter()
+        .map(|cycle| {
+            cycle
+                .iter()
+                .zip(cycle.iter().cycle().skip(1))
+                .fold(Vector::from([0., 0., 0.]), |area, (&a, &b)| {
+                    area + vertices[a].coords.cross(&vertices[b].coords)
+                })
+                .dot(&plane.normal)
+        })
+        .collect::<Vec<_>>();
+
+    let mut exteriors = areas
+        .iter()
+        .enumerate()
+        .filter(|(_, &area)| area > Scalar::ZERO);
+    let (exterior, _) = exteriors.next()?;
+    if exteriors.next().is_some() {
+        return None;
+    }
+
+    let exterior = cycles.remove(exterior);
+    cycles.insert(0, exterior);
+
+    Some(cycles)
+}
+
+fn edges(triangle: &[usize; 3]) -> [[usize; 2]; 3] {
+    let [a, b, c] = *triangle;
+    [[a, b], [b, c], [c, a]]
+}
+
+/// The plane of a triangle
+struct Plane {
+    origin: Point<3>,
+    normal: Vector<3>,
+}
+
+impl Plane {
+    fn from_triangle(vertices: &[Point<3>], triangle: &[usize; 3]) -> Self {
+        let [a, b, c] = triangle.map(|index| vertices[index]);
+
+        Self {
+            origin: a,
+            normal: (b - a).cross(&(c - a)).normalize(),
+        }
+    }
+
+    /// Determine whether a triangle lies within the plane, facing the same way
+    fn contains(&self, vertices: &[Point<3>], triangle: &[usize; 3]) -> bool {
+        let other = Plane::from_triangle(vertices, triangle);
+        if other.normal.dot(&self.normal) <= Scalar::ZERO {
+            return false;
+        }
+
+        triangle.iter().all(|&index| {
+            let point = vertices[index];
+
+            // Faces are built from the projection of their vertices into the
+            // plane. If a vertex isn't in the plane within floating-point
+            // accuracy, its position would no longer match the one from the
+            // neighboring faces.
+            let distance = (point - self.origin).dot(&self.normal).abs();
+            let magnitude = point
+                .coords
+                .components
+                .into_iter()
+                .chain(self.origin.coords.components)
+                .map(Scalar::abs)
+                .max()
+                .unwrap_or(Scalar::ZERO);
+
+            distance <= magnitude * DISTANCE_EPSILON
+        })
+    }
+}
+
+/// The distance from the plane that vertices may have, relative to their size
+const DISTANCE_EPSILON: f64 = 4. * f64::EPSILON;
@@ -0,0 +1,463 @@
+//! Mesh import
+//!
+//! Triangle meshes don't carry any topology beyond the indices of their
+//! triangles, and many formats (most notably STL) don't even share vertices
+//! between triangles. The importers in this module reconstruct the topology by
+//! welding vertices that are close to each other, and then build a [`Shell`]
+//! from the result.
+
+mod coplanar;
+mod obj;
+mod stl;
+mod three_mf;
+
+use std::{collections::HashMap, fs, path::Path};
+
+use fj_core::{
+    objects::Shell,
+    operations::{build::BuildShell, insert::Insert},
+    storage::Handle,
+    Instance,
+};
+use fj_math::{Point, Scalar};
+
+use crate::Error;
+
+/// Configuration for importing meshes
+#[derive(Clone, Copy, Debug)]
+pub struct MeshImportConfig {
+    /// The maximum distance between vertices that are welded together
+    ///
+    /// Vertices that are closer to each other than this are considered to be
+    /// the same vertex. Triangles that collapse as a result are dropped.
+    pub weld_tolerance: Scalar,
+
+    /// Whether to merge adjacent coplanar triangles into polygonal faces
+    ///
+    /// If this is disabled, every triangle becomes a face of the shell.
+    ///
+    /// Only triangles that are coplanar within floating-point accuracy are
+    /// merged, as the vertices of the resulting faces must still coincide with
+    /// those of the neighboring faces.
+    pub merge_coplanar_faces: bool,
+}
+
+impl Default for MeshImportConfig {
+    fn default() -> Self {
+        Self {
+            weld_tolerance: Scalar::from_f64(1e-6), // 1 µm
+            merge_coplanar_faces: false,
+        }
+    }
+}
+
+/// Import the mesh file at the given path
+///
+/// Currently 3MF, OBJ & STL file types are supported. The case insensitive file
+/// extension of the provided path is used to switch between supported types.
+///
+/// Vertices are welded according to [`MeshImportConfig::weld_tolerance`], and
+/// the shell is built using [`BuildShell`]. Each triangle becomes a face,
+/// unless [`MeshImportConfig::merge_coplanar_faces`] is enabled.
+///
+/// The shell is inserted into the provided [`Instance`], which means it is
+/// validated like any other object. Shells must be closed, so importing a mesh
+/// that has holes results in validation errors.
+pub fn import_mesh(
+    path: &Path,
+    config: &MeshImportConfig,
+    core: &mut Instance,
+) -> Result<Handle<Shell>, Error> {
+    match path.extension() {
+        Some(extension) if extension.to_ascii_uppercase() == "3MF" => {
+            import_3mf(path, config, core)
+        }
+        Some(extension) if extension.to_ascii_uppercase() == "STL" => {
+            import_stl(path, config, core)
+        }
+        Some(extension) if extension.to_ascii_uppercase() == "OBJ" => {
+            import_obj(path, config, core)
+        }
+        Some(extension) => Err(Error::InvalidExtension(
+            extension.to_string_lossy().into_owned(),
+        )),
+        None => Err(Error::NoExtension),
+    }
+}
+
+/// Import the STL file at the given path
+///
+/// Both binary and ASCII STL files are supported. The normals stored in the
+/// file are ignored. The orientation of each triangle is defined by the order
+/// of its vertices instead.
+///
+/// See [`import_mesh`] for how the shell is built.
+pub fn import_stl(
+    path: &Path,
+    config: &MeshImportConfig,
+    core: &mut Instance,
+) -> Result<Handle<Shell>, Error> {
+    let data = fs::read(path)?;
+    let mesh = stl::parse(&data)?;
+
+    import_shell(mesh, config, core)
+}
+
+/// Import the OBJ file at the given path
+///
+/// Only vertex positions and faces are imported. Faces with more than 3
+/// vertices are split into triangles, assuming they are convex. Everything
+/// else (normals, texture coordinates, groups, materials, ...) is ignored.
+///
+/// See [`import_mesh`] for how the shell is built.
+pub fn import_obj(
+    path: &Path,
+    config: &MeshImportConfig,
+    core: &mut Instance,
+) -> Result<Handle<Shell>, Error> {
+    let source = fs::read_to_string(path)?;
+    let mesh = obj::parse(&source)?;
+
+    import_shell(mesh, config, core)
+}
+
+/// Import the 3MF file at the given path
+///
+/// All objects that are part of the build are imported into a single shell,
+/// with the transforms of build items and components applied. Coordinates are
+/// converted to millimeters, according to the unit of the model. Materials and
+/// colors are ignored.
+///
+/// See [`import_mesh`] for how the shell is built.
+pub fn import_3mf(
+    path: &Path,
+    config: &MeshImportConfig,
+    core: &mut Instance,
+) -> Result<Handle<Shell>, Error> {
+    let file = fs::File::open(path)?;
+    let mesh = three_mf::parse(file)?;
+
+    import_shell(mesh, config, core)
+}
+
+/// Build a shell from a triangle mesh
+fn import_shell(
+    mesh: TriangleMesh,
+    config: &MeshImportConfig,
+    core: &mut Instance,
+) -> Result<Handle<Shell>, Error> {
+    let mesh = mesh.weld(config.weld_tolerance.into_f64());
+    if mesh.triangles.is_empty() {
+        return Err(Error::MeshNoTriangles);
+    }
+
+    let shell = if config.merge_coplanar_faces {
+        let faces = coplanar::merge(&mesh.vertices, &mesh.triangles);
+        Shell::from_vertices_and_polygons(mesh.vertices, faces, core)?
+    } else {
+        Shell::from_vertices_and_indices(mesh.vertices, mesh.triangles, core)
+    };
+
+    Ok(shell.insert(&mut core.services))
+}
+
+/// A triangle mesh, as read from a file
+#[derive(Debug, Default)]
+struct TriangleMesh {
+    vertices: Vec<Point<3>>,
+    triangles: Vec<[usize; 3]>,
+}
+
+impl TriangleMesh {
+    /// Add a triangle whose vertices aren't shared with any other triangle
+    fn push_triangle(&mut self, points: [Point<3>; 3]) {
+        let first = self.vertices.len();
+        self.vertices.extend(points);
+        self.triangles.push([first, first + 1, first + 2]);
+    }
+
+    /// Merge vertices that are within the given distance of each other
+    ///
+    /// Also drops the triangles that become degenerate as a result, as well as
+    /// the vertices that are no longer referenced.
+    fn weld(self, tolerance: f64) -> Self {
+        // Vertices are sorted into a grid whose cells are as large as the
+        // tolerance. Any vertex that is close enough to be welded to another
+        // one is then located in one of the neighboring cells.
+        let cell_size = if tolerance > 0. { tolerance } else { 1. };
+        let cell = |point: &Point<3>| {
+            point
+                .coords
+                .components
+                .map(|c| (c.into_f64() / cell_size).floor() as i64)
+        };
+
+        let mut vertices: Vec<Point<3>> = Vec::new();
+        let mut grid: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+
+        let indices = self
+            .vertices
+            .iter()
+            .map(|point| {
+                let [x, y, z] = cell(point);
+
+                let mut neighbors = (x - 1..=x + 1).flat_map(|x| {
+                    (y - 1..=y + 1).flat_map(move |y| {
+                        (z - 1..=z + 1).map(move |z| [x, y, z])
+                    })
+                });
+                let existing = neighbors.find_map(|cell| {
+                    grid.get(&cell)?.iter().copied().find(|&index| {
+                        vertices[index].distance_to(point).into_f64()
+                            <= tolerance
+                    })
+                });
+
+                existing.unwrap_or_else(|| {
+                    let index = vertices.len();
+                    vertices.push(*point);
+                    grid.entry([x, y, z]).or_default().push(index);
+                    index
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let triangles = self
+            .triangles
+            .iter()
+            .map(|triangle| triangle.map(|index| indices[index]))
+            .filter(|&[a, b, c]| {
+                let [a, b, c] = [a, b, c].map(|index| vertices[index]);
+
+                // The area of the triangle, divided by its longest edge, is
+                // proportional to its smallest height. If that is too small,
+                // the triangle doesn't span a plane.
+                let area = (b - a).cross(&(c - a)).magnitude().into_f64();
+                let longest_edge = [b - a, c - b, a - c]
+                    .into_iter()
+                    .map(|edge| edge.magnitude().into_f64())
+                    .fold(0., f64::max);
+
+                area > 0. && area > tolerance * longest_edge
+            })
+            .collect::<Vec<_>>();
+
+        // Welding and dropping triangles leaves vertices behind, that aren't
+        // part of the mesh any more.
+        let mut used = vec![None; vertices.len()];
+        let mut used_vertices = Vec::new();
+        let triangles = triangles
+            .into_iter()
+            .map(|triangle| {
+                triangle.map(|index| {
+                    *used[index].get_or_insert_with(|| {
+                        used_vertices.push(vertices[index]);
+                        used_vertices.len() - 1
+                    })
+                })
+            })
+            .collect();
+
+        Self {
+            vertices: used_vertices,
+            triangles,
+        }
+    }
+}
+
+/// Parse 3 coordinates from the provided tokens
+///
+/// Returns `None`, if there are less than 3 tokens, or if any of them isn't a
+/// finite number.
+fn parse_point<'r>(
+    tokens: &mut impl Iterator<Item = &'r str>,
+) -> Option<Point<3>> {
+    let mut coords = [0.; 3];
+    for coord in &mut coords {
+        *coord = tokens
+            .next()?
+            .parse()
+            .ok()
+            .filter(|coord: &f64| coord.is_finite())?;
+    }
+
+    Some(Point::from(coords))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use fj_core::Instance;
+    use fj_export::{export_3mf, Unit};
+    use fj_interop::{Color, Mesh};
+    use fj_math::{Point, Scalar};
+
+    use super::{import_mesh, MeshImportConfig, TriangleMesh};
+
+    #[test]
+    fn weld() {
+        let mesh = TriangleMesh {
+            vertices: vec![
+                Point::from([0., 0., 0.]),
+                Point::from([1., 0., 0.]),
+                Point::from([0., 1., 0.]),
+                Point::from([1., 0., 1e-9]),
+                Point::from([0., 1., 0.]),
+                Point::from([1., 1., 0.]),
+                Point::from([5., 5., 5.]),
+            ],
+            triangles: vec![[0, 1, 2], [3, 5, 4], [0, 3, 1]],
+        };
+
+        let mesh = mesh.weld(1e-6);
+
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.triangles, vec![[0, 1, 2], [1, 3, 2]]);
+    }
+
+    #[test]
+    fn import_stl_cube() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let path = temp_file("cube.stl");
+        fs::write(&path, ascii_stl(&cube()))?;
+        let shell = import_mesh(&path, &MeshImportConfig::default(), &mut core);
+        fs::remove_file(&path)?;
+
+        assert_eq!(shell?.faces().len(), 12);
+        core.services.drop_and_validate()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn import_obj_merged() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        // The bottom face has a square hole in it, which doesn't go through
+        // the cube. It's walled off by an inner box.
+        let source = "\
+            v 0 0 0\nv 3 0 0\nv 3 3 0\nv 0 3 0\n\
+            v 0 0 3\nv 3 0 3\nv 3 3 3\nv 0 3 3\n\
+            v 1 1 0\nv 2 1 0\nv 2 2 0\nv 1 2 0\n\
+            v 1 1 1\nv 2 1 1\nv 2 2 1\nv 1 2 1\n\
+            f 1 4 12 9\nf 1 9 10 2\nf 2 10 11 3\nf 3 11 12 4\n\
+            f 5 6 7 8\n\
+            f 1 2 6 5\nf 2 3 7 6\nf 3 4 8 7\nf 4 1 5 8\n\
+            f 9 12 16 13\nf 12 11 15 16\nf 11 10 14 15\nf 10 9 13 14\n\
+            f 13 16 15 14\n\
+        ";
+
+        let path = temp_file("box.obj");
+        fs::write(&path, source)?;
+        let config = MeshImportConfig {
+            weld_tolerance: Scalar::from_f64(1e-6),
+            merge_coplanar_faces: true,
+        };
+        let shell = import_mesh(&path, &config, &mut core);
+        fs::remove_file(&path)?;
+        let shell = shell?;
+
+        // The 6 sides of the outer box, 4 walls and the top of the inner one.
+        assert_eq!(shell.faces().len(), 11);
+        let bottom = shell
+            .faces()
+            .iter()
+            .find(|face| face.region().interiors().len() == 1);
+        assert!(bottom.is_some());
+
+        core.services.drop_and_validate()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn import_3mf_exported_cube() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let mut mesh = Mesh::new();
+        for triangle in cube() {
+            mesh.push_triangle(triangle, Color::default());
+        }
+
+        let path = temp_file("cube.3mf");
+        export_3mf([&mesh], Unit::Centimeter, &path)?;
+        let shell = import_mesh(&path, &MeshImportConfig::default(), &mut core);
+        fs::remove_file(&path)?;
+        let shell = shell?;
+
+        assert_eq!(shell.faces().len(), 12);
+
+        // The 3MF file is in centimeters, which get converted to millimeters.
+        let max_x = shell
+            .faces()
+            .iter()
+            .flat_map(|face| {
+                let surface = face.surface().geometry();
+                face.region().exterior().half_edges().iter().map(
+                    move |half_edge| {
+                        surface
+                            .point_from_surface_coords(
+                                half_edge.start_position(),
+                            )
+                            .x
+                    },
+                )
+            })
+            .max();
+        assert_eq!(max_x, Some(Scalar::from(10.)));
+
+        core.services.drop_and_validate()?;
+
+        Ok(())
+    }
+
+    fn cube() -> Vec<[Point<3>; 3]> {
+        let [a, b, c, d, e, f, g, h] = [
+            [0., 0., 0.],
+            [1., 0., 0.],
+            [1., 1., 0.],
+            [0., 1., 0.],
+            [0., 0., 1.],
+            [1., 0., 1.],
+            [1., 1., 1.],
+            [0., 1., 1.],
+        ]
+        .map(Point::from);
+
+        let quads = [
+            [a, d, c, b],
+            [e, f, g, h],
+            [a, b, f, e],
+            [b, c, g, f],
+            [c, d, h, g],
+            [d, a, e, h],
+        ];
+
+        quads
+            .into_iter()
+            .flat_map(|[a, b, c, d]| [[a, b, c], [a, c, d]])
+            .collect()
+    }
+
+    fn ascii_stl(triangles: &[[Point<3>; 3]]) -> String {
+        let mut stl = String::from("solid cube\n");
+        for triangle in triangles {
+            stl.push_str("facet normal 0 0 0\nouter loop\n");
+            for point in triangle {
+                stl.push_str(&format!(
+                    "vertex {} {} {}\n",
+                    point.x, point.y, point.z
+                ));
+            }
+            stl.push_str("endloop\nendfacet\n");
+        }
+        stl.push_str("endsolid cube\n");
+        stl
+    }
+
+    fn temp_file(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("fj-import-{}-{name}", std::process::id()))
+    }
+}
@@ -0,0 +1,99 @@
+//! OBJ parser
+//!
+//! Only the statements that define geometry (`v` and `f`) are parsed. All
+//! others are skipped.
+
+use crate::Error;
+
+use super::{parse_point, TriangleMesh};
+
+/// Parse the contents of an OBJ file
+pub fn parse(source: &str) -> Result<TriangleMesh, Error> {
+    let mut mesh = TriangleMesh::default();
+
+    for (i, line) in source.lines().enumerate() {
+        let error = |message: &str| Error::MeshSyntax {
+            format: "OBJ",
+            message: format!("line {}: {message}", i + 1),
+        };
+
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("v") => {
+                // A fourth coordinate (the weight) is allowed, but only
+                // relevant for rational curves and surfaces.
+                let point = parse_point(&mut tokens)
+                    .ok_or_else(|| error("invalid vertex"))?;
+                mesh.vertices.push(point);
+            }
+            Some("f") => {
+                let indices = tokens
+                    .map(|token| vertex_index(token, mesh.vertices.len()))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| error("invalid vertex reference"))?;
+
+                let Some((&first, rest)) = indices.split_first() else {
+                    return Err(error("face has no vertices"));
+                };
+                if rest.len() < 2 {
+                    return Err(error("face has fewer than 3 vertices"));
+                }
+
+                for pair in rest.windows(2) {
+                    mesh.triangles.push([first, pair[0], pair[1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(mesh)
+}
+
+/// Resolve a vertex reference of a face
+///
+/// The reference can include texture coordinates and a normal (`v/vt/vn`),
+/// which are ignored. Indices start at 1, negative indices are relative to the
+/// end of the list of vertices that have been defined so far.
+fn vertex_index(token: &str, num_vertices: usize) -> Option<usize> {
+    let index = token.split('/').next()?.parse::<i64>().ok()?;
+
+    let index = if index > 0 {
+        usize::try_from(index - 1).ok()?
+    } else {
+        num_vertices.checked_sub(usize::try_from(index.checked_neg()?).ok()?)?
+    };
+
+    (index < num_vertices).then_some(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn parse_faces() -> anyhow::Result<()> {
+        let source = "\
+            # a quad and a triangle
+            o quad
+            v 0 0 0
+            v 1 0 0
+            v 1 1 0 1.0
+            v 0 1 0
+            vn 0 0 1
+            f 1//1 2//1 3//1 4//1
+            f -4/1 -2 -1
+        ";
+
+        let mesh = parse(source)?;
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.triangles, vec![[0, 1, 2], [0, 2, 3], [0, 2, 3]]);
+
+        assert!(parse("v 0 0 0\nf 1 2 3").is_err());
+        assert!(parse("v nan 0 0").is_err());
+        assert!(parse("v 0 1e400 0").is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+//! STL parser
+//!
+//! Binary and ASCII STL files are both supported. Which one a file is, is
+//! determined by its size. ASCII files are supposed to start with `solid`, but
+//! many binary files do the same, so that alone can't be trusted.
+
+use std::mem;
+
+use fj_math::Point;
+
+use crate::Error;
+
+use super::{parse_point, TriangleMesh};
+
+/// Parse the contents of an STL file
+pub fn parse(data: &[u8]) -> Result<TriangleMesh, Error> {
+    if let Some(num_triangles) = binary_triangle_count(data) {
+        return parse_binary(&data[HEADER_SIZE..], num_triangles);
+    }
+
+    let start = data
+        .iter()
+        .position(|c| !c.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    let is_ascii = data[start..]
+        .get(..5)
+        .is_some_and(|start| start.eq_ignore_ascii_case(b"solid"));
+    if !is_ascii {
+        return Err(error(
+            "file is neither ASCII, nor does its size match the number of \
+            triangles",
+        ));
+    }
+
+    let source = std::str::from_utf8(data)
+        .map_err(|_| error("ASCII file is not valid UTF-8"))?;
+    parse_ascii(source)
+}
+
+/// Determine the number of triangles, if this is a binary STL file
+fn binary_triangle_count(data: &[u8]) -> Option<usize> {
+    let count = data.get(HEADER_SIZE - 4..HEADER_SIZE)?;
+    let count = u32::from_le_bytes(count.try_into().ok()?) as usize;
+
+    let size = count.checked_mul(TRIANGLE_SIZE)?.checked_add(HEADER_SIZE)?;
+    (size == data.len()).then_some(count)
+}
+
+fn parse_binary(
+    data: &[u8],
+    num_triangles: usize,
+) -> Result<TriangleMesh, Error> {
+    let mut mesh = TriangleMesh::default();
+
+    for (i, triangle) in data
+        .chunks_exact(TRIANGLE_SIZE)
+        .take(num_triangles)
+        .enumerate()
+    {
+        // Each triangle starts with its normal, which we skip. It's followed
+        // by the 3 vertices and a 2-byte attribute that we don't use.
+        let points = [0, 1, 2].map(|i| {
+            let offset = 12 + i * 12;
+            [0, 1, 2].map(|j| {
+                let start = offset + j * 4;
+                let bytes = [0, 1, 2, 3].map(|k| triangle[start + k]);
+                f64::from(f32::from_le_bytes(bytes))
+            })
+        });
+
+        if points.iter().flatten().any(|coord| !coord.is_finite()) {
+            return Err(error(format!(
+                "triangle {}: coordinates are not finite",
+                i + 1
+            )));
+        }
+
+        mesh.push_triangle(points.map(Point::from));
+    }
+
+    Ok(mesh)
+}
+
+fn parse_ascii(source: &str) -> Result<TriangleMesh, Error> {
+    let mut mesh = TriangleMesh::default();
+    let mut points = Vec::new();
+
+    for (i, line) in source.lines().enumerate() {
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("vertex") => {
+                let point = parse_point(&mut tokens).ok_or_else(|| {
+                    error(format!("line {}: invalid vertex", i + 1))
+                })?;
+                points.push(point);
+            }
+            Some("endloop") => {
+                let points = <[_; 3]>::try_from(mem::take(&mut points))
+                    .map_err(|points| {
+                        error(format!(
+                            "line {}: expected 3 vertices, found {}",
+                            i + 1,
+                            points.len()
+                        ))
+                    })?;
+                mesh.push_triangle(points);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(mesh)
+}
+
+fn error(message: impl Into<String>) -> Error {
+    Error::MeshSyntax {
+        format: "STL",
+        message: message.into(),
+    }
+}
+
+/// The size of the header of a binary STL file, including the triangle count
+const HEADER_SIZE: usize = 84;
+
+/// The size of a triangle in a binary STL file
+const TRIANGLE_SIZE: usize = 50;
+
+#[cfg(test)]
+mod tests {
+    use fj_math::Point;
+
+    use super::parse;
+
+    #[test]
+    fn parse_binary_and_ascii() -> anyhow::Result<()> {
+        let points = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]];
+
+        // Binary files can start with "solid", too.
+        let mut binary = b"solid".to_vec();
+        binary.resize(80, 0);
+        binary.extend(1u32.to_le_bytes());
+        binary.extend([0; 12]);
+        for coord in points.iter().flatten() {
+            binary.extend((*coord as f32).to_le_bytes());
+        }
+        binary.extend([0; 2]);
+
+        let ascii = "\
+            solid triangle
+              facet normal 0 0 1
+                outer loop
+                  vertex 0 0 0
+                  vertex 1e0 0 0
+                  vertex 0 1 0
+                endloop
+              endfacet
+            endsolid triangle
+        ";
+
+        for data in [binary.as_slice(), ascii.as_bytes()] {
+            let mesh = parse(data)?;
+            assert_eq!(mesh.vertices, points.map(Point::from));
+            assert_eq!(mesh.triangles, vec![[0, 1, 2]]);
+        }
+
+        assert!(parse(b"not an STL file").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn non_finite_coordinates() {
+        for value in [f32::NAN, f32::INFINITY] {
+            let mut binary = vec![0; 80];
+            binary.extend(1u32.to_le_bytes());
+            binary.extend([0; 12]);
+            for coord in [value, 0., 0., 1., 0., 0., 0., 1., 0.] {
+                binary.extend(coord.to_le_bytes());
+            }
+            binary.extend([0; 2]);
+
+            assert!(parse(&binary).is_err());
+        }
+
+        for value in ["nan", "inf", "1e400"] {
+            let ascii = format!(
+                "\
+                solid triangle
+                  facet normal 0 0 1
+                    outer loop
+                      vertex {value} 0 0
+                      vertex 1 0 0
+                      vertex 0 1 0
+                    endloop
+                  endfacet
+                endsolid triangle
+                "
+            );
+
+            assert!(parse(ascii.as_bytes()).is_err());
+        }
+    }
+}
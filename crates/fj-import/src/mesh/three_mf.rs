@@ -0,0 +1,406 @@
+//! 3MF parser
+//!
+//! Only the parts of the 3MF core specification that define geometry are
+//! parsed: mesh objects, components, and the build.
+
+use std::{
+    collections::BTreeMap,
+    io::{BufReader, Read, Seek},
+};
+
+use fj_math::Point;
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+use zip::ZipArchive;
+
+use crate::Error;
+
+use super::TriangleMesh;
+
+/// Parse a 3MF file
+pub fn parse(reader: impl Read + Seek) -> Result<TriangleMesh, Error> {
+    let mut archive = ZipArchive::new(reader)?;
+
+    let model_path = {
+        let relationships = archive.by_name("_rels/.rels")?;
+        model_path(BufReader::new(relationships))?
+    };
+
+    let model = archive.by_name(&model_path)?;
+    let model = parse_model(BufReader::new(model))?;
+
+    let mut mesh = TriangleMesh::default();
+    for (object, transform) in &model.build {
+        model.add_object(*object, transform, 0, &mut mesh)?;
+    }
+
+    for point in &mut mesh.vertices {
+        *point = Point::origin() + point.coords * model.scale;
+    }
+
+    Ok(mesh)
+}
+
+/// Find the path of the model part within the package
+fn model_path(reader: impl std::io::BufRead) -> Result<String, Error> {
+    let mut reader = Reader::from_reader(reader);
+    let mut buffer = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buffer)? {
+            Event::Start(element) | Event::Empty(element)
+                if element.local_name().as_ref() == b"Relationship" =>
+            {
+                let is_model = attribute(&element, b"Type")?
+                    .is_some_and(|kind| kind.ends_with("/3dmodel"));
+
+                if is_model {
+                    let target = attribute(&element, b"Target")?
+                        .ok_or_else(|| error("relationship has no target"))?;
+
+                    // Targets are absolute within the package, but the names
+                    // of the files in the archive don't start with a slash.
+                    return Ok(target.trim_start_matches('/').to_string());
+                }
+            }
+            Event::Eof => return Err(error("package has no 3D model")),
+            _ => {}
+        }
+
+        buffer.clear();
+    }
+}
+
+fn parse_model(reader: impl std::io::BufRead) -> Result<Model, Error> {
+    let mut reader = Reader::from_reader(reader);
+    let mut buffer = Vec::new();
+
+    let mut model = Model {
+        scale: 1.,
+        objects: BTreeMap::new(),
+        build: Vec::new(),
+    };
+    let mut current = None;
+
+    loop {
+        match reader.read_event_into(&mut buffer)? {
+            Event::Start(element) | Event::Empty(element) => {
+                match element.local_name().as_ref() {
+                    b"model" => {
+                        if let Some(unit) = attribute(&element, b"unit")? {
+                            model.scale = unit_scale(&unit)?;
+                        }
+                    }
+                    b"object" => {
+                        let id = required(&element, b"id")?;
+                        current = Some((id, Object::default()));
+                    }
+                    b"vertex" => {
+                        let (_, object) = current
+                            .as_mut()
+                            .ok_or_else(|| error("vertex outside of object"))?;
+
+                        let coords = [b"x", b"y", b"z"]
+                            .map(|name| required::<f64>(&element, name));
+                        let [x, y, z] = coords;
+                        let coords = [x?, y?, z?];
+                        if coords.iter().any(|coord| !coord.is_finite()) {
+                            return Err(error(format!(
+                                "vertex coordinates are not finite: \
+                                {coords:?}"
+                            )));
+                        }
+                        object.mesh.vertices.push(Point::from(coords));
+                    }
+                    b"triangle" => {
+                        let (_, object) =
+                            current.as_mut().ok_or_else(|| {
+                                error("triangle outside of object")
+                            })?;
+
+                        let indices = [b"v1", b"v2", b"v3"]
+                            .map(|name| required(&element, name));
+                        let [a, b, c] = indices;
+                        object.mesh.triangles.push([a?, b?, c?]);
+                    }
+                    b"component" => {
+                        let (_, object) =
+                            current.as_mut().ok_or_else(|| {
+                                error("component outside of object")
+                            })?;
+
+                        let id = required(&element, b"objectid")?;
+                        let transform = transform(&element)?;
+                        object.components.push((id, transform));
+                    }
+                    b"item" => {
+                        let id = required(&element, b"objectid")?;
+                        let transform = transform(&element)?;
+                        model.build.push((id, transform));
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(element)
+                if element.local_name().as_ref() == b"object" =>
+            {
+                if let Some((id, object)) = current.take() {
+                    if object
+                        .mesh
+                        .triangles
+                        .iter()
+                        .flatten()
+                        .any(|&index| index >= object.mesh.vertices.len())
+                    {
+                        return Err(error(format!(
+                            "object {id} references undefined vertex"
+                        )));
+                    }
+
+                    model.objects.insert(id, object);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+
+        buffer.clear();
+    }
+
+    Ok(model)
+}
+
+struct Model {
+    /// The length of the model's unit, in millimeters
+    scale: f64,
+
+    objects: BTreeMap<u64, Object>,
+    build: Vec<(u64, Transform)>,
+}
+
+impl Model {
+    /// Add the triangles of an object to the mesh
+    ///
+    /// Components are added recursively. `depth` guards against components
+    /// that (directly or indirectly) refer to their own object.
+    fn add_object(
+        &self,
+        id: u64,
+        transform: &Transform,
+        depth: usize,
+        mesh: &mut TriangleMesh,
+    ) -> Result<(), Error> {
+        let object = self
+            .objects
+            .get(&id)
+            .ok_or_else(|| error(format!("undefined object {id}")))?;
+
+        if depth > self.objects.len() {
+            return Err(error(format!("object {id} contains itself")));
+        }
+
+        let offset = mesh.vertices.len();
+        mesh.vertices.extend(
+            object
+                .mesh
+                .vertices
+                .iter()
+                .map(|point| transform.apply(point)),
+        );
+        mesh.triangles.extend(
+            object
+                .mesh
+                .triangles
+                .iter()
+                .map(|triangle| triangle.map(|index| index + offset)),
+        );
+
+        for (component, component_transform) in &object.components {
+            let transform = component_transform.then(transform);
+            self.add_object(*component, &transform, depth + 1, mesh)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Object {
+    mesh: TriangleMesh,
+    components: Vec<(u64, Transform)>,
+}
+
+/// An affine transform, as defined by 3MF
+///
+/// The rows contain the images of the x, y, and z axes, followed by the
+/// translation. Points are transformed as row vectors, meaning the point is the
+/// left operand when multiplying it with this matrix.
+#[derive(Clone, Copy)]
+struct Transform([[f64; 3]; 4]);
+
+impl Transform {
+    const IDENTITY: Self =
+        Self([[1., 0., 0.], [0., 1., 0.], [0., 0., 1.], [0., 0., 0.]]);
+
+    fn apply(&self, point: &Point<3>) -> Point<3> {
+        let [x, y, z] = point.coords.components.map(|c| c.into_f64());
+        let [m0, m1, m2, m3] = self.0;
+
+        Point::from(
+            [0, 1, 2].map(|j| x * m0[j] + y * m1[j] + z * m2[j] + m3[j]),
+        )
+    }
+
+    /// Combine this transform with another one that is applied after it
+    fn then(&self, other: &Self) -> Self {
+        let mut rows = [[0.; 3]; 4];
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..3).map(|k| self.0[i][k] * other.0[k][j]).sum();
+            }
+        }
+        for (value, translation) in rows[3].iter_mut().zip(other.0[3]) {
+            *value += translation;
+        }
+
+        Self(rows)
+    }
+}
+
+fn transform(element: &BytesStart) -> Result<Transform, Error> {
+    let Some(value) = attribute(element, b"transform")? else {
+        return Ok(Transform::IDENTITY);
+    };
+
+    let values = value
+        .split_whitespace()
+        .map(|value| {
+            value.parse::<f64>().ok().filter(|value| value.is_finite())
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|values| values.len() == 12)
+        .ok_or_else(|| error(format!("invalid transform `{value}`")))?;
+
+    let mut rows = [[0.; 3]; 4];
+    for (row, values) in rows.iter_mut().zip(values.chunks(3)) {
+        row.copy_from_slice(values);
+    }
+
+    Ok(Transform(rows))
+}
+
+fn unit_scale(unit: &str) -> Result<f64, Error> {
+    let scale = match unit {
+        "micron" => 0.001,
+        "millimeter" => 1.,
+        "centimeter" => 10.,
+        "inch" => 25.4,
+        "foot" => 304.8,
+        "meter" => 1000.,
+        _ => return Err(error(format!("unknown unit `{unit}`"))),
+    };
+
+    Ok(scale)
+}
+
+fn attribute(
+    element: &BytesStart,
+    name: &[u8],
+) -> Result<Option<String>, Error> {
+    let Some(attribute) = element
+        .try_get_attribute(name)
+        .map_err(quick_xml::Error::from)?
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some(attribute.unescape_value()?.into_owned()))
+}
+
+/// Access an attribute that must be present, and parse it
+fn required<T: std::str::FromStr>(
+    element: &BytesStart,
+    name: &[u8],
+) -> Result<T, Error> {
+    let describe = || {
+        format!(
+            "attribute `{}` of `{}`",
+            String::from_utf8_lossy(name),
+            String::from_utf8_lossy(element.local_name().as_ref())
+        )
+    };
+
+    let value = attribute(element, name)?
+        .ok_or_else(|| error(format!("missing {}", describe())))?;
+
+    value
+        .trim()
+        .parse()
+        .map_err(|_| error(format!("invalid {}: `{value}`", describe())))
+}
+
+fn error(message: impl Into<String>) -> Error {
+    Error::MeshSyntax {
+        format: "3MF",
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_math::Point;
+
+    use super::{super::TriangleMesh, parse_model};
+
+    #[test]
+    fn components_and_transforms() -> anyhow::Result<()> {
+        let model = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <model unit="inch" xmlns="http://schemas.microsoft.com/3dmanufacturing/core/2015/02">
+              <resources>
+                <object id="1" type="model">
+                  <mesh>
+                    <vertices>
+                      <vertex x="0" y="0" z="0" />
+                      <vertex x="1" y="0" z="0" />
+                      <vertex x="0" y="1" z="0" />
+                    </vertices>
+                    <triangles>
+                      <triangle v1="0" v2="1" v3="2" />
+                    </triangles>
+                  </mesh>
+                </object>
+                <object id="2" type="model">
+                  <components>
+                    <component objectid="1" transform="0 1 0 -1 0 0 0 0 1 0 0 0" />
+                  </components>
+                </object>
+              </resources>
+              <build>
+                <item objectid="2" transform="1 0 0 0 1 0 0 0 1 5 0 0" />
+              </build>
+            </model>
+        "#;
+
+        let model = parse_model(model.as_bytes())?;
+        assert_eq!(model.scale, 25.4);
+
+        let mut mesh = TriangleMesh::default();
+        let [(object, transform)] = model.build.as_slice() else {
+            panic!("Expected one build item");
+        };
+        model.add_object(*object, transform, 0, &mut mesh)?;
+
+        // The component rotates by 90° around z, the build item then moves the
+        // result along x.
+        assert_eq!(
+            mesh.vertices,
+            [[5., 0., 0.], [5., 1., 0.], [4., 0., 0.]].map(Point::from)
+        );
+        assert_eq!(mesh.triangles, vec![[0, 1, 2]]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1313 @@
+//! STEP import
+//!
+//! See [`import_step`].
+
+mod parser;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    f64::consts::TAU,
+    fs,
+    path::Path,
+};
+
+use fj_core::{
+    geometry::{CurveBoundary, GlobalPath, SurfaceGeometry, SurfacePath},
+    objects::{Cycle, Face, HalfEdge, Shell, Solid, Surface, Vertex},
+    operations::{
+        build::{BuildCycle, BuildFace, BuildHalfEdge, BuildShell},
+        insert::Insert,
+        join::JoinCycle,
+        update::{
+            UpdateCycle, UpdateFace, UpdateHalfEdge, UpdateRegion, UpdateShell,
+        },
+    },
+    storage::Handle,
+    Instance,
+};
+use fj_math::{Circle, Line, Point, Scalar, Vector};
+
+use crate::Error;
+
+use self::parser::{Entities, Parameter, Record};
+
+/// Import the STEP file at the given path
+///
+/// Every shell in the file (`CLOSED_SHELL` or `OPEN_SHELL`) becomes a shell of
+/// the returned solid. Lengths are converted to millimeters. Shells are imported
+/// in the coordinate system they are defined in. Placements that assemblies
+/// apply to them are ignored. Shells that are referenced in reverse (like the
+/// voids of a `BREP_WITH_VOIDS`) are imported reversed, so they face away from
+/// the material.
+///
+/// Only geometry that Fornjot can represent is supported: faces on planes and
+/// cylinders, bounded by lines and circles. Faces on cylinders must have a
+/// seam, meaning their boundary can't wrap around the cylinder. An error is
+/// returned for anything else.
+///
+/// All objects are built using the regular operations and inserted into the
+/// provided [`Instance`]. This means they are validated like any other object,
+/// and validation errors are reported through the validation service.
+pub fn import_step(
+    path: &Path,
+    core: &mut Instance,
+) -> Result<Handle<Solid>, Error> {
+    let source = fs::read_to_string(path)?;
+    let entities = parser::parse(&source)?;
+
+    let mut importer = Importer {
+        entities: &entities,
+        scale: 1.,
+        vertices: BTreeMap::new(),
+        edges: BTreeMap::new(),
+        joined_edges: BTreeMap::new(),
+        core,
+    };
+    importer.scale = importer.length_unit()?;
+    let reversed_shells = importer.reversed_shells()?;
+
+    let shells = entities
+        .iter()
+        .filter(|(_, entity)| {
+            entity.record("CLOSED_SHELL").is_some()
+                || entity.record("OPEN_SHELL").is_some()
+        })
+        .map(|(id, _)| importer.shell(*id, reversed_shells.contains(id)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if shells.is_empty() {
+        return Err(Error::StepNoShells);
+    }
+
+    Ok(Solid::new(shells).insert(&mut importer.core.services))
+}
+
+struct Importer<'r> {
+    entities: &'r Entities,
+
+    /// The length of the file's length unit, in millimeters
+    scale: f64,
+
+    vertices: BTreeMap<u64, (Handle<Vertex>, Point<3>)>,
+    edges: BTreeMap<u64, Edge>,
+
+    /// The cycle and index of the first half-edge that refers to an edge
+    ///
+    /// Any other half-edge that refers to the same edge is joined to this one.
+    joined_edges: BTreeMap<u64, (Handle<Cycle>, usize)>,
+
+    core: &'r mut Instance,
+}
+
+impl<'r> Importer<'r> {
+    /// Find the shells that are referenced in reverse
+    fn reversed_shells(&self) -> Result<BTreeSet<u64>, Error> {
+        let mut reversed_shells = BTreeSet::new();
+
+        for (&id, entity) in self.entities {
+            let Some(record) = entity.record("ORIENTED_CLOSED_SHELL") else {
+                continue;
+            };
+
+            let item = Item { id, record };
+            if !item.boolean(3)? {
+                reversed_shells.insert(item.reference(2)?);
+            }
+        }
+
+        Ok(reversed_shells)
+    }
+
+    fn shell(
+        &mut self,
+        id: u64,
+        reversed: bool,
+    ) -> Result<Handle<Shell>, Error> {
+        let item =
+            self.item(id, &["CLOSED_SHELL", "OPEN_SHELL"], "expected shell")?;
+
+        let faces = item
+            .references(1)?
+            .into_iter()
+            .map(|face| self.face(face, reversed))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Shell::empty()
+            .add_faces(faces)
+            .insert(&mut self.core.services))
+    }
+
+    fn face(&mut self, id: u64, reversed: bool) -> Result<Handle<Face>, Error> {
+        let item = self.item(
+            id,
+            &["ADVANCED_FACE", "FACE_SURFACE"],
+            "only faces with explicit surface geometry are supported",
+        )?;
+        let surface = self.surface(item.reference(2)?)?;
+
+        let mut bounds = Vec::new();
+        for bound in item.references(1)? {
+            bounds.push(self.bound(bound, &surface, reversed)?);
+        }
+
+        // The outer bound is optional. If it's missing, the bound that
+        // encloses the largest area must be the exterior.
+        let exterior = bounds
+            .iter()
+            .position(|bound| bound.is_outer)
+            .or_else(|| {
+                bounds
+                    .iter()
+                    .map(|bound| area(&bound.half_edges).abs())
+                    .enumerate()
+                    .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map(|(i, _)| i)
+            })
+            .ok_or_else(|| item.invalid("face has no bounds"))?;
+        let exterior = bounds.remove(exterior);
+
+        if let SurfaceKind::Cylinder { .. } = surface {
+            // Each bound has been made continuous on its own. Move the
+            // interiors into the range of the exterior, so they end up within
+            // it.
+            let min_u = exterior
+                .half_edges
+                .iter()
+                .map(|half_edge| half_edge.start_position().u)
+                .min()
+                .unwrap_or(Scalar::ZERO);
+
+            for bound in &mut bounds {
+                let Some(first) = bound.half_edges.first() else {
+                    continue;
+                };
+                let u = first.start_position().u;
+                let offset = -((u - min_u) / TAU).floor() * TAU;
+
+                shift_u(&mut bound.half_edges, offset);
+            }
+        }
+
+        let exterior = self.cycle(exterior);
+        let interiors = bounds
+            .into_iter()
+            .map(|bound| self.cycle(bound))
+            .collect::<Vec<_>>();
+
+        let surface =
+            Surface::new(surface.geometry()).insert(&mut self.core.services);
+
+        let face = Face::unbound(surface, self.core).update_region(|region| {
+            region
+                .update_exterior(|_| exterior)
+                .add_interiors(interiors)
+                .insert(&mut self.core.services)
+        });
+
+        Ok(face.insert(&mut self.core.services))
+    }
+
+    /// Import a face bound
+    ///
+    /// The orientation of a face is defined by the winding of its bounds, so
+    /// reversing the bound reverses the face.
+    fn bound(
+        &mut self,
+        id: u64,
+        surface: &SurfaceKind,
+        reversed: bool,
+    ) -> Result<Bound, Error> {
+        let item = self.item(
+            id,
+            &["FACE_OUTER_BOUND", "FACE_BOUND"],
+            "expected face bound",
+        )?;
+        let is_outer = item.record.name == "FACE_OUTER_BOUND";
+
+        let edge_loop = self.item(
+            item.reference(1)?,
+            &["EDGE_LOOP"],
+            "only edge loops are supported as face bounds",
+        )?;
+
+        let mut oriented_edges = Vec::new();
+        for oriented_edge in edge_loop.references(1)? {
+            let oriented_edge = self.item(
+                oriented_edge,
+                &["ORIENTED_EDGE"],
+                "expected oriented edge",
+            )?;
+            oriented_edges
+                .push((oriented_edge.reference(3)?, oriented_edge.boolean(4)?));
+        }
+
+        if item.boolean(2)? == reversed {
+            oriented_edges.reverse();
+            for (_, orientation) in &mut oriented_edges {
+                *orientation = !*orientation;
+            }
+        }
+
+        let mut half_edges = Vec::new();
+        let mut edges = Vec::new();
+        for (edge_id, orientation) in oriented_edges {
+            let edge = self.edge(edge_id)?;
+            let path = edge.path(edge_id, surface)?;
+
+            let boundary = CurveBoundary::from(edge.boundary.map(|t| [t]));
+            let (boundary, (start_vertex, _)) = if orientation {
+                (boundary, &edge.vertices[0])
+            } else {
+                (boundary.reverse(), &edge.vertices[1])
+            };
+
+            half_edges.push(
+                HalfEdge::unjoined(path, boundary, self.core)
+                    .update_start_vertex(|_| start_vertex.clone()),
+            );
+            edges.push(edge_id);
+        }
+
+        if let SurfaceKind::Cylinder { .. } = surface {
+            make_continuous_on_cylinder(&mut half_edges, edge_loop)?;
+        }
+
+        Ok(Bound {
+            half_edges,
+            edges,
+            is_outer,
+        })
+    }
+
+    fn cycle(&mut self, bound: Bound) -> Handle<Cycle> {
+        let half_edges = bound
+            .half_edges
+            .into_iter()
+            .map(|half_edge| half_edge.insert(&mut self.core.services))
+            .collect::<Vec<_>>();
+
+        let mut cycle = Cycle::empty().add_half_edges(half_edges);
+
+        // Join every half-edge to the first one that refers to the same edge,
+        // which makes them siblings. That one might be in the same cycle, as
+        // is the case for the seam of a face on a cylinder.
+        for (index, edge) in bound.edges.iter().enumerate() {
+            let (other, other_index) = match self.joined_edges.get(edge) {
+                Some((other, other_index)) => ((**other).clone(), *other_index),
+                None => {
+                    let Some(other_index) =
+                        bound.edges[..index].iter().position(|e| e == edge)
+                    else {
+                        continue;
+                    };
+                    (cycle.clone(), other_index)
+                }
+            };
+
+            cycle = cycle.join_to(
+                &other,
+                index..=index,
+                other_index..=other_index,
+                self.core,
+            );
+        }
+
+        let cycle = cycle.insert(&mut self.core.services);
+
+        for (index, edge) in bound.edges.into_iter().enumerate() {
+            self.joined_edges
+                .entry(edge)
+                .or_insert_with(|| (cycle.clone(), index));
+        }
+
+        cycle
+    }
+
+    fn edge(&mut self, id: u64) -> Result<Edge, Error> {
+        if let Some(edge) = self.edges.get(&id) {
+            return Ok(edge.clone());
+        }
+
+        let item = self.item(
+            id,
+            &["EDGE_CURVE"],
+            "only edge curves are supported as edges",
+        )?;
+
+        let start_id = item.reference(1)?;
+        let end_id = item.reference(2)?;
+        let vertices = [self.vertex(start_id)?, self.vertex(end_id)?];
+        let kind = self.curve(item.reference(3)?)?;
+        let same_sense = item.boolean(4)?;
+
+        // The edge's curve coordinates are chosen here, once, and shared by
+        // both half-edges that refer to it. This is required for joining them.
+        let boundary = match kind {
+            CurveKind::Line { .. } => [0., 1.],
+            CurveKind::Circle { placement, .. } => {
+                let [start, end] = [&vertices[0], &vertices[1]]
+                    .map(|(_, point)| placement.angle(*point));
+
+                let delta = if start_id == end_id {
+                    TAU
+                } else if same_sense {
+                    (end - start).rem_euclid(TAU)
+                } else {
+                    (start - end).rem_euclid(TAU)
+                };
+                let delta = if same_sense { delta } else { -delta };
+
+                [start, start + delta]
+            }
+        };
+
+        let edge = Edge {
+            kind,
+            vertices,
+            boundary,
+        };
+        self.edges.insert(id, edge.clone());
+
+        Ok(edge)
+    }
+
+    fn vertex(&mut self, id: u64) -> Result<(Handle<Vertex>, Point<3>), Error> {
+        if let Some(vertex) = self.vertices.get(&id) {
+            return Ok(vertex.clone());
+        }
+
+        let item = self.item(
+            id,
+            &["VERTEX_POINT"],
+            "only vertex points are supported as vertices",
+        )?;
+        let point = self.point(item.reference(1)?)?;
+
+        let vertex = (Vertex::new().insert(&mut self.core.services), point);
+        self.vertices.insert(id, vertex.clone());
+
+        Ok(vertex)
+    }
+
+    fn surface(&self, id: u64) -> Result<SurfaceKind, Error> {
+        let item = self.item(
+            id,
+            &["PLANE", "CYLINDRICAL_SURFACE"],
+            "only planes and cylinders are supported as surfaces",
+        )?;
+        let placement = self.placement(item.reference(1)?)?;
+
+        if item.record.name == "PLANE" {
+            Ok(SurfaceKind::Plane(placement))
+        } else {
+            let radius = self.length(&item, 2)?;
+            Ok(SurfaceKind::Cylinder { placement, radius })
+        }
+    }
+
+    fn curve(&self, id: u64) -> Result<CurveKind, Error> {
+        let item = self.item(
+            id,
+            &["LINE", "CIRCLE", "SURFACE_CURVE", "SEAM_CURVE"],
+            "only lines and circles are supported as curves",
+        )?;
+
+        match item.record.name.as_str() {
+            "LINE" => {
+                let vector = self.item(
+                    item.reference(2)?,
+                    &["VECTOR"],
+                    "expected vector",
+                )?;
+                let direction = self.direction(vector.reference(1)?)?;
+
+                Ok(CurveKind::Line { direction })
+            }
+            "CIRCLE" => {
+                let placement = self.placement(item.reference(1)?)?;
+                let radius = self.length(&item, 2)?;
+
+                Ok(CurveKind::Circle { placement, radius })
+            }
+            _ => {
+                // Surface curves have additional representations in the
+                // parameter space of their surfaces. Those are redundant, as
+                // we compute our own, so we only care about the 3D curve.
+                self.curve(item.reference(1)?)
+            }
+        }
+    }
+
+    fn placement(&self, id: u64) -> Result<Placement, Error> {
+        let item = self.item(
+            id,
+            &["AXIS2_PLACEMENT_3D"],
+            "only 3D placements are supported",
+        )?;
+
+        let origin = self.point(item.reference(1)?)?;
+        let axis = match item.parameter(2)? {
+            Parameter::Unset => Vector::unit_z(),
+            _ => self.direction(item.reference(2)?)?,
+        };
+        let ref_direction = match item.parameter(3)? {
+            Parameter::Unset if axis.x.abs() < Scalar::from(0.9) => {
+                Vector::unit_x()
+            }
+            Parameter::Unset => Vector::unit_y(),
+            _ => self.direction(item.reference(3)?)?,
+        };
+
+        // The reference direction only needs to be approximately
+        // perpendicular to the axis. Its projection is what's used.
+        let x = ref_direction - axis * ref_direction.dot(&axis);
+        if x.magnitude() < Scalar::from(ANGULAR_TOLERANCE) {
+            return Err(item.invalid("reference direction is parallel to axis"));
+        }
+        let x = x.normalize();
+        let y = axis.cross(&x);
+
+        Ok(Placement { origin, axis, x, y })
+    }
+
+    fn point(&self, id: u64) -> Result<Point<3>, Error> {
+        let item = self.item(
+            id,
+            &["CARTESIAN_POINT"],
+            "only cartesian points are supported as points",
+        )?;
+        let coords = item.coordinates(1)?.map(|coord| coord * self.scale);
+
+        if coords.iter().any(|coord| !coord.is_finite()) {
+            return Err(item.invalid("coordinates are out of range"));
+        }
+
+        Ok(Point::from(coords))
+    }
+
+    fn direction(&self, id: u64) -> Result<Vector<3>, Error> {
+        let item = self.item(id, &["DIRECTION"], "expected direction")?;
+        let direction = Vector::from(item.coordinates(1)?);
+
+        let magnitude = direction.magnitude().into_f64();
+        if magnitude == 0. {
+            return Err(item.invalid("direction has zero length"));
+        }
+        if !magnitude.is_finite() {
+            return Err(item.invalid("direction is out of range"));
+        }
+
+        Ok(direction.normalize())
+    }
+
+    fn length(&self, item: &Item, index: usize) -> Result<Scalar, Error> {
+        let length = item.real(index)? * self.scale;
+
+        if !(length > 0. && length.is_finite()) {
+            return Err(item.invalid(format!(
+                "length `{length}` isn't positive and finite"
+            )));
+        }
+
+        Ok(Scalar::from(length))
+    }
+
+    /// Find the length unit of the file, in millimeters
+    ///
+    /// Falls back to millimeters, if the file doesn't specify a length unit.
+    fn length_unit(&self) -> Result<f64, Error> {
+        for (&id, entity) in self.entities {
+            let Some(record) = entity.record("GLOBAL_UNIT_ASSIGNED_CONTEXT")
+            else {
+                continue;
+            };
+
+            let context = Item { id, record };
+            for unit in context.references(0)? {
+                let is_length_unit = self
+                    .entities
+                    .get(&unit)
+                    .is_some_and(|unit| unit.record("LENGTH_UNIT").is_some());
+
+                if is_length_unit {
+                    return self.unit_in_millimeters(unit);
+                }
+            }
+        }
+
+        Ok(1.)
+    }
+
+    fn unit_in_millimeters(&self, id: u64) -> Result<f64, Error> {
+        let item = self.item(
+            id,
+            &["SI_UNIT", "CONVERSION_BASED_UNIT"],
+            "unsupported unit",
+        )?;
+
+        if item.record.name == "SI_UNIT" {
+            let prefix = match item.parameter(0)? {
+                Parameter::Enumeration(prefix) => match prefix.as_str() {
+                    "KILO" => 1e3,
+                    "HECTO" => 1e2,
+                    "DECA" => 1e1,
+                    "DECI" => 1e-1,
+                    "CENTI" => 1e-2,
+                    "MILLI" => 1e-3,
+                    "MICRO" => 1e-6,
+                    "NANO" => 1e-9,
+                    _ => {
+                        return Err(
+                            item.invalid(format!("unknown prefix `{prefix}`"))
+                        )
+                    }
+                },
+                _ => 1.,
+            };
+
+            return Ok(prefix * 1000.);
+        }
+
+        // A conversion-based unit, like inches, is defined as a multiple of
+        // another unit.
+        let measure = self.item(
+            item.reference(1)?,
+            &["LENGTH_MEASURE_WITH_UNIT", "MEASURE_WITH_UNIT"],
+            "expected measure with unit",
+        )?;
+        let factor = match measure.parameter(0)? {
+            Parameter::Typed(record) => Item {
+                id: measure.id,
+                record,
+            }
+            .real(0)?,
+            _ => measure.real(0)?,
+        };
+
+        Ok(factor * self.unit_in_millimeters(measure.reference(1)?)?)
+    }
+
+    fn item(
+        &self,
+        id: u64,
+        expected: &[&str],
+        reason: &'static str,
+    ) -> Result<Item<'r>, Error> {
+        let entity =
+            self.entities.get(&id).ok_or_else(|| Error::StepInvalid {
+                id,
+                reason: "entity is referenced, but not defined".into(),
+            })?;
+
+        expected
+            .iter()
+            .find_map(|name| entity.record(name))
+            .map(|record| Item { id, record })
+            .ok_or_else(|| Error::StepUnsupported {
+                id,
+                entity: entity.type_name(),
+                reason,
+            })
+    }
+}
+
+/// A record of an entity, along with the entity's id
+#[derive(Clone, Copy)]
+struct Item<'r> {
+    id: u64,
+    record: &'r Record,
+}
+
+impl<'r> Item<'r> {
+    fn parameter(&self, index: usize) -> Result<&'r Parameter, Error> {
+        self.record.parameters.get(index).ok_or_else(|| {
+            self.invalid(format!(
+                "`{}` is missing parameter {index}",
+                self.record.name
+            ))
+        })
+    }
+
+    fn reference(&self, index: usize) -> Result<u64, Error> {
+        match self.parameter(index)? {
+            Parameter::Reference(id) => Ok(*id),
+            _ => Err(self.unexpected(index, "a reference")),
+        }
+    }
+
+    fn references(&self, index: usize) -> Result<Vec<u64>, Error> {
+        match self.parameter(index)? {
+            Parameter::List(parameters) => parameters
+                .iter()
+                .map(|parameter| match parameter {
+                    Parameter::Reference(id) => Ok(*id),
+                    _ => Err(self.unexpected(index, "a list of references")),
+                })
+                .collect(),
+            _ => Err(self.unexpected(index, "a list of references")),
+        }
+    }
+
+    fn real(&self, index: usize) -> Result<f64, Error> {
+        let value = match self.parameter(index)? {
+            Parameter::Real(value) => *value,
+            Parameter::Integer(value) => *value as f64,
+            _ => return Err(self.unexpected(index, "a number")),
+        };
+
+        if !value.is_finite() {
+            return Err(self.unexpected(index, "a finite number"));
+        }
+
+        Ok(value)
+    }
+
+    fn coordinates(&self, index: usize) -> Result<[f64; 3], Error> {
+        let coords = match self.parameter(index)? {
+            Parameter::List(parameters) => parameters
+                .iter()
+                .map(|parameter| match parameter {
+                    Parameter::Real(value) if value.is_finite() => Some(*value),
+                    Parameter::Integer(value) => Some(*value as f64),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>(),
+            _ => None,
+        };
+
+        coords
+            .and_then(|coords| coords.try_into().ok())
+            .ok_or_else(|| self.unexpected(index, "three finite numbers"))
+    }
+
+    fn boolean(&self, index: usize) -> Result<bool, Error> {
+        match self.parameter(index)? {
+            Parameter::Enumeration(value) if value == "T" => Ok(true),
+            Parameter::Enumeration(value) if value == "F" => Ok(false),
+            _ => Err(self.unexpected(index, "a boolean")),
+        }
+    }
+
+    fn unexpected(&self, index: usize, expected: &str) -> Error {
+        self.invalid(format!(
+            "expected parameter {index} of `{}` to be {expected}",
+            self.record.name
+        ))
+    }
+
+    fn invalid(&self, reason: impl Into<String>) -> Error {
+        Error::StepInvalid {
+            id: self.id,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// An orthonormal coordinate system, as defined by `AXIS2_PLACEMENT_3D`
+#[derive(Clone, Copy)]
+struct Placement {
+    origin: Point<3>,
+    axis: Vector<3>,
+    x: Vector<3>,
+    y: Vector<3>,
+}
+
+impl Placement {
+    /// The angle of a point around the axis, relative to the x-axis
+    fn angle(&self, point: Point<3>) -> f64 {
+        self.angle_of_vector(point - self.origin)
+    }
+
+    fn angle_of_vector(&self, vector: Vector<3>) -> f64 {
+        let x = vector.dot(&self.x).into_f64();
+        let y = vector.dot(&self.y).into_f64();
+        y.atan2(x)
+    }
+
+    /// Project a point into the xy-plane of the placement
+    fn project_point(&self, point: Point<3>) -> Point<2> {
+        Point::origin() + self.project_vector(point - self.origin)
+    }
+
+    fn project_vector(&self, vector: Vector<3>) -> Vector<2> {
+        Vector::from([vector.dot(&self.x), vector.dot(&self.y)])
+    }
+}
+
+#[derive(Clone, Copy)]
+enum SurfaceKind {
+    Plane(Placement),
+    Cylinder {
+        placement: Placement,
+        radius: Scalar,
+    },
+}
+
+impl SurfaceKind {
+    fn geometry(&self) -> SurfaceGeometry {
+        match *self {
+            Self::Plane(Placement { origin, x, y, .. }) => SurfaceGeometry {
+                u: GlobalPath::Line(Line::from_origin_and_direction(origin, x)),
+                v: y,
+            },
+            Self::Cylinder {
+                placement:
+                    Placement {
+                        origin, axis, x, y, ..
+                    },
+                radius,
+            } => SurfaceGeometry {
+                u: GlobalPath::Circle(Circle::new(
+                    origin,
+                    x * radius,
+                    y * radius,
+                )),
+                v: axis,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum CurveKind {
+    Line {
+        direction: Vector<3>,
+    },
+    Circle {
+        placement: Placement,
+        radius: Scalar,
+    },
+}
+
+/// An imported face bound, before it becomes a cycle
+struct Bound {
+    half_edges: Vec<HalfEdge>,
+
+    /// The ids of the `EDGE_CURVE`s that the half-edges refer to
+    edges: Vec<u64>,
+
+    is_outer: bool,
+}
+
+/// An imported `EDGE_CURVE`
+#[derive(Clone)]
+struct Edge {
+    kind: CurveKind,
+    vertices: [(Handle<Vertex>, Point<3>); 2],
+
+    /// The curve coordinates of the edge's start and end vertices
+    ///
+    /// Lines go from `0` to `1`. Circles use the angle around their axis.
+    boundary: [f64; 2],
+}
+
+impl Edge {
+    /// Compute the path of the edge in the coordinates of the given surface
+    fn path(
+        &self,
+        id: u64,
+        surface: &SurfaceKind,
+    ) -> Result<SurfacePath, Error> {
+        let unsupported = |reason| Error::StepUnsupported {
+            id,
+            entity: "EDGE_CURVE".into(),
+            reason,
+        };
+        let [(_, start), (_, end)] = &self.vertices;
+
+        let path = match (*surface, self.kind) {
+            (SurfaceKind::Plane(plane), CurveKind::Line { .. }) => {
+                SurfacePath::line_from_points_with_coords([
+                    ([0.], plane.project_point(*start)),
+                    ([1.], plane.project_point(*end)),
+                ])
+            }
+            (
+                SurfaceKind::Plane(plane),
+                CurveKind::Circle { placement, radius },
+            ) => {
+                if !is_parallel(placement.axis, plane.axis) {
+                    return Err(unsupported(
+                        "circle is not parallel to the plane it is on",
+                    ));
+                }
+
+                SurfacePath::Circle(Circle::new(
+                    plane.project_point(placement.origin),
+                    plane.project_vector(placement.x * radius),
+                    plane.project_vector(placement.y * radius),
+                ))
+            }
+            (
+                SurfaceKind::Cylinder { placement, .. },
+                CurveKind::Line { direction },
+            ) => {
+                if !is_parallel(direction, placement.axis) {
+                    return Err(unsupported(
+                        "line is not parallel to the axis of the cylinder it \
+                        is on",
+                    ));
+                }
+
+                let u = placement.angle(*start);
+                let [start_v, end_v] = [start, end].map(|point| {
+                    (*point - placement.origin).dot(&placement.axis)
+                });
+
+                SurfacePath::line_from_points_with_coords([
+                    ([0.], [u, start_v.into_f64()]),
+                    ([1.], [u, end_v.into_f64()]),
+                ])
+            }
+            (
+                SurfaceKind::Cylinder {
+                    placement: cylinder,
+                    radius: cylinder_radius,
+                },
+                CurveKind::Circle { placement, radius },
+            ) => {
+                let offset = placement.origin - cylinder.origin;
+                let distance_from_axis = (offset
+                    - cylinder.axis * offset.dot(&cylinder.axis))
+                .magnitude();
+
+                let is_coaxial = is_parallel(placement.axis, cylinder.axis)
+                    && distance_from_axis
+                        <= cylinder_radius * RELATIVE_TOLERANCE
+                    && (radius - cylinder_radius).abs()
+                        <= cylinder_radius * RELATIVE_TOLERANCE;
+                if !is_coaxial {
+                    return Err(unsupported(
+                        "circle is not coaxial with the cylinder it is on",
+                    ));
+                }
+
+                // The circle runs around the cylinder, so its angle maps
+                // directly to the u-coordinate of the cylinder, possibly in
+                // the opposite direction.
+                let u = cylinder.angle_of_vector(placement.x);
+                let v = offset.dot(&cylinder.axis);
+                let direction =
+                    if placement.axis.dot(&cylinder.axis) > Scalar::ZERO {
+                        1.
+                    } else {
+                        -1.
+                    };
+
+                SurfacePath::Line(Line::from_origin_and_direction(
+                    Point::from([Scalar::from(u), v]),
+                    Vector::from([direction, 0.]),
+                ))
+            }
+        };
+
+        Ok(path)
+    }
+}
+
+/// Make the u-coordinates of a bound on a cylinder continuous
+///
+/// The angle around a cylinder wraps around, so the half-edges of a bound can
+/// end up with u-coordinates that differ by multiples of a full turn. This
+/// shifts them, so each half-edge starts where the previous one ended.
+fn make_continuous_on_cylinder(
+    half_edges: &mut [HalfEdge],
+    edge_loop: Item,
+) -> Result<(), Error> {
+    let mut previous_end: Option<Point<2>> = None;
+
+    for half_edge in half_edges.iter_mut() {
+        let [start, _] = endpoints(half_edge);
+
+        if let Some(previous_end) = previous_end {
+            let offset = ((previous_end.u - start.u) / TAU).round() * TAU;
+            shift_u(std::slice::from_mut(half_edge), offset);
+        }
+
+        let [_, end] = endpoints(half_edge);
+        previous_end = Some(end);
+    }
+
+    if let (Some(first), Some(last)) = (half_edges.first(), half_edges.last()) {
+        let [start, _] = endpoints(first);
+        let [_, end] = endpoints(last);
+
+        if (end.u - start.u).abs() > Scalar::from(ANGULAR_TOLERANCE) {
+            return Err(Error::StepUnsupported {
+                id: edge_loop.id,
+                entity: edge_loop.record.name.clone(),
+                reason: "loop wraps around cylinder; faces without a seam are \
+                    not supported",
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn shift_u(half_edges: &mut [HalfEdge], offset: impl Into<Scalar>) {
+    let offset = Vector::from([offset.into(), Scalar::ZERO]);
+    if offset.u == Scalar::ZERO {
+        return;
+    }
+
+    for half_edge in half_edges {
+        *half_edge = half_edge.update_path(|path| match path {
+            SurfacePath::Line(line) => {
+                SurfacePath::Line(Line::from_origin_and_direction(
+                    line.origin() + offset,
+                    line.direction(),
+                ))
+            }
+            SurfacePath::Circle(circle) => SurfacePath::Circle(Circle::new(
+                circle.center() + offset,
+                circle.a(),
+                circle.b(),
+            )),
+        });
+    }
+}
+
+fn endpoints(half_edge: &HalfEdge) -> [Point<2>; 2] {
+    half_edge
+        .boundary()
+        .inner
+        .map(|point| half_edge.path().point_from_path_coords(point))
+}
+
+/// Compute the signed area enclosed by a bound, in surface coordinates
+fn area(half_edges: &[HalfEdge]) -> f64 {
+    const SAMPLES_PER_HALF_EDGE: u32 = 16;
+
+    let points = half_edges
+        .iter()
+        .flat_map(|half_edge| {
+            let [a, b] = half_edge.boundary().inner.map(|point| point.t);
+            (0..SAMPLES_PER_HALF_EDGE).map(move |i| {
+                let t = a
+                    + (b - a) * f64::from(i) / f64::from(SAMPLES_PER_HALF_EDGE);
+                half_edge.path().point_from_path_coords([t])
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut sum = 0.;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        sum += (a.u * b.v - b.u * a.v).into_f64();
+    }
+
+    sum / 2.
+}
+
+fn is_parallel(a: Vector<3>, b: Vector<3>) -> bool {
+    a.cross(&b).magnitude() <= Scalar::from(ANGULAR_TOLERANCE)
+}
+
+/// The tolerance used when comparing directions and angles, in radians
+const ANGULAR_TOLERANCE: f64 = 1e-6;
+
+/// The tolerance used when comparing lengths, relative to their magnitude
+const RELATIVE_TOLERANCE: f64 = 1e-6;
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use fj_core::{
+        algorithms::{
+            approx::Approx, bounding_volume::BoundingVolume,
+            triangulate::Triangulate,
+        },
+        objects::{Cycle, Region, Shell, Sketch, Solid},
+        operations::{
+            build::{BuildCycle, BuildRegion, BuildShell, BuildSketch},
+            insert::Insert,
+            reverse::Reverse,
+            sweep::SweepSketch,
+            update::{UpdateRegion, UpdateSketch},
+        },
+        storage::Handle,
+        Instance,
+    };
+    use fj_export::{ExportOptions, Unit};
+    use fj_math::{Point, Scalar, Vector};
+
+    use crate::Error;
+
+    use super::import_step;
+
+    #[test]
+    fn import_exported_spacer() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let bottom_surface = core.services.objects.surfaces.xy_plane();
+        let spacer = Sketch::empty()
+            .add_region(
+                Region::circle(Point::origin(), 2., &mut core)
+                    .add_interiors([Cycle::circle(
+                        Point::origin(),
+                        1.,
+                        &mut core,
+                    )
+                    .reverse(&mut core)
+                    .insert(&mut core.services)])
+                    .insert(&mut core.services),
+            )
+            .sweep_sketch(
+                bottom_surface,
+                Vector::from([0., 0., 1.]),
+                &mut core,
+            );
+
+        let path = env::temp_dir()
+            .join(format!("fj-import-spacer-{}.step", std::process::id()));
+        fj_export::export_step(&spacer, &path)?;
+        let imported = import_step(&path, &mut core);
+        fs::remove_file(&path)?;
+        let imported = imported?;
+
+        assert_eq!(imported.shells().len(), 1);
+        let shell = imported.shells().iter().next().unwrap();
+        assert_eq!(shell.faces().len(), 4);
+
+        core.services.drop_and_validate()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn import_exported_void() -> anyhow::Result<()> {
+        let mut core = Instance::new();
+
+        let [outer, inner] = [
+            [[0., 0., 0.], [0., 4., 0.], [4., 0., 0.], [0., 0., 4.]],
+            [
+                [0.5, 0.5, 0.5],
+                [0.5, 1., 0.5],
+                [1., 0.5, 0.5],
+                [0.5, 0.5, 1.],
+            ],
+        ]
+        .map(|points| {
+            Shell::tetrahedron(points, &mut core)
+                .shell
+                .insert(&mut core.services)
+        });
+        let void =
+            Shell::new(inner.faces().iter().map(|face| {
+                face.reverse(&mut core).insert(&mut core.services)
+            }))
+            .insert(&mut core.services);
+        let solid = Solid::new([outer, void]).insert(&mut core.services);
+
+        let path = env::temp_dir()
+            .join(format!("fj-import-void-{}.step", std::process::id()));
+        fj_export::export_step(&solid, &path)?;
+        let imported = import_step(&path, &mut core);
+        fs::remove_file(&path)?;
+        let imported = imported?;
+
+        // The outer shell must face outward, the void inward.
+        let mut volumes = imported
+            .shells()
+            .iter()
+            .map(|shell| volume(shell))
+            .collect::<Result<Vec<_>, _>>()?;
+        volumes.sort();
+        assert_eq!(volumes.len(), 2);
+        assert!(volumes[0] < Scalar::ZERO);
+        assert!(volumes[1] > Scalar::ZERO);
+        core.services.drop_and_validate()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn length_units() -> anyhow::Result<()> {
+        let millimeters = tetrahedron_step()?;
+        let centimeters = millimeters
+            .replace("SI_UNIT(.MILLI.,.METRE.)", "SI_UNIT(.CENTI.,.METRE.)");
+        let inches = millimeters.replace(
+            "(LENGTH_UNIT()NAMED_UNIT(*)SI_UNIT(.MILLI.,.METRE.))",
+            "(CONVERSION_BASED_UNIT('INCH',#1000)LENGTH_UNIT()NAMED_UNIT(*));\n\
+            #1000=LENGTH_MEASURE_WITH_UNIT(LENGTH_MEASURE(25.4),#1001);\n\
+            #1001=(LENGTH_UNIT()NAMED_UNIT(*)SI_UNIT(.MILLI.,.METRE.))",
+        );
+        assert_ne!(centimeters, millimeters);
+        assert_ne!(inches, millimeters);
+
+        for (name, source, scale) in [
+            ("millimeters", millimeters, 1.),
+            ("centimeters", centimeters, 10.),
+            ("inches", inches, 25.4),
+        ] {
+            let mut core = Instance::new();
+
+            let solid = import_source(name, &source, &mut core)?;
+            let size = solid.aabb().map(|aabb| aabb.size());
+            let expected = Vector::from([scale, scale, scale]);
+            assert!(
+                size.is_some_and(|size| (size - expected).magnitude()
+                    < Scalar::from(1e-9 * scale)),
+                "unexpected size for {name}: {size:?}",
+            );
+
+            core.services.drop_and_validate()?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_entities() -> anyhow::Result<()> {
+        let source = tetrahedron_step()?;
+
+        for (name, from, to) in [
+            ("sphere", "=PLANE(", "=SPHERICAL_SURFACE("),
+            ("spline", "=LINE(", "=B_SPLINE_CURVE_WITH_KNOTS("),
+        ] {
+            let mut core = Instance::new();
+
+            let source = source.replace(from, to);
+            assert_ne!(source, tetrahedron_step()?);
+
+            let entity = &to[1..to.len() - 1];
+            let result = import_source(name, &source, &mut core);
+            assert!(
+                matches!(
+                    &result,
+                    Err(Error::StepUnsupported { entity: e, .. }) if e == entity
+                ),
+                "unexpected result for {name}: {result:?}",
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn out_of_range_numbers() -> anyhow::Result<()> {
+        let source = tetrahedron_step()?;
+
+        // Numbers with an exponent this large are parsed as infinity.
+        for (name, from, to) in [
+            (
+                "point",
+                "CARTESIAN_POINT('',(1.0,",
+                "CARTESIAN_POINT('',(1.E400,",
+            ),
+            ("direction", "DIRECTION('',(0.0,", "DIRECTION('',(1.E400,"),
+        ] {
+            let mut core = Instance::new();
+
+            let source = source.replacen(from, to, 1);
+            assert_ne!(source, tetrahedron_step()?);
+
+            let result = import_source(name, &source, &mut core);
+            assert!(
+                matches!(&result, Err(Error::StepInvalid { .. })),
+                "unexpected result for {name}: {result:?}",
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn exported_length_units() -> anyhow::Result<()> {
+        for (unit, scale) in [
+            (Unit::Micron, 1.),
+            (Unit::Centimeter, 2.),
+            (Unit::Meter, 1.),
+            (Unit::Inch, 0.5),
+            (Unit::Foot, 1.),
+        ] {
+            let source = tetrahedron_step_with_options(&ExportOptions {
+                unit,
+                scale,
+                ..ExportOptions::default()
+            })?;
+
+            let mut core = Instance::new();
+
+            // Whatever the unit of the file, the imported model must have the
+            // size of the exported one, scaled.
+            let solid = import_source("units", &source, &mut core)?;
+            let size = solid.aabb().map(|aabb| aabb.size());
+            let expected = Vector::from([scale, scale, scale]);
+            assert!(
+                size.is_some_and(
+                    |size| (size - expected).magnitude() < Scalar::from(1e-9)
+                ),
+                "unexpected size for {unit:?}: {size:?}",
+            );
+
+            core.services.drop_and_validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Export a tetrahedron to STEP, with millimeters as the length unit
+    fn tetrahedron_step() -> anyhow::Result<String> {
+        tetrahedron_step_with_options(&ExportOptions {
+            name: Some("tetrahedron".into()),
+            ..ExportOptions::default()
+        })
+    }
+
+    fn tetrahedron_step_with_options(
+        options: &ExportOptions,
+    ) -> anyhow::Result<String> {
+        let mut core = Instance::new();
+
+        let shell = Shell::tetrahedron(
+            [[0., 0., 0.], [0., 1., 0.], [1., 0., 0.], [0., 0., 1.]],
+            &mut core,
+        )
+        .shell
+        .insert(&mut core.services);
+        let solid = Solid::new([shell]);
+
+        let mut step = Vec::new();
+        fj_export::export_step_to_writer(&solid, options, &mut step)?;
+
+        core.services.drop_and_validate()?;
+        Ok(String::from_utf8(step)?)
+    }
+
+    fn import_source(
+        name: &str,
+        source: &str,
+        core: &mut Instance,
+    ) -> Result<Handle<Solid>, Error> {
+        let path = env::temp_dir()
+            .join(format!("fj-import-{name}-{}.step", std::process::id()));
+        fs::write(&path, source)?;
+        let result = import_step(&path, core);
+        fs::remove_file(&path)?;
+
+        result
+    }
+
+    /// Compute the signed volume enclosed by a shell
+    fn volume(shell: &Shell) -> anyhow::Result<Scalar> {
+        let mut volume = Scalar::ZERO;
+
+        // Faces are triangulated one by one, as approximating the whole shell
+        // would also check it for points that are very close, but not
+        // identical. That happens for imported shells, due to floating point
+        // inaccuracies.
+        for face in shell.faces() {
+            let mesh = face.approx(0.1)?.triangulate()?;
+            for triangle in mesh.triangles() {
+                let [a, b, c] =
+                    triangle.inner.points().map(|point| point.coords);
+                volume += a.dot(&b.cross(&c)) / 6.;
+            }
+        }
+
+        Ok(volume)
+    }
+}
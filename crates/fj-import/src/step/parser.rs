@@ -0,0 +1,430 @@
+//! Parser for the STEP exchange structure (ISO 10303-21)
+//!
+//! Only the data section is parsed. The header is skipped, as it doesn't contain
+//! anything that is relevant for importing geometry.
+
+use std::collections::BTreeMap;
+
+use crate::Error;
+
+/// The entities from the data section of a STEP file, by their id
+pub type Entities = BTreeMap<u64, Entity>;
+
+/// An entity instance from a STEP file
+///
+/// Most entities are simple and consist of a single record. Complex entities
+/// consist of multiple records, one for each of their types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entity {
+    pub records: Vec<Record>,
+}
+
+impl Entity {
+    /// Access the record of the given type, if the entity has one
+    pub fn record(&self, name: &str) -> Option<&Record> {
+        self.records.iter().find(|record| record.name == name)
+    }
+
+    /// Describe the entity's type, for use in error messages
+    pub fn type_name(&self) -> String {
+        self.records
+            .iter()
+            .map(|record| record.name.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// A typed list of parameters
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub name: String,
+    pub parameters: Vec<Parameter>,
+}
+
+/// A parameter of a record
+#[derive(Clone, Debug, PartialEq)]
+pub enum Parameter {
+    Reference(u64),
+    Integer(i64),
+    Real(f64),
+    String(String),
+    Enumeration(String),
+    Binary(String),
+    List(Vec<Parameter>),
+    Typed(Box<Record>),
+    Unset,
+    Derived,
+}
+
+/// Parse the data section of a STEP file
+pub fn parse(source: &str) -> Result<Entities, Error> {
+    let mut parser = Parser {
+        source: source.as_bytes(),
+        position: 0,
+    };
+    let mut entities = BTreeMap::new();
+
+    // Skip everything up to and including the start of the data section. The
+    // data section header can optionally have parameters.
+    loop {
+        let keyword = parser.keyword_or_skip()?;
+        if keyword.as_deref() == Some("DATA") {
+            parser.skip_to(b';')?;
+            break;
+        }
+    }
+
+    loop {
+        parser.skip_whitespace();
+
+        match parser.peek() {
+            Some(b'#') => {
+                let id = parser.reference()?;
+                parser.expect(b'=')?;
+
+                let records = if parser.peek_after_whitespace() == Some(b'(') {
+                    parser.expect(b'(')?;
+
+                    let mut records = Vec::new();
+                    while parser.peek_after_whitespace() != Some(b')') {
+                        records.push(parser.record()?);
+                    }
+                    parser.expect(b')')?;
+
+                    records
+                } else {
+                    vec![parser.record()?]
+                };
+                parser.expect(b';')?;
+
+                entities.insert(id, Entity { records });
+            }
+            Some(_) => {
+                let keyword = parser.keyword()?;
+                if keyword == "ENDSEC" {
+                    parser.expect(b';')?;
+                    break;
+                }
+
+                return Err(parser.error(format!(
+                    "expected entity instance, found `{keyword}`"
+                )));
+            }
+            None => {
+                return Err(parser.error("unexpected end of data section"));
+            }
+        }
+    }
+
+    Ok(entities)
+}
+
+struct Parser<'r> {
+    source: &'r [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn record(&mut self) -> Result<Record, Error> {
+        let name = self.keyword()?;
+        self.expect(b'(')?;
+        let parameters = self.parameters()?;
+
+        Ok(Record { name, parameters })
+    }
+
+    /// Parse a parameter list, after its opening parenthesis
+    fn parameters(&mut self) -> Result<Vec<Parameter>, Error> {
+        let mut parameters = Vec::new();
+
+        if self.peek_after_whitespace() == Some(b')') {
+            self.position += 1;
+            return Ok(parameters);
+        }
+
+        loop {
+            parameters.push(self.parameter()?);
+
+            match self.peek_after_whitespace() {
+                Some(b',') => self.position += 1,
+                Some(b')') => {
+                    self.position += 1;
+                    return Ok(parameters);
+                }
+                _ => return Err(self.error("expected `,` or `)`")),
+            }
+        }
+    }
+
+    fn parameter(&mut self) -> Result<Parameter, Error> {
+        let parameter = match self.peek_after_whitespace() {
+            Some(b'#') => Parameter::Reference(self.reference()?),
+            Some(b'$') => {
+                self.position += 1;
+                Parameter::Unset
+            }
+            Some(b'*') => {
+                self.position += 1;
+                Parameter::Derived
+            }
+            Some(b'(') => {
+                self.position += 1;
+                Parameter::List(self.parameters()?)
+            }
+            Some(b'\'') => Parameter::String(self.string()?),
+            Some(b'"') => {
+                self.position += 1;
+                let start = self.position;
+                self.skip_to(b'"')?;
+                let binary = &self.source[start..self.position - 1];
+                Parameter::Binary(String::from_utf8_lossy(binary).into_owned())
+            }
+            Some(b'.') => {
+                self.position += 1;
+                let start = self.position;
+                self.skip_to(b'.')?;
+                let value = &self.source[start..self.position - 1];
+                Parameter::Enumeration(
+                    String::from_utf8_lossy(value).into_owned(),
+                )
+            }
+            Some(c) if c == b'-' || c == b'+' || c.is_ascii_digit() => {
+                self.number()?
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == b'!' => {
+                Parameter::Typed(Box::new(self.record()?))
+            }
+            _ => return Err(self.error("expected parameter")),
+        };
+
+        Ok(parameter)
+    }
+
+    fn reference(&mut self) -> Result<u64, Error> {
+        self.expect(b'#')?;
+
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+
+        self.text(start)
+            .parse()
+            .map_err(|_| self.error("expected entity id"))
+    }
+
+    fn number(&mut self) -> Result<Parameter, Error> {
+        let start = self.position;
+        let mut is_real = false;
+
+        while let Some(c) = self.peek() {
+            match c {
+                b'0'..=b'9' | b'-' | b'+' => {}
+                b'.' | b'E' | b'e' => is_real = true,
+                _ => break,
+            }
+            self.position += 1;
+        }
+
+        let text = self.text(start);
+        let parameter = if is_real {
+            // Rust doesn't accept a decimal point that is directly followed by
+            // the exponent, while STEP files commonly contain such numbers.
+            let text = text.replace(".E", ".0E").replace(".e", ".0e");
+            text.parse().map(Parameter::Real).ok()
+        } else {
+            text.parse().map(Parameter::Integer).ok()
+        };
+
+        parameter.ok_or_else(|| self.error(format!("invalid number `{text}`")))
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        self.expect(b'\'')?;
+
+        let mut value = Vec::new();
+        loop {
+            match self.peek() {
+                Some(b'\'')
+                    if self.source.get(self.position + 1) == Some(&b'\'') =>
+                {
+                    value.push(b'\'');
+                    self.position += 2;
+                }
+                Some(b'\'') => {
+                    self.position += 1;
+                    break;
+                }
+                Some(c) => {
+                    value.push(c);
+                    self.position += 1;
+                }
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+
+        Ok(String::from_utf8_lossy(&value).into_owned())
+    }
+
+    fn keyword(&mut self) -> Result<String, Error> {
+        self.skip_whitespace();
+
+        let start = self.position;
+        while self.peek().is_some_and(|c| {
+            c.is_ascii_alphanumeric() || c == b'_' || c == b'!'
+        }) {
+            self.position += 1;
+        }
+
+        if start == self.position {
+            return Err(self.error("expected keyword"));
+        }
+
+        Ok(self.text(start).to_ascii_uppercase())
+    }
+
+    /// Parse a keyword, or skip a single character that can't start one
+    fn keyword_or_skip(&mut self) -> Result<Option<String>, Error> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() => self.keyword().map(Some),
+            Some(b'\'') => {
+                // Strings could contain anything, including keywords.
+                self.string()?;
+                Ok(None)
+            }
+            Some(_) => {
+                self.position += 1;
+                Ok(None)
+            }
+            None => Err(self.error("file has no data section")),
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), Error> {
+        if self.peek_after_whitespace() != Some(expected) {
+            return Err(
+                self.error(format!("expected `{}`", char::from(expected)))
+            );
+        }
+
+        self.position += 1;
+        Ok(())
+    }
+
+    /// Skip past the next occurrence of a character
+    fn skip_to(&mut self, c: u8) -> Result<(), Error> {
+        match self.source[self.position..].iter().position(|&x| x == c) {
+            Some(offset) => {
+                self.position += offset + 1;
+                Ok(())
+            }
+            None => Err(self.error(format!("expected `{}`", char::from(c)))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_ascii_whitespace() => self.position += 1,
+                Some(b'/')
+                    if self.source.get(self.position + 1) == Some(&b'*') =>
+                {
+                    let end = self.source[self.position + 2..]
+                        .windows(2)
+                        .position(|window| window == b"*/");
+
+                    self.position = match end {
+                        Some(end) => self.position + 2 + end + 2,
+                        None => self.source.len(),
+                    };
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.source.get(self.position).copied()
+    }
+
+    fn peek_after_whitespace(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.peek()
+    }
+
+    fn text(&self, start: usize) -> String {
+        String::from_utf8_lossy(&self.source[start..self.position]).into_owned()
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        let line = self.source[..self.position]
+            .iter()
+            .filter(|&&c| c == b'\n')
+            .count()
+            + 1;
+
+        Error::StepSyntax {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Parameter};
+
+    #[test]
+    fn parse_entities() -> anyhow::Result<()> {
+        let source = "\
+            ISO-10303-21;
+            HEADER;
+            FILE_NAME('DATA;','',(''),(''),'','','');
+            ENDSEC;
+            DATA;
+            #1=CARTESIAN_POINT('it''s',(0.,-1.5,2.E-3));
+            /* comment */
+            #2=(NAMED_UNIT(*)SI_UNIT(.MILLI.,.METRE.)LENGTH_UNIT());
+            #3=LINE('',#1,$);
+            ENDSEC;
+            END-ISO-10303-21;
+        ";
+
+        let entities = parse(source)?;
+        assert_eq!(entities.len(), 3);
+
+        let point = &entities[&1].records[0];
+        assert_eq!(point.name, "CARTESIAN_POINT");
+        assert_eq!(
+            point.parameters,
+            vec![
+                Parameter::String("it's".into()),
+                Parameter::List(vec![
+                    Parameter::Real(0.),
+                    Parameter::Real(-1.5),
+                    Parameter::Real(2e-3),
+                ]),
+            ]
+        );
+
+        let unit = &entities[&2];
+        assert_eq!(unit.records.len(), 3);
+        assert_eq!(
+            unit.record("SI_UNIT").map(|record| &record.parameters),
+            Some(&vec![
+                Parameter::Enumeration("MILLI".into()),
+                Parameter::Enumeration("METRE".into()),
+            ])
+        );
+
+        assert_eq!(
+            entities[&3].records[0].parameters[1],
+            Parameter::Reference(1)
+        );
+
+        Ok(())
+    }
+}
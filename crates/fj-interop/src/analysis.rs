@@ -0,0 +1,245 @@
+use std::{collections::BTreeMap, fmt};
+
+use fj_math::{Point, Scalar};
+
+use crate::Mesh;
+
+impl Mesh<Point<3>> {
+    /// Analyze the mesh for problems that would make it invalid
+    ///
+    /// Many consumers of meshes (3D printing slicers, for example) expect a
+    /// closed, manifold mesh with consistent winding. This method checks for
+    /// any violations of those expectations. See [`MeshAnalysis`].
+    pub fn analyze(&self) -> MeshAnalysis {
+        let mut analysis = MeshAnalysis::default();
+
+        let mut triangles_by_directed_edge: BTreeMap<_, usize> =
+            BTreeMap::new();
+        let mut triangles_by_points: BTreeMap<_, usize> = BTreeMap::new();
+
+        for triangle in self.triangles() {
+            let triangle = triangle.inner;
+            let [a, b, c] = triangle.points();
+
+            for edge in [[a, b], [b, c], [c, a]] {
+                *triangles_by_directed_edge.entry(edge).or_default() += 1;
+            }
+
+            *triangles_by_points.entry(triangle.normalize()).or_default() += 1;
+
+            // A triangle whose height is tiny compared to its longest side
+            // might as well have no area. Those lead to numerical problems in
+            // many algorithms.
+            let double_area = (b - a).cross(&(c - a)).magnitude();
+            let longest_side = [(a, b), (b, c), (c, a)]
+                .map(|(a, b)| a.distance_to(&b))
+                .into_iter()
+                .max()
+                .unwrap_or(Scalar::ZERO);
+            if double_area
+                <= longest_side * longest_side * DEGENERATE_RELATIVE_HEIGHT
+            {
+                analysis.degenerate_triangles.push(triangle);
+            }
+        }
+
+        let mut undirected_edges: BTreeMap<_, [usize; 2]> = BTreeMap::new();
+        for ([a, b], num_triangles) in triangles_by_directed_edge {
+            let (edge, direction) =
+                if a <= b { ([a, b], 0) } else { ([b, a], 1) };
+            undirected_edges.entry(edge).or_default()[direction] +=
+                num_triangles;
+        }
+
+        for (edge, [forward, backward]) in undirected_edges {
+            match forward + backward {
+                1 => analysis.boundary_edges.push(edge),
+                2 => {
+                    if forward != backward {
+                        analysis.inconsistent_winding.push(edge);
+                    }
+                }
+                _ => analysis.non_manifold_edges.push(edge),
+            }
+        }
+
+        for (triangle, num_triangles) in triangles_by_points {
+            if num_triangles > 1 {
+                analysis.duplicate_triangles.push(triangle);
+            }
+        }
+
+        analysis
+    }
+}
+
+/// The result of a mesh analysis
+///
+/// See [`Mesh::analyze`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MeshAnalysis {
+    /// Edges that are shared by more than two triangles
+    pub non_manifold_edges: Vec<[Point<3>; 2]>,
+
+    /// Edges that are only part of one triangle, meaning the mesh is not closed
+    pub boundary_edges: Vec<[Point<3>; 2]>,
+
+    /// Edges whose two triangles traverse them in the same direction
+    ///
+    /// This means one of the triangles is wound the wrong way, and faces in the
+    /// wrong direction.
+    pub inconsistent_winding: Vec<[Point<3>; 2]>,
+
+    /// Triangles that have no significant area
+    pub degenerate_triangles: Vec<fj_math::Triangle<3>>,
+
+    /// Triangles that occur more than once in the mesh
+    ///
+    /// Triangles are considered to be duplicates, if they have the same points,
+    /// regardless of their winding.
+    pub duplicate_triangles: Vec<fj_math::Triangle<3>>,
+}
+
+impl MeshAnalysis {
+    /// Indicate whether the analysis found no problems
+    pub fn is_valid(&self) -> bool {
+        self.non_manifold_edges.is_empty()
+            && self.boundary_edges.is_empty()
+            && self.inconsistent_winding.is_empty()
+            && self.degenerate_triangles.is_empty()
+            && self.duplicate_triangles.is_empty()
+    }
+}
+
+impl fmt::Display for MeshAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_valid() {
+            return write!(f, "Mesh is valid");
+        }
+
+        writeln!(f, "Mesh is invalid:")?;
+
+        for (problems, description) in [
+            (self.non_manifold_edges.len(), "non-manifold edges"),
+            (self.boundary_edges.len(), "open boundary edges"),
+            (
+                self.inconsistent_winding.len(),
+                "edges with inconsistent winding",
+            ),
+            (self.degenerate_triangles.len(), "degenerate triangles"),
+            (self.duplicate_triangles.len(), "duplicate triangles"),
+        ] {
+            if problems > 0 {
+                writeln!(f, "- {problems} {description}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The height of a triangle, relative to its longest side, below which it is
+/// considered degenerate
+const DEGENERATE_RELATIVE_HEIGHT: f64 = 1e-10;
+
+#[cfg(test)]
+mod tests {
+    use fj_math::Point;
+
+    use crate::{Color, Mesh};
+
+    #[test]
+    fn valid() {
+        let mesh = tetrahedron(|_| true);
+
+        let analysis = mesh.analyze();
+        assert!(analysis.is_valid(), "{analysis}");
+    }
+
+    #[test]
+    fn non_manifold_edges() {
+        let mut mesh = tetrahedron(|_| true);
+        let [a, b, _, _] = points();
+        mesh.push_triangle(
+            [b, a, Point::from([0., -1., 0.])],
+            Color::default(),
+        );
+
+        let analysis = mesh.analyze();
+        assert_eq!(analysis.non_manifold_edges, vec![[a, b]]);
+    }
+
+    #[test]
+    fn boundary_edges() {
+        let mesh = tetrahedron(|i| i != 0);
+
+        let analysis = mesh.analyze();
+        assert_eq!(analysis.boundary_edges.len(), 3);
+        assert!(analysis.non_manifold_edges.is_empty());
+        assert!(analysis.inconsistent_winding.is_empty());
+    }
+
+    #[test]
+    fn inconsistent_winding() {
+        let mut mesh = Mesh::new();
+        for (i, [a, b, c]) in TRIANGLES.into_iter().enumerate() {
+            let triangle = if i == 0 { [a, c, b] } else { [a, b, c] };
+            mesh.push_triangle(triangle.map(|i| points()[i]), Color::default());
+        }
+
+        let analysis = mesh.analyze();
+        assert_eq!(analysis.inconsistent_winding.len(), 3);
+        assert!(analysis.boundary_edges.is_empty());
+        assert!(analysis.non_manifold_edges.is_empty());
+    }
+
+    #[test]
+    fn degenerate_triangles() {
+        let mut mesh = tetrahedron(|_| true);
+        let degenerate =
+            [[0., 0., 0.], [1., 0., 0.], [2., 0., 1e-12]].map(Point::from);
+        mesh.push_triangle(degenerate, Color::default());
+
+        let analysis = mesh.analyze();
+        assert_eq!(analysis.degenerate_triangles, vec![degenerate.into()]);
+    }
+
+    #[test]
+    fn duplicate_triangles() {
+        let mut mesh = tetrahedron(|_| true);
+        let [a, b, c] = TRIANGLES[0].map(|i| points()[i]);
+        mesh.push_triangle([a, c, b], Color::default());
+
+        let analysis = mesh.analyze();
+        assert_eq!(
+            analysis.duplicate_triangles,
+            vec![fj_math::Triangle::from([a, b, c]).normalize()]
+        );
+    }
+
+    fn points() -> [Point<3>; 4] {
+        [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]]
+            .map(Point::from)
+    }
+
+    const TRIANGLES: [[usize; 3]; 4] =
+        [[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]];
+
+    /// Build a tetrahedron, with outward-facing triangles
+    ///
+    /// Only includes the triangles whose index is accepted by the filter.
+    fn tetrahedron(filter: impl Fn(usize) -> bool) -> Mesh<Point<3>> {
+        let mut mesh = Mesh::new();
+
+        for (i, triangle) in TRIANGLES.into_iter().enumerate() {
+            if filter(i) {
+                mesh.push_triangle(
+                    triangle.map(|i| points()[i]),
+                    Color::default(),
+                );
+            }
+        }
+
+        mesh
+    }
+}
@@ -9,14 +9,18 @@
 //!
 //! [Fornjot]: https://www.fornjot.app/
 
+mod analysis;
 mod color;
 mod mesh;
 mod model;
+mod simplify;
 
 pub mod ext;
 
 pub use self::{
+    analysis::MeshAnalysis,
     color::Color,
     mesh::{Index, Mesh, Triangle},
     model::Model,
+    simplify::Simplification,
 };
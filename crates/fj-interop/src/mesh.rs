@@ -1,4 +1,4 @@
-use std::{collections::HashMap, hash::Hash};
+use std::{collections::HashMap, hash::Hash, iter};
 
 use fj_math::Point;
 
@@ -12,6 +12,9 @@ pub struct Mesh<V> {
 
     indices_by_vertex: HashMap<V, Index>,
     triangles: Vec<Triangle>,
+
+    /// The indices of the triangles that start a new face
+    face_starts: Vec<usize>,
 }
 
 impl<V> Mesh<V>
@@ -69,6 +72,34 @@ where
     pub fn triangles(&self) -> impl Iterator<Item = Triangle> + '_ {
         self.triangles.iter().copied()
     }
+
+    /// Mark the start of a new face
+    ///
+    /// All triangles that are added after this call belong to the new face,
+    /// until this method is called again.
+    pub fn start_face(&mut self) {
+        let start = self.triangles.len();
+        if self.face_starts.last() != Some(&start) {
+            self.face_starts.push(start);
+        }
+    }
+
+    /// Access the triangles of the mesh, grouped by the face they belong to
+    ///
+    /// Triangles that were added before the first call to
+    /// [`Mesh::start_face`] are grouped into a face of their own.
+    pub fn faces(&self) -> impl Iterator<Item = &[Triangle]> + '_ {
+        let starts = iter::once(0).chain(self.face_starts.iter().copied());
+        let ends = starts
+            .clone()
+            .skip(1)
+            .chain(iter::once(self.triangles.len()));
+
+        starts
+            .zip(ends)
+            .map(|(start, end)| &self.triangles[start..end])
+            .filter(|face| !face.is_empty())
+    }
 }
 
 impl Mesh<Point<3>> {
@@ -100,6 +131,7 @@ impl<V> Default for Mesh<V> {
             indices: Vec::default(),
             indices_by_vertex: HashMap::default(),
             triangles: Vec::default(),
+            face_starts: Vec::default(),
         }
     }
 }
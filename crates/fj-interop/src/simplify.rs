@@ -0,0 +1,624 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, BinaryHeap, HashMap},
+};
+
+use fj_math::{Point, Scalar, Vector};
+
+use crate::{Color, Mesh};
+
+/// Defines how far a mesh is simplified
+///
+/// See [`Mesh::simplify`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Simplification {
+    /// The number of triangles to simplify the mesh down to
+    ///
+    /// The simplification stops, once the mesh has this many triangles (or
+    /// less). The result can have more triangles, if the simplification runs
+    /// out of edges that can be collapsed without violating any of the other
+    /// requirements.
+    pub target_triangles: Option<usize>,
+
+    /// The maximum error that simplification may introduce
+    ///
+    /// The error is the squared distance of a moved vertex from the planes of
+    /// the original triangles that it was part of.
+    pub max_error: Option<Scalar>,
+}
+
+impl Simplification {
+    /// Simplify down to the given number of triangles
+    pub fn to_triangle_count(target_triangles: usize) -> Self {
+        Self {
+            target_triangles: Some(target_triangles),
+            max_error: None,
+        }
+    }
+
+    /// Simplify as far as possible, without exceeding the given error
+    pub fn to_max_error(max_error: impl Into<Scalar>) -> Self {
+        Self {
+            target_triangles: None,
+            max_error: Some(max_error.into()),
+        }
+    }
+}
+
+impl Mesh<Point<3>> {
+    /// Simplify the mesh, by collapsing edges
+    ///
+    /// Uses quadric error metrics (Garland and Heckbert) to decide which edges
+    /// to collapse first, and where to put the vertex that results from the
+    /// collapse.
+    ///
+    /// Vertices on the boundary of the mesh, and vertices on an edge between
+    /// triangles of different colors, are never moved or removed. This
+    /// preserves the boundaries of the mesh, as well as its color regions.
+    ///
+    /// Remaining triangles stay in the face they belonged to (see
+    /// [`Mesh::faces`]). Faces whose triangles have all been removed are
+    /// dropped.
+    ///
+    /// If neither a target triangle count nor a maximum error are specified in
+    /// `simplification`, the mesh is returned unchanged.
+    pub fn simplify(&self, simplification: Simplification) -> Self {
+        let mut simplifier = Simplifier::new(self);
+
+        if simplification.target_triangles.is_some()
+            || simplification.max_error.is_some()
+        {
+            simplifier.run(simplification);
+        }
+
+        simplifier.into_mesh()
+    }
+}
+
+struct Simplifier {
+    positions: Vec<Point<3>>,
+    quadrics: Vec<Quadric>,
+    locked: Vec<bool>,
+    versions: Vec<u64>,
+    triangles_of_vertex: Vec<BTreeSet<usize>>,
+
+    triangles: Vec<[usize; 3]>,
+    colors: Vec<Color>,
+    faces: Vec<usize>,
+    removed: Vec<bool>,
+    num_triangles: usize,
+}
+
+impl Simplifier {
+    fn new(mesh: &Mesh<Point<3>>) -> Self {
+        let mut positions = Vec::new();
+        let mut indices = HashMap::new();
+        let mut triangles = Vec::new();
+        let mut colors = Vec::new();
+        let mut faces = Vec::new();
+
+        for (face, triangles_of_face) in mesh.faces().enumerate() {
+            for triangle in triangles_of_face {
+                let points = triangle.inner.points();
+                let indices = points.map(|point| {
+                    *indices.entry(point).or_insert_with(|| {
+                        positions.push(point);
+                        positions.len() - 1
+                    })
+                });
+
+                triangles.push(indices);
+                colors.push(triangle.color);
+                faces.push(face);
+            }
+        }
+
+        let mut quadrics = vec![Quadric::default(); positions.len()];
+        let mut triangles_of_vertex = vec![BTreeSet::new(); positions.len()];
+
+        for (i, triangle) in triangles.iter().enumerate() {
+            let quadric =
+                Quadric::from_triangle(triangle.map(|i| positions[i]));
+
+            for &vertex in triangle {
+                quadrics[vertex] = quadrics[vertex] + quadric;
+                triangles_of_vertex[vertex].insert(i);
+            }
+        }
+
+        // Vertices on the boundary of the mesh or of a color region must stay
+        // where they are. Same goes for vertices on non-manifold edges, as we
+        // can't reason about those.
+        let mut locked = vec![false; positions.len()];
+        let mut triangles_of_edge: HashMap<_, Vec<usize>> = HashMap::new();
+        for (i, triangle) in triangles.iter().enumerate() {
+            for edge in edges_of_triangle(*triangle) {
+                triangles_of_edge.entry(edge).or_default().push(i);
+            }
+        }
+        for ((a, b), triangles_of_edge) in triangles_of_edge {
+            let is_boundary_or_non_manifold = triangles_of_edge.len() != 2;
+            let is_color_boundary = triangles_of_edge
+                .iter()
+                .any(|&i| colors[i] != colors[triangles_of_edge[0]]);
+
+            if is_boundary_or_non_manifold || is_color_boundary {
+                locked[a] = true;
+                locked[b] = true;
+            }
+        }
+
+        let num_triangles = triangles.len();
+
+        Self {
+            versions: vec![0; positions.len()],
+            positions,
+            quadrics,
+            locked,
+            triangles_of_vertex,
+            removed: vec![false; triangles.len()],
+            triangles,
+            colors,
+            faces,
+            num_triangles,
+        }
+    }
+
+    fn run(&mut self, simplification: Simplification) {
+        let mut queue = BinaryHeap::new();
+
+        let mut edges = BTreeSet::new();
+        for triangle in &self.triangles {
+            edges.extend(edges_of_triangle(*triangle));
+        }
+        for (a, b) in edges {
+            self.push_collapse(a, b, &mut queue);
+        }
+
+        while let Some(Reverse(collapse)) = queue.pop() {
+            if let Some(target) = simplification.target_triangles {
+                if self.num_triangles <= target {
+                    break;
+                }
+            }
+            if let Some(max_error) = simplification.max_error {
+                if collapse.cost > max_error {
+                    break;
+                }
+            }
+
+            let [a, b] = collapse.vertices;
+            let is_outdated = self.versions[a] != collapse.versions[0]
+                || self.versions[b] != collapse.versions[1];
+            if is_outdated {
+                continue;
+            }
+
+            if !self.can_collapse(a, b, collapse.position) {
+                continue;
+            }
+
+            self.collapse(a, b, collapse.position);
+
+            let neighbors = self.neighbors(b);
+            for neighbor in neighbors {
+                self.push_collapse(b, neighbor, &mut queue);
+            }
+        }
+    }
+
+    fn push_collapse(
+        &self,
+        a: usize,
+        b: usize,
+        queue: &mut BinaryHeap<Reverse<Collapse>>,
+    ) {
+        // The vertex that is collapsed into the other one is removed, so it
+        // must not be locked. If both aren't locked, it doesn't matter.
+        let (a, b) = match (self.locked[a], self.locked[b]) {
+            (true, true) => return,
+            (true, false) => (b, a),
+            (false, _) => (a, b),
+        };
+
+        let quadric = self.quadrics[a] + self.quadrics[b];
+
+        let position = if self.locked[b] {
+            self.positions[b]
+        } else {
+            let [pa, pb] = [a, b].map(|i| self.positions[i]);
+            let midpoint = pa + (pb - pa) * 0.5;
+
+            let mut candidates = vec![pa, pb, midpoint];
+
+            // If the quadric is close to singular, the optimal position can be
+            // far away from the edge. We don't want to move vertices that far.
+            if let Some(optimal) = quadric.optimal_position() {
+                if optimal.distance_to(&midpoint) <= pa.distance_to(&pb) {
+                    candidates.push(optimal);
+                }
+            }
+
+            candidates
+                .into_iter()
+                .min_by_key(|&position| quadric.error(position))
+                .expect("Candidates are not empty")
+        };
+
+        queue.push(Reverse(Collapse {
+            cost: quadric.error(position),
+            vertices: [a, b],
+            versions: [self.versions[a], self.versions[b]],
+            position,
+        }));
+    }
+
+    fn can_collapse(&self, a: usize, b: usize, position: Point<3>) -> bool {
+        // To not change the topology of the mesh, the two vertices must share
+        // exactly two neighbors (the opposite vertices of the two triangles
+        // that share the collapsed edge).
+        let neighbors_a = self.neighbors(a);
+        let neighbors_b = self.neighbors(b);
+        if neighbors_a.intersection(&neighbors_b).count() != 2 {
+            return false;
+        }
+
+        // The remaining triangles must not flip or degenerate.
+        for &i in self.triangles_of_vertex[a]
+            .iter()
+            .chain(&self.triangles_of_vertex[b])
+        {
+            let triangle = self.triangles[i];
+            if triangle.contains(&a) && triangle.contains(&b) {
+                // This triangle is going to be removed.
+                continue;
+            }
+
+            let before = triangle.map(|i| self.positions[i]);
+            let after = triangle.map(|i| {
+                if i == a || i == b {
+                    position
+                } else {
+                    self.positions[i]
+                }
+            });
+
+            let normal_before = normal(before);
+            let normal_after = normal(after);
+
+            if normal_after.magnitude() == Scalar::ZERO
+                || normal_before.dot(&normal_after) <= Scalar::ZERO
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn collapse(&mut self, a: usize, b: usize, position: Point<3>) {
+        let triangles_of_a = std::mem::take(&mut self.triangles_of_vertex[a]);
+
+        for i in triangles_of_a {
+            if self.triangles[i].contains(&b) {
+                self.removed[i] = true;
+                self.num_triangles -= 1;
+
+                for vertex in self.triangles[i] {
+                    self.triangles_of_vertex[vertex].remove(&i);
+                }
+            } else {
+                for vertex in &mut self.triangles[i] {
+                    if *vertex == a {
+                        *vertex = b;
+                    }
+                }
+                self.triangles_of_vertex[b].insert(i);
+            }
+        }
+
+        self.positions[b] = position;
+        self.quadrics[b] = self.quadrics[a] + self.quadrics[b];
+
+        self.versions[a] += 1;
+        self.versions[b] += 1;
+    }
+
+    fn neighbors(&self, vertex: usize) -> BTreeSet<usize> {
+        self.triangles_of_vertex[vertex]
+            .iter()
+            .flat_map(|&i| self.triangles[i])
+            .filter(|&neighbor| neighbor != vertex)
+            .collect()
+    }
+
+    fn into_mesh(self) -> Mesh<Point<3>> {
+        let mut mesh = Mesh::new();
+        let mut current_face = None;
+
+        for (i, triangle) in self.triangles.into_iter().enumerate() {
+            if self.removed[i] {
+                continue;
+            }
+
+            if current_face != Some(self.faces[i]) {
+                mesh.start_face();
+                current_face = Some(self.faces[i]);
+            }
+
+            mesh.push_triangle(
+                triangle.map(|i| self.positions[i]),
+                self.colors[i],
+            );
+        }
+
+        mesh
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd)]
+struct Collapse {
+    cost: Scalar,
+    vertices: [usize; 2],
+    versions: [u64; 2],
+    position: Point<3>,
+}
+
+/// A symmetric 4x4 matrix that measures the distance from a set of planes
+#[derive(Clone, Copy, Debug, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn from_triangle(points: [Point<3>; 3]) -> Self {
+        let normal = normal(points);
+        let magnitude = normal.magnitude();
+        if magnitude == Scalar::ZERO {
+            return Self::default();
+        }
+
+        let [a, b, c] = normal.normalize().components.map(|s| s.into_f64());
+        let d = -(a * points[0].x.into_f64()
+            + b * points[0].y.into_f64()
+            + c * points[0].z.into_f64());
+
+        // Weighting by area makes sure that small triangles don't have an
+        // outsized influence.
+        let area = magnitude.into_f64() / 2.;
+
+        Self(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|value| value * area),
+        )
+    }
+
+    fn error(&self, point: Point<3>) -> Scalar {
+        let [x, y, z] = point.coords.components.map(|s| s.into_f64());
+        let [q11, q12, q13, q14, q22, q23, q24, q33, q34, q44] = self.0;
+
+        let error = q11 * x * x
+            + 2. * q12 * x * y
+            + 2. * q13 * x * z
+            + 2. * q14 * x
+            + q22 * y * y
+            + 2. * q23 * y * z
+            + 2. * q24 * y
+            + q33 * z * z
+            + 2. * q34 * z
+            + q44;
+
+        // Floating-point inaccuracies can make this slightly negative.
+        Scalar::from_f64(error.max(0.))
+    }
+
+    fn optimal_position(&self) -> Option<Point<3>> {
+        let [q11, q12, q13, q14, q22, q23, q24, q33, q34, _] = self.0;
+
+        let determinant = q11 * (q22 * q33 - q23 * q23)
+            - q12 * (q12 * q33 - q23 * q13)
+            + q13 * (q12 * q23 - q22 * q13);
+
+        if determinant.abs() < 1e-12 {
+            return None;
+        }
+
+        // Cramer's rule
+        let [b1, b2, b3] = [-q14, -q24, -q34];
+        let x = (b1 * (q22 * q33 - q23 * q23) - q12 * (b2 * q33 - q23 * b3)
+            + q13 * (b2 * q23 - q22 * b3))
+            / determinant;
+        let y = (q11 * (b2 * q33 - q23 * b3) - b1 * (q12 * q33 - q23 * q13)
+            + q13 * (q12 * b3 - b2 * q13))
+            / determinant;
+        let z = (q11 * (q22 * b3 - b2 * q23) - q12 * (q12 * b3 - b2 * q13)
+            + b1 * (q12 * q23 - q22 * q13))
+            / determinant;
+
+        Some(Point::from([x, y, z]))
+    }
+}
+
+impl std::ops::Add for Quadric {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut sum = self.0;
+        for (a, b) in sum.iter_mut().zip(rhs.0) {
+            *a += b;
+        }
+        Self(sum)
+    }
+}
+
+fn edges_of_triangle([a, b, c]: [usize; 3]) -> [(usize, usize); 3] {
+    [(a, b), (b, c), (c, a)].map(|(a, b)| (a.min(b), a.max(b)))
+}
+
+fn normal([a, b, c]: [Point<3>; 3]) -> Vector<3> {
+    (b - a).cross(&(c - a))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use fj_math::{Point, Scalar};
+
+    use crate::{Color, Mesh};
+
+    use super::{normal, Simplification};
+
+    const RED: Color = Color([255, 0, 0, 255]);
+    const BLUE: Color = Color([0, 0, 255, 255]);
+
+    #[test]
+    fn target_triangle_count() {
+        let mesh = grid(10, |_| 0., |_| RED);
+        assert_eq!(mesh.triangles().count(), 200);
+
+        let simplified = mesh.simplify(Simplification::to_triangle_count(100));
+
+        let num_triangles = simplified.triangles().count();
+        assert!(num_triangles <= 100, "{num_triangles} triangles left");
+        assert!(simplified.vertices().all(|vertex| vertex.z == Scalar::ZERO));
+    }
+
+    #[test]
+    fn max_error() {
+        // A flat grid, with a single peak in the middle.
+        let peak = Point::from([5., 5., 1.]);
+        let mesh =
+            grid(10, |[x, y]| if [x, y] == [5, 5] { 1. } else { 0. }, |_| RED);
+
+        let simplified = mesh.simplify(Simplification::to_max_error(1e-9));
+
+        // The flat parts can be simplified without introducing any error, but
+        // the peak must stay where it is.
+        assert!(simplified.triangles().count() < mesh.triangles().count());
+        assert!(simplified.vertices().any(|vertex| vertex == peak));
+
+        // Without a limit on the error, the peak is removed.
+        let simplified = mesh.simplify(Simplification::to_triangle_count(0));
+        assert!(simplified.vertices().all(|vertex| vertex != peak));
+    }
+
+    #[test]
+    fn boundary_and_color_edges_are_locked() {
+        let mesh = grid(10, |_| 0., |[x, _]| if x < 5 { RED } else { BLUE });
+
+        let simplified = mesh.simplify(Simplification::to_triangle_count(0));
+        assert!(simplified.triangles().count() < mesh.triangles().count());
+
+        let vertices = simplified.vertices().collect::<BTreeSet<_>>();
+        for vertex in mesh.vertices() {
+            let [x, y] = [vertex.x, vertex.y].map(Scalar::into_f64);
+
+            let is_boundary = x == 0. || x == 10. || y == 0. || y == 10.;
+            let is_color_edge = x == 5.;
+
+            if is_boundary || is_color_edge {
+                assert!(vertices.contains(&vertex), "{vertex:?} was removed");
+            }
+        }
+
+        // With the boundaries of the mesh and the color regions in place, the
+        // area of each color region doesn't change.
+        for color in [RED, BLUE] {
+            let difference = area(&simplified, color) - area(&mesh, color);
+            assert!(difference.abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn triangles_are_not_flipped() {
+        let mesh =
+            grid(10, |[x, y]| f64::from((x * 7 + y * 3) % 5) * 0.1, |_| RED);
+
+        let simplified = mesh.simplify(Simplification::to_triangle_count(0));
+        assert!(simplified.triangles().count() < mesh.triangles().count());
+
+        // All triangles of the grid face upwards. Flipping one would make it
+        // face downwards.
+        for triangle in simplified.triangles() {
+            let normal = normal(triangle.inner.points());
+            assert!(normal.z >= Scalar::ZERO, "{triangle:?} is flipped");
+        }
+    }
+
+    #[test]
+    fn faces_are_preserved() {
+        let mut mesh = Mesh::new();
+        for offset in [0., 20.] {
+            mesh.start_face();
+
+            for triangle in grid(10, |_| 0., |_| RED).triangles() {
+                let [a, b, c] = triangle
+                    .inner
+                    .points()
+                    .map(|point| point + [offset, 0., 0.]);
+                mesh.push_triangle([a, b, c], triangle.color);
+            }
+        }
+
+        let simplified = mesh.simplify(Simplification::to_triangle_count(0));
+        assert!(simplified.triangles().count() < mesh.triangles().count());
+
+        let faces = simplified.faces().collect::<Vec<_>>();
+        assert_eq!(faces.len(), 2);
+        for (face, x_range) in faces.into_iter().zip([0. ..=10., 20. ..=30.]) {
+            assert!(face.iter().all(|triangle| triangle
+                .inner
+                .points()
+                .iter()
+                .all(|point| x_range.contains(&point.x.into_f64()))));
+        }
+    }
+
+    /// Build a grid of `n` by `n` squares, each made up of two triangles
+    ///
+    /// The height of each vertex and the color of each square are defined by
+    /// the provided functions, based on their position in the grid.
+    fn grid(
+        n: u32,
+        height: impl Fn([u32; 2]) -> f64,
+        color: impl Fn([u32; 2]) -> Color,
+    ) -> Mesh<Point<3>> {
+        let point = |[x, y]: [u32; 2]| {
+            Point::from([f64::from(x), f64::from(y), height([x, y])])
+        };
+
+        let mut mesh = Mesh::new();
+
+        for x in 0..n {
+            for y in 0..n {
+                let [a, b, c, d] =
+                    [[x, y], [x + 1, y], [x + 1, y + 1], [x, y + 1]].map(point);
+                let color = color([x, y]);
+
+                mesh.push_triangle([a, b, c], color);
+                mesh.push_triangle([a, c, d], color);
+            }
+        }
+
+        mesh
+    }
+
+    fn area(mesh: &Mesh<Point<3>>, color: Color) -> f64 {
+        mesh.triangles()
+            .filter(|triangle| triangle.color == color)
+            .map(|triangle| {
+                normal(triangle.inner.points()).magnitude().into_f64() / 2.
+            })
+            .sum()
+    }
+}
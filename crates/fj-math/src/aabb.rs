@@ -1,7 +1,7 @@
 use parry2d_f64::bounding_volume::BoundingVolume as _;
 use parry3d_f64::bounding_volume::BoundingVolume as _;
 
-use super::{Point, Vector};
+use super::{Point, Scalar, Vector};
 
 /// An axis-aligned bounding box (AABB)
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
@@ -45,6 +45,27 @@ impl<const D: usize> Aabb<D> {
 
         true
     }
+
+    /// Determine whether the AABB intersects another
+    ///
+    /// AABBs that only touch are considered to intersect.
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..D).all(|i| {
+            self.min.coords.components[i] <= other.max.coords.components[i]
+                && other.min.coords.components[i]
+                    <= self.max.coords.components[i]
+        })
+    }
+
+    /// Compute an AABB that is larger by the provided amount in all directions
+    pub fn loosened(&self, amount: impl Into<Scalar>) -> Self {
+        let amount = amount.into();
+
+        Self {
+            min: self.min.coords.components.map(|c| c - amount).into(),
+            max: self.max.coords.components.map(|c| c + amount).into(),
+        }
+    }
 }
 
 impl Aabb<2> {
@@ -174,4 +195,16 @@ mod tests {
         assert!(!aabb.contains([0., 2.]));
         assert!(!aabb.contains([4., 2.]));
     }
+
+    #[test]
+    fn intersects() {
+        let aabb = Aabb::<2>::from_points([[1., 1.], [3., 3.]]);
+
+        assert!(aabb.intersects(&Aabb::<2>::from_points([[2., 2.], [4., 4.]])));
+        assert!(aabb.intersects(&Aabb::<2>::from_points([[3., 0.], [4., 1.]])));
+        assert!(!aabb.intersects(&Aabb::<2>::from_points([[4., 0.], [5., 5.]])));
+        assert!(aabb
+            .loosened(1.)
+            .intersects(&Aabb::<2>::from_points([[4., 0.], [5., 5.]])));
+    }
 }
@@ -0,0 +1,215 @@
+use crate::{Aabb, Scalar};
+
+/// A bounding volume hierarchy (BVH)
+///
+/// A spatial index over a collection of [`Aabb`]s. It can find the AABBs that
+/// intersect a given one, without comparing it to every single AABB in the
+/// collection.
+///
+/// The BVH doesn't store the objects that the AABBs belong to. AABBs are
+/// identified by their index in the sequence that the BVH was built from.
+#[derive(Clone, Debug, Default)]
+pub struct Bvh<const D: usize> {
+    nodes: Vec<Node<D>>,
+    items: Vec<(usize, Aabb<D>)>,
+}
+
+impl<const D: usize> Bvh<D> {
+    /// The maximum number of AABBs in a leaf node
+    const LEAF_SIZE: usize = 4;
+
+    /// Build a BVH from the provided AABBs
+    pub fn new(aabbs: impl IntoIterator<Item = Aabb<D>>) -> Self {
+        let mut items = aabbs.into_iter().enumerate().collect::<Vec<_>>();
+        let mut nodes = Vec::new();
+
+        if !items.is_empty() {
+            build(&mut items, 0, &mut nodes);
+        }
+
+        Self { nodes, items }
+    }
+
+    /// Access the number of AABBs in the BVH
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Indicate whether the BVH contains no AABBs
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Find the AABBs that intersect the provided one
+    ///
+    /// Returns the indices of the intersecting AABBs, in ascending order. AABBs
+    /// that only touch are considered to intersect, as defined by
+    /// [`Aabb::intersects`].
+    pub fn intersecting(&self, aabb: &Aabb<D>) -> Vec<usize> {
+        let mut indices = Vec::new();
+
+        let mut nodes = Vec::new();
+        if !self.nodes.is_empty() {
+            nodes.push(0);
+        }
+
+        while let Some(node) = nodes.pop() {
+            let node = &self.nodes[node];
+
+            if !node.aabb.intersects(aabb) {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Branch { left, right } => {
+                    nodes.extend([left, right]);
+                }
+                NodeKind::Leaf { start, end } => {
+                    indices.extend(
+                        self.items[start..end]
+                            .iter()
+                            .filter(|(_, item)| item.intersects(aabb))
+                            .map(|&(index, _)| index),
+                    );
+                }
+            }
+        }
+
+        indices.sort_unstable();
+        indices
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Node<const D: usize> {
+    aabb: Aabb<D>,
+    kind: NodeKind,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum NodeKind {
+    Branch { left: usize, right: usize },
+    Leaf { start: usize, end: usize },
+}
+
+/// Build the node for the provided items; return its index
+///
+/// `offset` is the position of `items` within all items of the BVH.
+fn build<const D: usize>(
+    items: &mut [(usize, Aabb<D>)],
+    offset: usize,
+    nodes: &mut Vec<Node<D>>,
+) -> usize {
+    let aabb = items
+        .iter()
+        .map(|&(_, aabb)| aabb)
+        .reduce(|a, b| merge(&a, &b))
+        .expect("Nodes are only built for at least one item");
+
+    let index = nodes.len();
+    nodes.push(Node {
+        aabb,
+        kind: NodeKind::Leaf {
+            start: offset,
+            end: offset + items.len(),
+        },
+    });
+
+    if items.len() <= Bvh::<D>::LEAF_SIZE {
+        return index;
+    }
+
+    // Split the items in half, along the axis in which their centers are
+    // spread out the most. Centers are doubled, which doesn't change their
+    // order, but saves us a division.
+    let center = |aabb: &Aabb<D>, axis: usize| {
+        aabb.min.coords.components[axis] + aabb.max.coords.components[axis]
+    };
+    let axis = (0..D)
+        .max_by_key(|&axis| {
+            let (min, max) = items.iter().fold(
+                (Scalar::MAX, -Scalar::MAX),
+                |(min, max), (_, aabb)| {
+                    let center = center(aabb, axis);
+                    (min.min(center), max.max(center))
+                },
+            );
+            max - min
+        })
+        .unwrap_or_default();
+
+    let mid = items.len() / 2;
+    items.select_nth_unstable_by_key(mid, |(_, aabb)| center(aabb, axis));
+
+    let (left_items, right_items) = items.split_at_mut(mid);
+    let left = build(left_items, offset, nodes);
+    let right = build(right_items, offset + mid, nodes);
+
+    nodes[index].kind = NodeKind::Branch { left, right };
+
+    index
+}
+
+fn merge<const D: usize>(a: &Aabb<D>, b: &Aabb<D>) -> Aabb<D> {
+    let mut merged = *a;
+
+    for i in 0..D {
+        let min = &mut merged.min.coords.components[i];
+        *min = (*min).min(b.min.coords.components[i]);
+
+        let max = &mut merged.max.coords.components[i];
+        *max = (*max).max(b.max.coords.components[i]);
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Aabb, Point};
+
+    use super::Bvh;
+
+    #[test]
+    fn intersecting() {
+        let aabbs = (0..10)
+            .flat_map(|x| (0..10).map(move |y| [x, y]))
+            .map(|[x, y]| {
+                let min = Point::from([f64::from(x), f64::from(y)]);
+                Aabb {
+                    min,
+                    max: min + [0.5, 0.5],
+                }
+            })
+            .collect::<Vec<_>>();
+        let bvh = Bvh::new(aabbs.iter().copied());
+
+        assert_eq!(bvh.len(), aabbs.len());
+
+        for query in [
+            Aabb::<2>::from_points([[0.25, 0.25], [0.75, 0.75]]),
+            Aabb::<2>::from_points([[2.5, 3.], [5.25, 4.25]]),
+            Aabb::<2>::from_points([[-1., -1.], [20., 20.]]),
+            Aabb::<2>::from_points([[0.6, 0.6], [0.9, 0.9]]),
+        ] {
+            let expected = aabbs
+                .iter()
+                .enumerate()
+                .filter(|(_, aabb)| aabb.intersects(&query))
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>();
+
+            assert_eq!(bvh.intersecting(&query), expected);
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let bvh = Bvh::<3>::new([]);
+
+        assert!(bvh.is_empty());
+        assert!(bvh
+            .intersecting(&Aabb::<3>::from_points([[0., 0., 0.], [1., 1., 1.]]))
+            .is_empty());
+    }
+}
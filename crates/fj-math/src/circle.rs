@@ -1,4 +1,4 @@
-use approx::AbsDiffEq;
+use std::fmt;
 
 use crate::{Aabb, Point, Scalar, Vector};
 
@@ -18,40 +18,53 @@ impl<const D: usize> Circle<D> {
     ///
     /// # Panics
     ///
-    /// Panics, if any of the following requirements are not met:
+    /// Panics, if any of the requirements of [`Circle::try_new`] are not met.
+    pub fn new(
+        center: impl Into<Point<D>>,
+        a: impl Into<Vector<D>>,
+        b: impl Into<Vector<D>>,
+    ) -> Self {
+        match Self::try_new(center, a, b) {
+            Ok(circle) => circle,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Construct a circle, if the provided vectors define one
+    ///
+    /// Returns an error, if any of the following requirements are not met:
     ///
     /// - The circle radius (defined by the length of `a` and `b`) must not be
     ///   zero.
     /// - `a` and `b` must be of equal length.
     /// - `a` and `b` must be perpendicular to each other.
-    pub fn new(
+    pub fn try_new(
         center: impl Into<Point<D>>,
         a: impl Into<Vector<D>>,
         b: impl Into<Vector<D>>,
-    ) -> Self {
+    ) -> Result<Self, InvalidCircle> {
         let center = center.into();
         let a = a.into();
         let b = b.into();
 
-        assert_eq!(
-            a.magnitude(),
-            b.magnitude(),
-            "`a` and `b` must be of equal length"
-        );
-        assert_ne!(
-            a.magnitude(),
-            Scalar::ZERO,
-            "circle radius must not be zero"
-        );
-        // Requiring the vector to be *precisely* perpendicular is not
-        // practical, because of numerical inaccuracy. This epsilon value seems
-        // seems to work for now, but maybe it needs to become configurable.
-        assert!(
-            a.dot(&b) < Scalar::default_epsilon(),
-            "`a` and `b` must be perpendicular to each other"
-        );
+        if a.magnitude() == Scalar::ZERO {
+            return Err(InvalidCircle::ZeroRadius);
+        }
 
-        Self { center, a, b }
+        // Requiring the vectors to be *precisely* of equal length and
+        // perpendicular is not practical, because of numerical inaccuracy.
+        // Circles that were transformed, or read from a file, would fail those
+        // checks. The tolerance is relative to the radius, to work regardless
+        // of scale.
+        let epsilon = a.magnitude() * Scalar::from_f64(RELATIVE_EPSILON);
+        if (a.magnitude() - b.magnitude()).abs() > epsilon {
+            return Err(InvalidCircle::UnequalAxes);
+        }
+        if a.dot(&b).abs() > a.magnitude() * epsilon {
+            return Err(InvalidCircle::NonPerpendicularAxes);
+        }
+
+        Ok(Self { center, a, b })
     }
 
     /// Construct a `Circle` from a center point and a radius
@@ -177,13 +190,47 @@ impl<const D: usize> approx::AbsDiffEq for Circle<D> {
     }
 }
 
+/// Returned by [`Circle::try_new`], if the vectors don't define a circle
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum InvalidCircle {
+    /// The circle radius is zero
+    ZeroRadius,
+
+    /// `a` and `b` are not of equal length
+    UnequalAxes,
+
+    /// `a` and `b` are not perpendicular to each other
+    NonPerpendicularAxes,
+}
+
+impl fmt::Display for InvalidCircle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            Self::ZeroRadius => "circle radius must not be zero",
+            Self::UnequalAxes => "`a` and `b` must be of equal length",
+            Self::NonPerpendicularAxes => {
+                "`a` and `b` must be perpendicular to each other"
+            }
+        };
+
+        write!(f, "{message}")
+    }
+}
+
+impl std::error::Error for InvalidCircle {}
+
+/// The tolerance for the requirements on `a` and `b`, relative to the radius
+///
+/// See [`Circle::try_new`].
+const RELATIVE_EPSILON: f64 = 1e-12;
+
 #[cfg(test)]
 mod tests {
     use std::f64::consts::{FRAC_PI_2, PI};
 
     use crate::{Point, Vector};
 
-    use super::Circle;
+    use super::{Circle, InvalidCircle};
 
     #[test]
     fn point_to_circle_coords() {
@@ -210,4 +257,49 @@ mod tests {
             Point::from([FRAC_PI_2 * 3.]),
         );
     }
+
+    #[test]
+    fn new_with_inaccurate_axes() {
+        // Axes that are off by a tiny amount relative to the radius, as they
+        // would be after a transformation, are accepted at any scale.
+        for radius in [1e-3, 1., 1e6] {
+            let a = Vector::from([radius, 0., 0.]);
+            let b = Vector::from([radius * 1e-14, radius * (1. + 1e-14), 0.]);
+
+            let circle = Circle::new(Point::origin(), a, b);
+            assert_eq!(circle.radius(), a.magnitude());
+        }
+    }
+
+    #[test]
+    fn try_new() {
+        let center = Point::<3>::origin();
+        let a = [1., 0., 0.];
+
+        assert!(Circle::try_new(center, a, [0., 1., 0.]).is_ok());
+        assert_eq!(
+            Circle::try_new(center, [0., 0., 0.], [0., 0., 0.]),
+            Err(InvalidCircle::ZeroRadius),
+        );
+        assert_eq!(
+            Circle::try_new(center, a, [0., 1.001, 0.]),
+            Err(InvalidCircle::UnequalAxes),
+        );
+        assert_eq!(
+            Circle::try_new(center, a, [0.6, 0.8, 0.]),
+            Err(InvalidCircle::NonPerpendicularAxes),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "must be of equal length")]
+    fn new_with_unequal_axes() {
+        Circle::new(Point::<3>::origin(), [1., 0., 0.], [0., 1.001, 0.]);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be perpendicular")]
+    fn new_with_non_perpendicular_axes() {
+        Circle::new(Point::<3>::origin(), [1., 0., 0.], [0.6, 0.8, 0.]);
+    }
 }
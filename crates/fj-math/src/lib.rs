@@ -33,6 +33,7 @@
 
 mod aabb;
 mod arc;
+mod bvh;
 mod circle;
 mod coordinates;
 mod line;
@@ -48,7 +49,8 @@ mod vector;
 pub use self::{
     aabb::Aabb,
     arc::Arc,
-    circle::Circle,
+    bvh::Bvh,
+    circle::{Circle, InvalidCircle},
     coordinates::{Uv, Xyz, T},
     line::Line,
     plane::Plane,
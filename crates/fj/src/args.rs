@@ -1,6 +1,10 @@
 use std::{num::ParseFloatError, path::PathBuf, str::FromStr};
 
-use fj_core::algorithms::approx::{InvalidTolerance, Tolerance};
+use fj_core::{
+    algorithms::approx::{InvalidTolerance, Tolerance},
+    validate::{Severity, ValidationCheck, ValidationConfig},
+};
+use fj_export::{ExportOptions, StlEncoding, Unit};
 use fj_math::Scalar;
 
 /// Standardized CLI for Fornjot models
@@ -27,6 +31,63 @@ pub struct Args {
     /// Ignore validation errors
     #[arg(short, long)]
     pub ignore_validation: bool,
+
+    /// Report failures of this validation check as warnings, not errors
+    ///
+    /// Can be specified multiple times. Checks are named in kebab-case, for
+    /// example `face-self-intersection`.
+    #[arg(long, value_name = "CHECK", value_parser = parse_check)]
+    pub validation_warn: Vec<ValidationCheck>,
+
+    /// Ignore failures of this validation check
+    ///
+    /// Can be specified multiple times. Checks are named in kebab-case, for
+    /// example `face-self-intersection`.
+    #[arg(long, value_name = "CHECK", value_parser = parse_check)]
+    pub validation_disable: Vec<ValidationCheck>,
+
+    /// Write a JSON report of the validation results to this path
+    ///
+    /// The model is still considered invalid, if there are validation errors.
+    #[arg(long, value_name = "PATH")]
+    pub validation_report: Option<PathBuf>,
+
+    /// Unit of the exported coordinates (um, mm, cm, m, in, or ft)
+    ///
+    /// The model's coordinates are interpreted as millimeters.
+    #[arg(long, value_parser = parse_unit, default_value = "mm")]
+    pub unit: Unit,
+
+    /// Factor to scale the exported model by, in addition to unit conversion
+    #[arg(long, value_parser = parse_scale, default_value_t = 1.)]
+    pub scale: f64,
+
+    /// Number of decimal places of coordinates in text-based export formats
+    #[arg(long, value_name = "DIGITS")]
+    pub precision: Option<u8>,
+
+    /// Don't export the mesh, if it has problems
+    ///
+    /// Problems include holes, non-manifold edges, inconsistent winding, and
+    /// degenerate or duplicate triangles.
+    #[arg(long)]
+    pub validate_mesh: bool,
+
+    /// Export STL files as ASCII instead of binary
+    #[arg(long)]
+    pub ascii_stl: bool,
+
+    /// Text in the header of exported binary STL files
+    #[arg(long, value_name = "TEXT", default_value = "")]
+    pub stl_header: String,
+
+    /// Name of the model, written to exported files that support it
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Author of the model, written to exported files that support it
+    #[arg(long)]
+    pub author: Option<String>,
 }
 
 impl Args {
@@ -37,6 +98,42 @@ impl Args {
     pub fn parse() -> Self {
         <Self as clap::Parser>::parse()
     }
+
+    /// The options for exporting the model, as specified by the arguments
+    pub fn export_options(&self) -> ExportOptions {
+        let stl_encoding = if self.ascii_stl {
+            StlEncoding::Ascii
+        } else {
+            StlEncoding::Binary
+        };
+
+        ExportOptions {
+            unit: self.unit,
+            scale: self.scale,
+            precision: self.precision,
+            stl_encoding,
+            stl_header: self.stl_header.clone(),
+            name: self.name.clone(),
+            author: self.author.clone(),
+        }
+    }
+
+    /// The validation configuration, as specified by the arguments
+    pub fn validation_config(&self) -> ValidationConfig {
+        let warn = self
+            .validation_warn
+            .iter()
+            .map(|&check| (check, Severity::Warning));
+        let disable = self
+            .validation_disable
+            .iter()
+            .map(|&check| (check, Severity::Disabled));
+
+        warn.chain(disable)
+            .fold(ValidationConfig::default(), |config, (check, severity)| {
+                config.with_severity(check, severity)
+            })
+    }
 }
 
 fn parse_tolerance(input: &str) -> Result<Tolerance, ArgsError> {
@@ -47,6 +144,35 @@ fn parse_tolerance(input: &str) -> Result<Tolerance, ArgsError> {
     Ok(tolerance)
 }
 
+fn parse_unit(input: &str) -> Result<Unit, ArgsError> {
+    let unit = match input {
+        "um" => Unit::Micron,
+        "mm" => Unit::Millimeter,
+        "cm" => Unit::Centimeter,
+        "m" => Unit::Meter,
+        "in" | "inch" => Unit::Inch,
+        "ft" => Unit::Foot,
+        _ => return Err(ArgsError::InvalidUnit(input.to_string())),
+    };
+
+    Ok(unit)
+}
+
+fn parse_check(input: &str) -> Result<ValidationCheck, ArgsError> {
+    ValidationCheck::from_name(input)
+        .ok_or_else(|| ArgsError::UnknownValidationCheck(input.to_string()))
+}
+
+fn parse_scale(input: &str) -> Result<f64, ArgsError> {
+    let scale = f64::from_str(input).map_err(ArgsError::ParseScale)?;
+
+    if !(scale.is_finite() && scale > 0.) {
+        return Err(ArgsError::InvalidScale(scale));
+    }
+
+    Ok(scale)
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ArgsError {
     #[error("Error parsing tolerance")]
@@ -54,4 +180,127 @@ pub enum ArgsError {
 
     #[error(transparent)]
     InvalidTolerance(#[from] InvalidTolerance),
+
+    #[error("Error parsing scale factor")]
+    ParseScale(#[source] ParseFloatError),
+
+    #[error("Unknown unit `{0}`; expected um, mm, cm, m, in, or ft")]
+    InvalidUnit(String),
+
+    #[error("Scale factor must be finite and positive, but is `{0}`")]
+    InvalidScale(f64),
+
+    #[error("Unknown validation check `{0}`")]
+    UnknownValidationCheck(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_core::validate::{Severity, ValidationCheck};
+    use fj_export::{StlEncoding, Unit};
+    use fj_math::Scalar;
+
+    use super::Args;
+
+    #[test]
+    fn defaults() -> Result<(), clap::Error> {
+        let args = parse(&[])?;
+
+        assert_eq!(args.export, None);
+        assert!(args.tolerance.is_none());
+        assert!(!args.ignore_validation);
+        assert!(!args.validate_mesh);
+        assert_eq!(args.unit, Unit::Millimeter);
+        assert_eq!(args.scale, 1.);
+        assert_eq!(args.export_options().stl_encoding, StlEncoding::Binary);
+
+        Ok(())
+    }
+
+    #[test]
+    fn export_arguments() -> Result<(), clap::Error> {
+        let args = parse(&[
+            "--export",
+            "model.stl",
+            "--tolerance",
+            "0.01",
+            "--validate-mesh",
+            "--unit",
+            "in",
+            "--scale",
+            "2",
+            "--precision",
+            "3",
+            "--ascii-stl",
+            "--name",
+            "model",
+        ])?;
+
+        assert_eq!(args.export, Some("model.stl".into()));
+        assert_eq!(
+            args.tolerance.map(|tolerance| tolerance.inner()),
+            Some(Scalar::from(0.01))
+        );
+        assert!(args.validate_mesh);
+
+        let options = args.export_options();
+        assert_eq!(options.unit, Unit::Inch);
+        assert_eq!(options.scale, 2.);
+        assert_eq!(options.precision, Some(3));
+        assert_eq!(options.stl_encoding, StlEncoding::Ascii);
+        assert_eq!(options.name.as_deref(), Some("model"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validation_arguments() -> Result<(), clap::Error> {
+        let args = parse(&[
+            "--validation-warn",
+            "face-self-intersection",
+            "--validation-disable",
+            "solid-vertices",
+            "--validation-disable",
+            "shell-orientation",
+        ])?;
+
+        let config = args.validation_config();
+        assert_eq!(
+            config.severity(ValidationCheck::FaceSelfIntersection),
+            Severity::Warning
+        );
+        assert_eq!(
+            config.severity(ValidationCheck::SolidVertices),
+            Severity::Disabled
+        );
+        assert_eq!(
+            config.severity(ValidationCheck::ShellOrientation),
+            Severity::Disabled
+        );
+        assert_eq!(
+            config.severity(ValidationCheck::ShellHalfEdgePairs),
+            Severity::Error
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        for args in [
+            &["--tolerance", "0"][..],
+            &["--tolerance", "x"],
+            &["--unit", "yd"],
+            &["--scale", "0"],
+            &["--scale", "inf"],
+            &["--validation-warn", "no-such-check"],
+            &["--validate-mesh", "true"],
+        ] {
+            assert!(parse(args).is_err(), "Expected error for {args:?}");
+        }
+    }
+
+    fn parse(args: &[&str]) -> Result<Args, clap::Error> {
+        <Args as clap::Parser>::try_parse_from(["model"].iter().chain(args))
+    }
 }
@@ -1,13 +1,19 @@
-use std::{error::Error as _, fmt, mem};
+use std::{
+    error::Error as _,
+    fmt,
+    fs::File,
+    io, mem,
+    path::{Path, PathBuf},
+};
 
 use fj_core::{
     algorithms::{
         approx::{InvalidTolerance, Tolerance},
         bounding_volume::BoundingVolume,
-        triangulate::Triangulate,
+        triangulate::{Triangulate, TriangulationError},
     },
     services::Services,
-    validate::ValidationErrors,
+    validate::{ValidationConfig, ValidationErrors},
 };
 use fj_interop::Model;
 use fj_math::{Aabb, Point, Scalar};
@@ -23,7 +29,13 @@ use crate::Args;
 ///
 /// This function is used by Fornjot's own testing infrastructure, but is useful
 /// beyond that, when using Fornjot directly to define a model.
-pub fn handle_model<M>(model: &M, services: Services) -> Result
+///
+/// `services` should come from an [`Instance`] that was constructed using
+/// [`Args::validation_config`], so checks that are disabled via the arguments
+/// don't run while the model is being defined.
+///
+/// [`Instance`]: fj_core::Instance
+pub fn handle_model<M>(model: &M, args: Args, services: Services) -> Result
 where
     for<'r> (&'r M, Tolerance): Triangulate,
     M: BoundingVolume<3>,
@@ -33,12 +45,25 @@ where
         .with(tracing_subscriber::EnvFilter::from_default_env())
         .init();
 
-    let args = Args::parse();
-
     if args.ignore_validation {
         mem::forget(services);
+    } else if let Some(path) = &args.validation_report {
+        write_validation_report(services, &args.validation_config(), path)?;
     } else {
-        services.drop_and_validate()?;
+        let warnings = services
+            .drop_and_validate_with_config(&args.validation_config())?;
+
+        for warning in warnings {
+            let mut message = warning.to_string();
+
+            let mut source = warning.source();
+            while let Some(err) = source {
+                message.push_str(&format!("\n\nCaused by:\n\t{err}"));
+                source = err.source();
+            }
+
+            tracing::warn!("Validation warning: {message}");
+        }
     }
 
     let aabb = model.aabb().unwrap_or(Aabb {
@@ -65,10 +90,17 @@ where
         Some(user_defined_tolerance) => user_defined_tolerance,
     };
 
-    let mesh = (model, tolerance).triangulate();
+    let mesh = (model, tolerance).triangulate()?;
 
-    if let Some(path) = args.export {
-        crate::export::export(&mesh, &path)?;
+    if let Some(path) = &args.export {
+        let options = args.export_options();
+        if args.validate_mesh {
+            crate::export::export_validated_with_options(
+                &mesh, path, &options,
+            )?;
+        } else {
+            crate::export::export_with_options(&mesh, path, &options)?;
+        }
         return Ok(());
     }
 
@@ -79,6 +111,30 @@ where
     Ok(())
 }
 
+fn write_validation_report(
+    services: Services,
+    config: &ValidationConfig,
+    path: &Path,
+) -> Result {
+    let report = services.drop_and_report(config);
+
+    File::create(path)
+        .and_then(|file| report.write_json(file))
+        .map_err(|err| Error::ValidationReport {
+            path: path.to_path_buf(),
+            err,
+        })?;
+
+    if !report.is_valid() {
+        return Err(Error::InvalidModel {
+            num_errors: report.errors.len(),
+            path: path.to_path_buf(),
+        });
+    }
+
+    Ok(())
+}
+
 /// Return value of [`handle_model`]
 pub type Result = std::result::Result<(), Error>;
 
@@ -101,9 +157,37 @@ pub enum Error {
     #[error(transparent)]
     Tolerance(#[from] InvalidTolerance),
 
+    /// Error triangulating model
+    #[error("Error triangulating model")]
+    Triangulation(#[from] TriangulationError),
+
     /// Unhandled validation errors
     #[error(transparent)]
     Validation(#[from] ValidationErrors),
+
+    /// Validation errors, which were written to a validation report
+    #[error(
+        "{num_errors} validation errors; see report at `{}`",
+        path.display()
+    )]
+    InvalidModel {
+        /// The number of validation errors
+        num_errors: usize,
+
+        /// The path of the validation report
+        path: PathBuf,
+    },
+
+    /// Error writing validation report
+    #[error("Error writing validation report to `{}`", path.display())]
+    ValidationReport {
+        /// The path that the report was supposed to be written to
+        path: PathBuf,
+
+        /// The error that occurred
+        #[source]
+        err: io::Error,
+    },
 }
 
 impl fmt::Debug for Error {
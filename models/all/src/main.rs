@@ -1,8 +1,14 @@
-use fj::handle_model;
+use fj::{core::operations::insert::Insert, handle_model};
 
 fn main() -> fj::Result {
-    let mut core = fj::core::Instance::new();
-    let model = all::model(&mut core);
-    handle_model(&model, core.services)?;
+    let args = fj::Args::parse();
+    let mut core =
+        fj::core::Instance::with_validation_config(args.validation_config());
+
+    // The solids that are merged into this model have been validated on their
+    // own. Insert the result, to also validate how they relate to each other.
+    let model = all::model(&mut core).insert(&mut core.services);
+
+    handle_model(&*model, args, core.services)?;
     Ok(())
 }
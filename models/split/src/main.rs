@@ -1,8 +1,10 @@
 use fj::handle_model;
 
 fn main() -> fj::Result {
-    let mut core = fj::core::Instance::new();
+    let args = fj::Args::parse();
+    let mut core =
+        fj::core::Instance::with_validation_config(args.validation_config());
     let model = split::model(1.0, 0.2, &mut core);
-    handle_model(&model, core.services)?;
+    handle_model(&model, args, core.services)?;
     Ok(())
 }